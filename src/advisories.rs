@@ -0,0 +1,332 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
+
+use crate::error::{Error, Result};
+
+/// The `[advisory]` table of a RustSec advisory
+#[derive(Debug, Clone, Deserialize)]
+pub struct AdvisoryMetadata {
+    pub id: String,
+    pub package: String,
+    pub date: Option<String>,
+    pub url: Option<String>,
+    pub title: Option<String>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub cvss: Option<String>,
+    /// `unmaintained`, `unsound` or `notice` for advisories that are not vulnerabilities
+    pub informational: Option<String>,
+    pub withdrawn: Option<String>,
+}
+
+/// The `[versions]` table of a RustSec advisory
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct AdvisoryVersions {
+    #[serde(default)]
+    pub patched: Vec<String>,
+    #[serde(default)]
+    pub unaffected: Vec<String>,
+}
+
+/// A RustSec advisory
+#[derive(Debug, Clone, Deserialize)]
+pub struct Advisory {
+    pub advisory: AdvisoryMetadata,
+    #[serde(default)]
+    pub versions: AdvisoryVersions,
+}
+
+impl Advisory {
+    /// Parse an advisory file: markdown with a fenced TOML front matter, or plain
+    /// TOML in older checkouts of the database
+    fn parse(content: &str) -> std::result::Result<Self, String> {
+        let Some(rest) = content.trim_start().strip_prefix("```toml") else {
+            return toml::from_str(content).map_err(|e| e.to_string());
+        };
+        let (front_matter, body) = rest
+            .split_once("\n```")
+            .ok_or_else(|| "unterminated TOML front matter".to_string())?;
+
+        let mut advisory: Advisory = toml::from_str(front_matter).map_err(|e| e.to_string())?;
+        if advisory.advisory.title.is_none() {
+            advisory.advisory.title = body
+                .lines()
+                .find_map(|line| line.strip_prefix("# "))
+                .map(|title| title.trim().to_string());
+        }
+        Ok(advisory)
+    }
+
+    /// Whether the given version of the package is affected
+    ///
+    /// A version is affected unless a `patched` or `unaffected` requirement admits it.
+    pub fn affects(&self, version: &semver::Version) -> bool {
+        !self
+            .versions
+            .patched
+            .iter()
+            .chain(&self.versions.unaffected)
+            .filter_map(|req| semver::VersionReq::parse(req).ok())
+            .any(|req| req.matches(version))
+    }
+
+    /// Severity rating derived from the CVSS vector, if there is one
+    pub fn severity(&self) -> Option<(f64, &'static str)> {
+        let score = cvss_base_score(self.advisory.cvss.as_deref()?)?;
+        let rating = match score {
+            0.0 => "none",
+            s if s < 4.0 => "low",
+            s if s < 7.0 => "medium",
+            s if s < 9.0 => "high",
+            _ => "critical",
+        };
+        Some((score, rating))
+    }
+}
+
+/// A local clone of the RustSec advisory database
+/// (<https://github.com/rustsec/advisory-db>)
+pub struct AdvisoryDatabase {
+    path: PathBuf,
+    advisories: HashMap<String, Vec<Advisory>>,
+}
+
+impl AdvisoryDatabase {
+    /// Default location, shared with `cargo audit`: `$CARGO_HOME/advisory-db`
+    pub fn default_path() -> Option<PathBuf> {
+        std::env::var_os("CARGO_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cargo")))
+            .map(|cargo_home| cargo_home.join("advisory-db"))
+    }
+
+    /// Load every advisory below `crates/` of the database; withdrawn advisories are skipped
+    pub fn open(path: &Path) -> Result<Self> {
+        let crates_dir = path.join("crates");
+        let crate_dirs = fs::read_dir(&crates_dir).map_err(|e| {
+            Error::NotFound(format!(
+                "No RustSec advisory database at {} ({}). Clone https://github.com/rustsec/advisory-db \
+                there or pass --advisory-db",
+                path.display(),
+                e
+            ))
+        })?;
+
+        let mut advisories: HashMap<String, Vec<Advisory>> = HashMap::new();
+        for file in crate_dirs
+            .flatten()
+            .filter_map(|dir| fs::read_dir(dir.path()).ok())
+            .flatten()
+            .flatten()
+        {
+            let file = file.path();
+            if !matches!(
+                file.extension().and_then(|e| e.to_str()),
+                Some("md" | "toml")
+            ) {
+                continue;
+            }
+            let parsed = fs::read_to_string(&file)
+                .map_err(|e| e.to_string())
+                .and_then(|content| Advisory::parse(&content));
+            match parsed {
+                Ok(advisory) if advisory.advisory.withdrawn.is_none() => advisories
+                    .entry(advisory.advisory.package.clone())
+                    .or_default()
+                    .push(advisory),
+                Ok(_) => {}
+                Err(e) => debug!("Skipping advisory {}: {}", file.display(), e),
+            }
+        }
+
+        info!(
+            "Loaded advisories for {} crates from {}",
+            advisories.len(),
+            path.display()
+        );
+        Ok(Self {
+            path: path.to_path_buf(),
+            advisories,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Advisories that affect a version of a crate
+    pub fn affecting(&self, name: &str, version: &semver::Version) -> Vec<&Advisory> {
+        self.advisories
+            .get(name)
+            .into_iter()
+            .flatten()
+            .filter(|advisory| advisory.affects(version))
+            .collect()
+    }
+}
+
+/// Base score of a CVSS v3.x vector, e.g. `CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H`
+///
+/// Follows the formulas of the CVSS v3.1 specification. Other CVSS versions yield `None`.
+pub fn cvss_base_score(vector: &str) -> Option<f64> {
+    let mut parts = vector.split('/');
+    if !parts.next()?.starts_with("CVSS:3") {
+        return None;
+    }
+    let metrics: HashMap<&str, &str> = parts.filter_map(|part| part.split_once(':')).collect();
+
+    let changed = match *metrics.get("S")? {
+        "U" => false,
+        "C" => true,
+        _ => return None,
+    };
+    let av = match *metrics.get("AV")? {
+        "N" => 0.85,
+        "A" => 0.62,
+        "L" => 0.55,
+        "P" => 0.2,
+        _ => return None,
+    };
+    let ac = match *metrics.get("AC")? {
+        "L" => 0.77,
+        "H" => 0.44,
+        _ => return None,
+    };
+    let pr = match (*metrics.get("PR")?, changed) {
+        ("N", _) => 0.85,
+        ("L", false) => 0.62,
+        ("L", true) => 0.68,
+        ("H", false) => 0.27,
+        ("H", true) => 0.5,
+        _ => return None,
+    };
+    let ui = match *metrics.get("UI")? {
+        "N" => 0.85,
+        "R" => 0.62,
+        _ => return None,
+    };
+    let impact_metric = |key: &str| match *metrics.get(key)? {
+        "H" => Some(0.56),
+        "L" => Some(0.22),
+        "N" => Some(0.0),
+        _ => None,
+    };
+    let (c, i, a) = (
+        impact_metric("C")?,
+        impact_metric("I")?,
+        impact_metric("A")?,
+    );
+
+    let iss = 1.0 - (1.0 - c) * (1.0 - i) * (1.0 - a);
+    let impact = if changed {
+        7.52 * (iss - 0.029) - 3.25 * (iss - 0.02f64).powi(15)
+    } else {
+        6.42 * iss
+    };
+    if impact <= 0.0 {
+        return Some(0.0);
+    }
+    let exploitability = 8.22 * av * ac * pr * ui;
+    let score = if changed {
+        1.08 * (impact + exploitability)
+    } else {
+        impact + exploitability
+    };
+    Some(round_up(score.min(10.0)))
+}
+
+/// The "Roundup" function of the CVSS v3.1 specification
+fn round_up(value: f64) -> f64 {
+    let scaled = (value * 100_000.0).round() as i64;
+    if scaled % 10_000 == 0 {
+        scaled as f64 / 100_000.0
+    } else {
+        (scaled / 10_000 + 1) as f64 / 10.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ADVISORY: &str = r#"```toml
+[advisory]
+id = "RUSTSEC-2099-0001"
+package = "demo"
+date = "2099-01-01"
+categories = ["memory-corruption"]
+aliases = ["CVE-2099-0001"]
+cvss = "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"
+
+[versions]
+patched = [">= 1.2.3, < 2.0.0", ">= 2.0.1"]
+unaffected = ["< 1.0.0"]
+```
+
+# Buffer overflow in `demo::parse`
+
+Details.
+"#;
+
+    #[test]
+    fn test_parse_advisory() {
+        let advisory = Advisory::parse(ADVISORY).unwrap();
+        assert_eq!(advisory.advisory.id, "RUSTSEC-2099-0001");
+        assert_eq!(
+            advisory.advisory.title.as_deref(),
+            Some("Buffer overflow in `demo::parse`")
+        );
+        assert_eq!(advisory.severity(), Some((9.8, "critical")));
+
+        let affects = |v: &str| advisory.affects(&semver::Version::parse(v).unwrap());
+        assert!(!affects("0.9.0"));
+        assert!(affects("1.2.2"));
+        assert!(!affects("1.2.3"));
+        assert!(affects("2.0.0"));
+        assert!(!affects("2.0.1"));
+    }
+
+    #[test]
+    fn test_open_database() -> Result<()> {
+        let database = AdvisoryDatabase::open(Path::new("tests/fixtures/advisory-db"))?;
+        let affecting = |name: &str, version: &str| -> Vec<String> {
+            database
+                .affecting(name, &semver::Version::parse(version).unwrap())
+                .iter()
+                .map(|advisory| advisory.advisory.id.clone())
+                .collect()
+        };
+
+        // The withdrawn advisory and the stray text file are skipped
+        assert_eq!(affecting("demo", "1.2.2"), vec!["RUSTSEC-2099-0001"]);
+        assert!(affecting("demo", "1.2.3").is_empty());
+        assert_eq!(affecting("old-thing", "0.1.0"), vec!["RUSTSEC-2099-0003"]);
+        assert!(affecting("unknown", "1.0.0").is_empty());
+
+        assert!(AdvisoryDatabase::open(Path::new("tests/fixtures/missing")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_cvss_base_score() {
+        let score = |v: &str| cvss_base_score(v);
+        assert_eq!(
+            score("CVSS:3.1/AV:N/AC:L/PR:L/UI:N/S:C/C:H/I:H/A:H"),
+            Some(9.9)
+        );
+        assert_eq!(
+            score("CVSS:3.0/AV:L/AC:H/PR:N/UI:R/S:U/C:L/I:N/A:N"),
+            Some(2.5)
+        );
+        assert_eq!(
+            score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:N/I:N/A:N"),
+            Some(0.0)
+        );
+        assert_eq!(score("CVSS:4.0/AV:N/AC:L/AT:N"), None);
+    }
+}
@@ -0,0 +1,538 @@
+use std::collections::BTreeMap;
+
+use crate::types::{ApiChange, DocumentationItem, SemverBump};
+
+/// Compare the public items of two releases, matched by path and kind, most
+/// severe changes first
+///
+/// Signatures are only compared when both releases have them. The public fields
+/// and variants of structs, enums and unions are compared one by one, as are the
+/// methods of traits.
+pub fn diff(old: &[DocumentationItem], new: &[DocumentationItem]) -> Vec<ApiChange> {
+    let old_items = index(old);
+    let new_items = index(new);
+
+    let mut changes = Vec::new();
+    let mut added: Vec<&DocumentationItem> = new_items
+        .iter()
+        .filter(|(key, _)| !old_items.contains_key(*key))
+        .map(|(_, item)| *item)
+        .collect();
+
+    for (key, old_item) in &old_items {
+        match new_items.get(key) {
+            Some(new_item) => changes.extend(changed(old_item, new_item)),
+            // An item whose kind changed is removed under one kind and added under another
+            None => match added.iter().position(|item| item.path == old_item.path) {
+                Some(position) => changes.push(kind_changed(old_item, added.remove(position))),
+                None => changes.push(change(
+                    old_item,
+                    "removed",
+                    SemverBump::Major,
+                    "removed from the public API".to_string(),
+                )),
+            },
+        }
+    }
+
+    for item in added {
+        let (severity, reason) = if item.kind == "required_method" {
+            (
+                SemverBump::Major,
+                "new required method, existing implementations of the trait must add it",
+            )
+        } else {
+            (SemverBump::Minor, "new public item")
+        };
+        changes.push(ApiChange {
+            new_signature: item.signature.clone(),
+            ..change(item, "added", severity, reason.to_string())
+        });
+    }
+
+    changes.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then_with(|| a.path.cmp(&b.path))
+    });
+    changes
+}
+
+/// The bump between two version numbers, `None` when either is not semver
+pub fn declared_bump(old_version: &str, new_version: &str) -> Option<SemverBump> {
+    let old = semver::Version::parse(old_version).ok()?;
+    let new = semver::Version::parse(new_version).ok()?;
+    let (low, high) = if old <= new { (old, new) } else { (new, old) };
+
+    // The leftmost non-zero component is the major one, the next the minor one
+    let levels = if low.major > 0 {
+        [
+            (low.major != high.major, SemverBump::Major),
+            (low.minor != high.minor, SemverBump::Minor),
+            (low.patch != high.patch, SemverBump::Patch),
+        ]
+    } else if low.minor > 0 {
+        [
+            (low.minor != high.minor || high.major > 0, SemverBump::Major),
+            (low.patch != high.patch, SemverBump::Minor),
+            (false, SemverBump::Patch),
+        ]
+    } else {
+        [
+            (
+                low.major != high.major || low.minor != high.minor,
+                SemverBump::Major,
+            ),
+            (low.patch != high.patch, SemverBump::Major),
+            (false, SemverBump::Patch),
+        ]
+    };
+
+    Some(
+        levels
+            .into_iter()
+            .find(|(differs, _)| *differs)
+            .map(|(_, bump)| bump)
+            .unwrap_or(if low == high {
+                SemverBump::None
+            } else {
+                // Only the pre-release or build metadata differs
+                SemverBump::Patch
+            }),
+    )
+}
+
+/// Items by path and kind, the first one wins for duplicates
+fn index(items: &[DocumentationItem]) -> BTreeMap<(&str, &str), &DocumentationItem> {
+    let mut index = BTreeMap::new();
+    for item in items {
+        index
+            .entry((item.path.as_str(), item.kind.as_str()))
+            .or_insert(item);
+    }
+    index
+}
+
+/// Signature and deprecation changes of an item present in both releases
+fn changed(old: &DocumentationItem, new: &DocumentationItem) -> Option<ApiChange> {
+    let (mut severity, mut reasons) = match (&old.signature, &new.signature) {
+        (Some(old_signature), Some(new_signature)) => {
+            signature_changes(&old.kind, old_signature, new_signature)
+        }
+        _ => (SemverBump::None, Vec::new()),
+    };
+    let signature_changed = !reasons.is_empty();
+
+    match (&old.deprecation, &new.deprecation) {
+        (None, Some(note)) => {
+            severity = severity.max(SemverBump::Minor);
+            reasons.push(format!("deprecated: {}", note));
+        }
+        (Some(_), None) => {
+            severity = severity.max(SemverBump::Patch);
+            reasons.push("no longer deprecated".to_string());
+        }
+        _ => {}
+    }
+
+    if reasons.is_empty() {
+        return None;
+    }
+    let mut api_change = change(old, "changed", severity, reasons.join("; "));
+    if signature_changed {
+        api_change.old_signature = old.signature.clone();
+        api_change.new_signature = new.signature.clone();
+    }
+    Some(api_change)
+}
+
+/// How the declaration of an item changed, with the bump each change requires
+fn signature_changes(kind: &str, old: &str, new: &str) -> (SemverBump, Vec<String>) {
+    if let (Some(old), Some(new)) = (Definition::parse(old), Definition::parse(new))
+        && matches!(kind, "struct" | "enum" | "union")
+    {
+        return definition_changes(kind, &old, &new);
+    }
+
+    if comparable_signature(kind, old) == comparable_signature(kind, new) {
+        return (SemverBump::None, Vec::new());
+    }
+    let reason = match kind {
+        "struct" | "enum" | "union" => "definition changed",
+        "trait" => "trait declaration changed: generics, bounds or supertraits differ",
+        _ => "signature changed",
+    };
+    (SemverBump::Major, vec![reason.to_string()])
+}
+
+/// Changes to the public fields or variants of a struct, enum or union
+///
+/// New fields only break code when the struct could be built with a literal,
+/// that is when it has no private fields; new variants only break exhaustive
+/// matches, which `#[non_exhaustive]` enums rule out.
+fn definition_changes(kind: &str, old: &Definition, new: &Definition) -> (SemverBump, Vec<String>) {
+    let mut severity = SemverBump::None;
+    let mut reasons = Vec::new();
+    let mut push = |bump: SemverBump, reason: String| {
+        severity = severity.max(bump);
+        reasons.push(reason);
+    };
+
+    if old.header != new.header {
+        push(
+            SemverBump::Major,
+            "declaration changed: generics or bounds differ".to_string(),
+        );
+    }
+    match (old.non_exhaustive, new.non_exhaustive) {
+        (false, true) => push(SemverBump::Major, "now #[non_exhaustive]".to_string()),
+        (true, false) => push(SemverBump::Minor, "no longer #[non_exhaustive]".to_string()),
+        _ => {}
+    }
+
+    let member = if kind == "enum" { "variant" } else { "field" };
+    for (name, old_member) in &old.members {
+        match new.members.get(name) {
+            None => push(SemverBump::Major, format!("{} `{}` removed", member, name)),
+            Some(new_member) if new_member != old_member => {
+                push(SemverBump::Major, format!("{} `{}` changed", member, name))
+            }
+            Some(_) => {}
+        }
+    }
+
+    let extensible = if kind == "enum" {
+        old.non_exhaustive
+    } else {
+        old.stripped
+    };
+    for name in new
+        .members
+        .keys()
+        .filter(|name| !old.members.contains_key(*name))
+    {
+        let bump = if extensible {
+            SemverBump::Minor
+        } else {
+            SemverBump::Major
+        };
+        push(bump, format!("{} `{}` added", member, name));
+    }
+
+    if kind != "enum" {
+        match (old.stripped, new.stripped) {
+            (false, true) => push(
+                SemverBump::Major,
+                "private fields added, the struct can no longer be built with a literal"
+                    .to_string(),
+            ),
+            (true, false) => push(SemverBump::Minor, "no private fields left".to_string()),
+            _ => {}
+        }
+    }
+    (severity, reasons)
+}
+
+/// The braced definition of a struct, enum or union, as rustdoc renders it
+struct Definition {
+    /// Everything before the braces: name, generics and where clause
+    header: String,
+    /// Public fields or variants by name
+    members: BTreeMap<String, String>,
+    /// Whether rustdoc left out private fields or hidden variants
+    stripped: bool,
+    non_exhaustive: bool,
+}
+
+impl Definition {
+    /// `None` for unit and tuple structs, which have no braced body
+    fn parse(signature: &str) -> Option<Self> {
+        let signature = signature.trim();
+        let (non_exhaustive, signature) = match signature.strip_prefix("#[non_exhaustive]") {
+            Some(rest) => (true, rest.trim_start()),
+            None => (false, signature),
+        };
+        let (header, body) = signature.split_once('{')?;
+        let body = body.strip_suffix('}')?;
+
+        let mut stripped = false;
+        let mut members = BTreeMap::new();
+        for member in split_members(body) {
+            let member = collapse_whitespace(member);
+            if member.starts_with("/*") || member.starts_with("//") {
+                stripped = true;
+            } else if !member.is_empty() {
+                members.insert(member_name(&member).to_string(), member);
+            }
+        }
+
+        Some(Self {
+            header: collapse_whitespace(header),
+            members,
+            stripped,
+            non_exhaustive,
+        })
+    }
+}
+
+/// Split a definition body at the commas between its members, not those nested
+/// in types or variant fields
+fn split_members(body: &str) -> Vec<&str> {
+    let mut members = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut previous = ' ';
+    for (i, c) in body.char_indices() {
+        match c {
+            '(' | '[' | '{' | '<' => depth += 1,
+            // `->` in function pointer types closes nothing
+            '>' if previous == '-' => {}
+            ')' | ']' | '}' | '>' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                members.push(&body[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+        previous = c;
+    }
+    members.push(&body[start..]);
+    members
+}
+
+/// Name of a field or variant: `port` of `pub port: u16`, `Io` of `Io(Error)`
+fn member_name(member: &str) -> &str {
+    let member = member.strip_prefix("pub ").unwrap_or(member);
+    member
+        .split(|c: char| matches!(c, ':' | '(' | '{' | '=') || c.is_whitespace())
+        .next()
+        .unwrap_or(member)
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// An item whose kind differs between the releases, such as a struct that became an enum
+fn kind_changed(old: &DocumentationItem, new: &DocumentationItem) -> ApiChange {
+    let (severity, reason) = match (old.kind.as_str(), new.kind.as_str()) {
+        ("required_method", "provided_method") => (
+            SemverBump::Minor,
+            "trait method now has a default implementation".to_string(),
+        ),
+        ("provided_method", "required_method") => (
+            SemverBump::Major,
+            "trait method lost its default implementation, implementations must add it".to_string(),
+        ),
+        (old_kind, new_kind) => (
+            SemverBump::Major,
+            format!("changed from {} to {}", old_kind, new_kind),
+        ),
+    };
+    ApiChange {
+        kind: new.kind.clone(),
+        old_signature: old.signature.clone(),
+        new_signature: new.signature.clone(),
+        ..change(old, "changed", severity, reason)
+    }
+}
+
+fn change(
+    item: &DocumentationItem,
+    change: &str,
+    severity: SemverBump,
+    reason: String,
+) -> ApiChange {
+    ApiChange {
+        path: item.path.clone(),
+        kind: item.kind.clone(),
+        change: change.to_string(),
+        severity,
+        reason,
+        old_signature: None,
+        new_signature: None,
+    }
+}
+
+/// A signature with whitespace collapsed; traits without their items, which are
+/// compared as items of their own
+fn comparable_signature(kind: &str, signature: &str) -> String {
+    let signature = match kind {
+        "trait" => signature
+            .split_once('{')
+            .map_or(signature, |(header, _)| header),
+        _ => signature,
+    };
+    collapse_whitespace(signature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(path: &str, kind: &str, signature: &str) -> DocumentationItem {
+        DocumentationItem {
+            name: path.rsplit("::").next().unwrap().to_string(),
+            kind: kind.to_string(),
+            path: path.to_string(),
+            signature: Some(signature.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let old = vec![
+            item(
+                "demo::spawn",
+                "function",
+                "pub fn spawn(name: &str) -> Handle",
+            ),
+            item("demo::visit", "function", "pub fn visit(path: &Path)"),
+            item(
+                "demo::Config",
+                "struct",
+                "pub struct Config { pub port: u16, }",
+            ),
+            item(
+                "demo::Read",
+                "trait",
+                "pub trait Read { fn read(&mut self) -> usize; }",
+            ),
+            item(
+                "demo::Read::read",
+                "required_method",
+                "fn read(&mut self) -> usize",
+            ),
+            item(
+                "demo::Read::size",
+                "required_method",
+                "fn size(&self) -> usize",
+            ),
+            item("demo::Error", "struct", "pub struct Error;"),
+            item(
+                "demo::Handle",
+                "struct",
+                "pub struct Handle {\n    pub workers: usize,\n    /* private fields */\n}",
+            ),
+            item(
+                "demo::Kind",
+                "enum",
+                "#[non_exhaustive]\npub enum Kind {\n    Io(Error),\n}",
+            ),
+        ];
+        let mut new = vec![
+            item(
+                "demo::spawn",
+                "function",
+                "pub fn spawn(name: &str)  -> Handle",
+            ),
+            item(
+                "demo::visit",
+                "function",
+                "pub fn visit(path: &Path, depth: usize)",
+            ),
+            item(
+                "demo::Config",
+                "struct",
+                "pub struct Config { pub port: u16, pub host: String, }",
+            ),
+            item(
+                "demo::Read",
+                "trait",
+                "pub trait Read { fn read(&mut self) -> usize; fn close(&mut self); }",
+            ),
+            item(
+                "demo::Read::read",
+                "required_method",
+                "fn read(&mut self) -> usize",
+            ),
+            item(
+                "demo::Read::size",
+                "provided_method",
+                "fn size(&self) -> usize",
+            ),
+            item(
+                "demo::Read::close",
+                "required_method",
+                "fn close(&mut self)",
+            ),
+            item("demo::Error", "enum", "pub enum Error { Io, }"),
+            item("demo::Runtime", "struct", "pub struct Runtime;"),
+            item(
+                "demo::Handle",
+                "struct",
+                "pub struct Handle {\n    pub workers: usize,\n    pub name: String,\n    /* private fields */\n}",
+            ),
+            item(
+                "demo::Kind",
+                "enum",
+                "#[non_exhaustive]\npub enum Kind {\n    Io(Error),\n    Parse { line: usize, column: usize },\n}",
+            ),
+        ];
+        new[0].deprecation = Some("since 1.1: use spawn_named".to_string());
+        // Documentation changes are not API changes
+        new[4].description = Some("Reads some bytes.".to_string());
+
+        let changes = diff(&old, &new);
+        let summary: Vec<(&str, &str, SemverBump)> = changes
+            .iter()
+            .map(|c| (c.path.as_str(), c.change.as_str(), c.severity))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("demo::Config", "changed", SemverBump::Major),
+                ("demo::Error", "changed", SemverBump::Major),
+                ("demo::Read::close", "added", SemverBump::Major),
+                ("demo::visit", "changed", SemverBump::Major),
+                ("demo::Handle", "changed", SemverBump::Minor),
+                ("demo::Kind", "changed", SemverBump::Minor),
+                ("demo::Read::size", "changed", SemverBump::Minor),
+                ("demo::Runtime", "added", SemverBump::Minor),
+                ("demo::spawn", "changed", SemverBump::Minor),
+            ]
+        );
+
+        assert_eq!(changes[0].reason, "field `host` added");
+        assert_eq!(changes[1].reason, "changed from struct to enum");
+        assert_eq!(changes[4].reason, "field `name` added");
+        assert_eq!(changes[5].reason, "variant `Parse` added");
+        assert_eq!(
+            changes[3].new_signature.as_deref(),
+            Some("pub fn visit(path: &Path, depth: usize)")
+        );
+        // Whitespace alone is no change, the deprecation is
+        assert_eq!(changes[8].reason, "deprecated: since 1.1: use spawn_named");
+        assert_eq!(changes[8].old_signature, None);
+
+        // Removing a variant is breaking even for #[non_exhaustive] enums
+        let removed = diff(&new, &old);
+        let kind = removed.iter().find(|c| c.path == "demo::Kind").unwrap();
+        assert_eq!(kind.severity, SemverBump::Major);
+        assert_eq!(kind.reason, "variant `Parse` removed");
+
+        // Removing an item is breaking
+        let runtime = removed.iter().find(|c| c.path == "demo::Runtime").unwrap();
+        assert_eq!(
+            (runtime.change.as_str(), runtime.severity),
+            ("removed", SemverBump::Major)
+        );
+    }
+
+    #[test]
+    fn test_declared_bump() {
+        assert_eq!(declared_bump("1.2.3", "2.0.0"), Some(SemverBump::Major));
+        assert_eq!(declared_bump("1.2.3", "1.3.0"), Some(SemverBump::Minor));
+        assert_eq!(declared_bump("1.2.3", "1.2.4"), Some(SemverBump::Patch));
+        assert_eq!(declared_bump("0.3.1", "0.4.0"), Some(SemverBump::Major));
+        assert_eq!(declared_bump("0.3.1", "0.3.2"), Some(SemverBump::Minor));
+        assert_eq!(declared_bump("0.0.1", "0.0.2"), Some(SemverBump::Major));
+        assert_eq!(
+            declared_bump("1.0.0-rc.1", "1.0.0"),
+            Some(SemverBump::Patch)
+        );
+        assert_eq!(declared_bump("1.0.0", "1.0.0"), Some(SemverBump::None));
+        assert_eq!(declared_bump("latest", "1.0.0"), None);
+    }
+}
@@ -0,0 +1,71 @@
+use std::path::PathBuf;
+
+use crate::http::RetryPolicy;
+use crate::license::LicensePolicy;
+use crate::rate_limit::RateLimitConfig;
+
+/// Runtime configuration shared by the MCP server and its clients
+#[derive(Debug, Clone, Default)]
+pub struct ServerConfig {
+    /// Answer every tool from local data only (index, cargo registry) without network access
+    pub offline: bool,
+    /// Retry, backoff and timeout settings for upstream HTTP requests
+    pub retry: RetryPolicy,
+    /// Client-side request rate limits per upstream host
+    pub rate_limit: RateLimitConfig,
+    /// Contact information (email or URL) appended to the default User-Agent
+    pub contact: Option<String>,
+    /// Complete User-Agent override, used as-is
+    pub user_agent: Option<String>,
+    /// Local clone of the RustSec advisory database, defaults to `$CARGO_HOME/advisory-db`
+    pub advisory_db: Option<PathBuf>,
+    /// Default allow/deny policy for license checks, tools may override it per call
+    pub license_policy: LicensePolicy,
+    /// Directory crate sources are unpacked into, defaults to `~/.cache/crates-mcp/src`
+    pub source_cache: Option<PathBuf>,
+}
+
+impl ServerConfig {
+    /// The User-Agent sent with every upstream request
+    ///
+    /// The crates.io data access policy asks automated clients to identify
+    /// themselves with a way to contact the operator.
+    pub fn user_agent(&self) -> String {
+        let base = format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION"));
+        match (&self.user_agent, &self.contact) {
+            (Some(user_agent), _) => user_agent.clone(),
+            (None, Some(contact)) => format!("{} ({})", base, contact),
+            (None, None) => base,
+        }
+    }
+
+    /// Whether the operator configured any way to identify themselves upstream
+    pub fn has_contact(&self) -> bool {
+        self.contact.is_some() || self.user_agent.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_agent() {
+        let mut config = ServerConfig::default();
+        assert!(!config.has_contact());
+        assert_eq!(
+            config.user_agent(),
+            format!("crates-mcp/{}", env!("CARGO_PKG_VERSION"))
+        );
+
+        config.contact = Some("ops@example.com".to_string());
+        assert!(config.has_contact());
+        assert_eq!(
+            config.user_agent(),
+            format!("crates-mcp/{} (ops@example.com)", env!("CARGO_PKG_VERSION"))
+        );
+
+        config.user_agent = Some("my-bot/1.0 (+https://example.com/bot)".to_string());
+        assert_eq!(config.user_agent(), "my-bot/1.0 (+https://example.com/bot)");
+    }
+}
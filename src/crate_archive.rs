@@ -0,0 +1,236 @@
+use flate2::read::GzDecoder;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Component, Path};
+use tracing::debug;
+
+use crate::error::{Error, Result};
+use crate::http::HttpClient;
+use crate::types::CrateFile;
+
+/// Largest `.crate` archive downloaded, the size limit of crates.io
+pub const MAX_CRATE_SIZE: u64 = 10 * 1024 * 1024;
+
+/// Largest total size of the files unpacked from one `.crate` archive
+const MAX_UNPACKED_SIZE: u64 = 256 * 1024 * 1024;
+
+/// Download the `.crate` archive of a crate version from crates.io
+pub async fn download(http_client: &HttpClient, name: &str, version: &str) -> Result<Vec<u8>> {
+    let crate_url = format!(
+        "https://static.crates.io/crates/{}/{}-{}.crate",
+        name, name, version
+    );
+    debug!("Downloading crate archive from: {}", crate_url);
+
+    let response = http_client.get(&crate_url).await?;
+
+    // Missing archives come back as 403 from the storage behind static.crates.io
+    let status = response.status();
+    if status == 404 || status == 403 {
+        return Err(Error::VersionNotFound {
+            name: name.to_string(),
+            version: version.to_string(),
+        });
+    }
+    if !status.is_success() {
+        return Err(Error::UpstreamStatus {
+            url: crate_url,
+            status: status.as_u16(),
+        });
+    }
+    if response
+        .content_length()
+        .is_some_and(|length| length > MAX_CRATE_SIZE)
+    {
+        return Err(Error::InvalidResponse(format!(
+            "The .crate archive of '{}' {} is larger than {} bytes",
+            name, version, MAX_CRATE_SIZE
+        )));
+    }
+
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| Error::InvalidResponse(format!("Failed to download crate archive: {}", e)))?;
+    Ok(bytes.to_vec())
+}
+
+/// Check a `.crate` archive against the hex SHA-256 checksum the index records for it
+pub fn verify_checksum(archive: &[u8], expected: &str, name: &str, version: &str) -> Result<()> {
+    let actual = to_hex(&Sha256::digest(archive));
+    if !actual.eq_ignore_ascii_case(expected) {
+        return Err(Error::InvalidResponse(format!(
+            "Checksum mismatch for the .crate archive of '{}' {}: expected {}, got {}",
+            name, version, expected, actual
+        )));
+    }
+    Ok(())
+}
+
+/// Unpack the files of a `.crate` archive into `dest`, without the
+/// `name-version/` directory they are packaged under
+///
+/// Only regular files are written, and paths escaping `dest` are skipped.
+pub fn unpack(archive: &[u8], dest: &Path) -> Result<()> {
+    let mut tar = tar::Archive::new(GzDecoder::new(archive));
+    let mut unpacked_size = 0;
+    for entry in tar.entries().map_err(invalid_archive)? {
+        let mut entry = entry.map_err(invalid_archive)?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let Some(path) = relative_path(&entry.path().map_err(invalid_archive)?) else {
+            continue;
+        };
+
+        unpacked_size += entry.size();
+        if unpacked_size > MAX_UNPACKED_SIZE {
+            return Err(Error::InvalidResponse(format!(
+                "The .crate archive unpacks to more than {} bytes",
+                MAX_UNPACKED_SIZE
+            )));
+        }
+
+        let target = dest.join(&path);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent).map_err(invalid_archive)?;
+        }
+        let mut file = fs::File::create(&target).map_err(invalid_archive)?;
+        io::copy(&mut entry, &mut file).map_err(invalid_archive)?;
+    }
+    Ok(())
+}
+
+/// Example files: everything below `examples/`
+pub fn examples(files: &[CrateFile]) -> Vec<CrateFile> {
+    files
+        .iter()
+        .filter(|file| file.path.starts_with("examples/"))
+        .cloned()
+        .collect()
+}
+
+/// Find an example by its path or by the name cargo runs it under, so
+/// `basic` matches `examples/basic.rs` as well as `examples/basic/main.rs`
+pub fn find_example<'a>(examples: &'a [CrateFile], example: &str) -> Option<&'a CrateFile> {
+    let example = example.trim().trim_start_matches("./");
+    let example = example.strip_prefix("examples/").unwrap_or(example);
+    let candidates = [
+        format!("examples/{}", example),
+        format!("examples/{}.rs", example),
+        format!("examples/{}/main.rs", example),
+    ];
+    candidates
+        .iter()
+        .find_map(|candidate| examples.iter().find(|file| file.path == *candidate))
+}
+
+/// Strip the `name-version/` directory every entry of a `.crate` is packaged under
+///
+/// `None` for the directory itself and for paths escaping it.
+fn relative_path(path: &Path) -> Option<String> {
+    let mut components = path.components();
+    components.next()?;
+    let relative = components.as_path();
+    if relative.as_os_str().is_empty()
+        || relative
+            .components()
+            .any(|component| !matches!(component, Component::Normal(_)))
+    {
+        return None;
+    }
+    Some(relative.to_string_lossy().replace('\\', "/"))
+}
+
+fn invalid_archive(e: io::Error) -> Error {
+    Error::InvalidResponse(format!("Failed to unpack crate archive: {}", e))
+}
+
+/// Lowercase hex encoding, as the index and the crates.io API write checksums
+pub fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+
+    fn build_archive(root: &str, files: &[(&str, &str)]) -> Vec<u8> {
+        let mut builder = tar::Builder::new(GzEncoder::new(Vec::new(), Compression::default()));
+        for (path, content) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(content.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(
+                    &mut header,
+                    format!("{}/{}", root, path),
+                    content.as_bytes(),
+                )
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap()
+    }
+
+    #[test]
+    fn test_verify_and_unpack() -> Result<()> {
+        let archive = build_archive(
+            "demo-1.0.0",
+            &[
+                ("Cargo.toml", "[package]\nname = \"demo\"\n"),
+                ("src/lib.rs", "pub fn run() {}\n"),
+                ("examples/basic.rs", "fn main() {\n    demo::run();\n}\n"),
+                ("examples/server/main.rs", "fn main() {}\n"),
+                ("examples/server/handler.rs", "pub fn handle() {}\n"),
+            ],
+        );
+
+        let checksum = to_hex(&Sha256::digest(&archive)).to_uppercase();
+        verify_checksum(&archive, &checksum, "demo", "1.0.0")?;
+        let mismatch = verify_checksum(&archive, &"0".repeat(64), "demo", "1.0.0").unwrap_err();
+        assert!(mismatch.to_string().contains("expected 0000"));
+
+        let dest = std::env::temp_dir().join(format!("crates-mcp-unpack-{}", std::process::id()));
+        unpack(&archive, &dest)?;
+        assert_eq!(
+            fs::read_to_string(dest.join("src/lib.rs")).map_err(invalid_archive)?,
+            "pub fn run() {}\n"
+        );
+        assert!(dest.join("examples/server/handler.rs").is_file());
+        fs::remove_dir_all(&dest).map_err(invalid_archive)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_example() {
+        let examples: Vec<CrateFile> = [
+            "examples/basic.rs",
+            "examples/server/handler.rs",
+            "examples/server/main.rs",
+        ]
+        .iter()
+        .map(|path| CrateFile {
+            path: path.to_string(),
+            size: 0,
+        })
+        .collect();
+
+        assert_eq!(
+            find_example(&examples, "basic").map(|file| file.path.as_str()),
+            Some("examples/basic.rs")
+        );
+        assert_eq!(
+            find_example(&examples, "server").map(|file| file.path.as_str()),
+            Some("examples/server/main.rs")
+        );
+        assert_eq!(
+            find_example(&examples, "examples/server/handler.rs").map(|file| file.path.as_str()),
+            Some("examples/server/handler.rs")
+        );
+        assert!(find_example(&examples, "missing").is_none());
+    }
+}
@@ -1,11 +1,34 @@
-use anyhow::{Context, Result};
-use crates_index::GitIndex;
-use reqwest::Client;
+use crates_index::{GitIndex, HashKind, SparseIndex};
+use regex::RegexBuilder;
 use serde::Deserialize;
-use std::path::PathBuf;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use tracing::{debug, error, info, warn};
 
-use crate::types::{CrateDependency, CrateInfo, CrateSearchResult, CrateVersion};
+use crate::advisories::AdvisoryDatabase;
+use crate::config::ServerConfig;
+use crate::crate_archive;
+use crate::error::{Error, Result};
+use crate::graph;
+use crate::http::HttpClient;
+use crate::license::{LicensePolicy, Verdict};
+use crate::local_registry::{self, LocalRegistry};
+use crate::lockfile::{self, LockedPackage};
+use crate::manifest::{self, ManifestDependency};
+use crate::resolver::{self, TreeOptions};
+use crate::source_cache::{self, FileFilter, GrepOptions, SourceCache};
+use crate::types::{
+    AdvisoryMatch, AdvisoryReport, CrateDependency, CrateExamples, CrateFiles, CrateInfo,
+    CrateSearchResult, CrateSourceFile, CrateVersion, DependencyTree, DuplicateCrate,
+    FeatureResolution, LicenseFinding, LicenseReport, LockedPackageReport, LockfileAudit,
+    ManifestAnalysis, ManifestDependencyReport, ManifestPackageReport, OutdatedDependency,
+    OutdatedPackage, OutdatedReport, ReverseDependencies, ReverseDependency, SourceSearch,
+    VersionRequirementMatch,
+};
+
+/// Largest part of a source file returned
+const MAX_FILE_SIZE: usize = 100 * 1024;
 
 #[derive(Deserialize)]
 struct CratesIoSearchResponse {
@@ -24,7 +47,6 @@ struct CratesIoSearchCrate {
 
 #[derive(Deserialize)]
 struct CratesIoSearchMeta {
-    #[allow(dead_code)]
     total: u64,
 }
 
@@ -57,25 +79,101 @@ struct CratesIoVersion {
     license: Option<String>,
 }
 
+#[derive(Deserialize)]
+struct CratesIoVersionResponse {
+    version: CratesIoVersionChecksum,
+}
+
+#[derive(Deserialize)]
+struct CratesIoVersionChecksum {
+    checksum: String,
+}
+
+#[derive(Deserialize)]
+struct CratesIoReverseDependenciesResponse {
+    dependencies: Vec<CratesIoReverseDependency>,
+    versions: Vec<CratesIoReverseDependencyVersion>,
+    meta: CratesIoSearchMeta,
+}
+
+#[derive(Deserialize)]
+struct CratesIoReverseDependency {
+    version_id: u64,
+    req: String,
+    optional: bool,
+    kind: String,
+    downloads: u64,
+}
+
+#[derive(Deserialize)]
+struct CratesIoReverseDependencyVersion {
+    id: u64,
+    #[serde(rename = "crate")]
+    crate_name: String,
+    num: String,
+}
+
 pub struct CratesClient {
-    pub(crate) http_client: Client,
+    pub(crate) http_client: HttpClient,
     pub(crate) git_index: Option<GitIndex>,
+    pub(crate) sparse_indexes: Vec<SparseIndex>,
+    pub(crate) local_registry: LocalRegistry,
+    pub(crate) offline: bool,
+    pub(crate) advisory_db: Option<PathBuf>,
+    /// The advisory database, loaded on first use and kept for the whole session
+    advisories: OnceLock<AdvisoryDatabase>,
+    source_cache: SourceCache,
 }
 
 impl CratesClient {
-    /// Create a new CratesClient with HTTP client and optional git index
-    pub async fn new() -> Result<Self> {
-        let http_client = Client::builder()
-            .user_agent(format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")))
-            .build()
-            .context("Failed to create HTTP client")?;
+    /// Create a new CratesClient with HTTP client and optional git and sparse indexes
+    pub async fn new(config: &ServerConfig, http_client: HttpClient) -> Result<Self> {
+        let git_index = if config.offline {
+            // Never clone the index when offline, only open an existing one
+            match GitIndex::try_new_cargo_default() {
+                Ok(index) => index,
+                Err(e) => {
+                    warn!("Failed to open local crates.io git index: {}", e);
+                    None
+                }
+            }
+        } else {
+            // Try to initialize git index with better error handling and recovery
+            Self::initialize_git_index_with_recovery().await
+        };
 
-        // Try to initialize git index with better error handling and recovery
-        let git_index = Self::initialize_git_index_with_recovery().await;
+        // The sparse index cache is populated by cargo itself and is read without fetching.
+        // Cargo 1.85 changed the directory hash, so look in both the new and the legacy location.
+        let sparse_indexes = [HashKind::Stable, HashKind::Legacy]
+            .iter()
+            .filter_map(|hash_kind| {
+                SparseIndex::from_url_with_hash_kind(crates_index::sparse::URL, hash_kind)
+                    .inspect_err(|e| debug!("Sparse index cache unavailable: {}", e))
+                    .ok()
+            })
+            .collect();
+
+        if config.offline {
+            info!("Running in offline mode - answering from local index and cargo registry only");
+        }
 
         Ok(Self {
             http_client,
             git_index,
+            sparse_indexes,
+            local_registry: LocalRegistry::discover(),
+            offline: config.offline,
+            advisory_db: config
+                .advisory_db
+                .clone()
+                .or_else(AdvisoryDatabase::default_path),
+            advisories: OnceLock::new(),
+            source_cache: SourceCache::new(
+                config
+                    .source_cache
+                    .clone()
+                    .unwrap_or_else(SourceCache::default_path),
+            ),
         })
     }
 
@@ -85,7 +183,7 @@ impl CratesClient {
         match GitIndex::new_cargo_default() {
             Ok(index) => {
                 info!("Successfully initialized crates.io git index");
-                return Some(index);
+                Some(index)
             }
             Err(e) => {
                 warn!("Failed to initialize crates.io git index: {}", e);
@@ -95,12 +193,17 @@ impl CratesClient {
                     info!("Attempting to recover from potential git index corruption...");
 
                     // Try to get the cargo registry path
-                    if let Some(registry_path) = Self::get_cargo_registry_path() {
+                    if let Some(registry_path) = LocalRegistry::get_cargo_registry_path() {
                         let index_path = registry_path.join("index/github.com-1ecc6299db9ec823");
 
                         if index_path.exists() {
-                            warn!("Found potentially corrupted index at: {}", index_path.display());
-                            warn!("If problems persist, consider manually deleting this directory and restarting");
+                            warn!(
+                                "Found potentially corrupted index at: {}",
+                                index_path.display()
+                            );
+                            warn!(
+                                "If problems persist, consider manually deleting this directory and restarting"
+                            );
 
                             // Attempt one more time after a brief delay
                             tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
@@ -117,65 +220,61 @@ impl CratesClient {
                     }
                 }
 
-                warn!("Git index unavailable - some features like dependency viewing will be limited");
+                warn!(
+                    "Git index unavailable - some features like dependency viewing will be limited"
+                );
                 info!("The server will continue to work with HTTP-only API access");
                 None
             }
         }
     }
 
-    /// Get the cargo registry path
-    fn get_cargo_registry_path() -> Option<PathBuf> {
-        if let Some(home) = std::env::var_os("HOME") {
-            let mut path = PathBuf::from(home);
-            path.push(".cargo/registry");
-            if path.exists() {
-                return Some(path);
-            }
+    /// Look up a crate in the git index, falling back to cargo's sparse index cache
+    fn index_crate(&self, name: &str) -> Result<crates_index::Crate> {
+        if self.git_index.is_none() && self.sparse_indexes.is_empty() {
+            return Err(Error::IndexUnavailable(
+                "Git index not available. Dependency viewing requires the local crates.io git index \
+                or cargo's sparse index cache. This may be due to a corrupted git index. Try deleting \
+                ~/.cargo/registry/index/github.com-1ecc6299db9ec823/ (or the equivalent on Windows) \
+                and restart the server to rebuild the index."
+                    .to_string(),
+            ));
         }
 
-        // Try Windows equivalent
-        if let Some(appdata) = std::env::var_os("APPDATA") {
-            let mut path = PathBuf::from(appdata);
-            // On Windows, cargo uses different paths, check common locations
-            if path.join(".cargo").exists() {
-                path.push(".cargo/registry");
-                if path.exists() {
-                    return Some(path);
-                }
-            }
+        if let Some(index_crate) = self.git_index.as_ref().and_then(|index| index.crate_(name)) {
+            return Ok(index_crate);
         }
 
-        // Try CARGO_HOME environment variable
-        if let Some(cargo_home) = std::env::var_os("CARGO_HOME") {
-            let mut path = PathBuf::from(cargo_home);
-            path.push("registry");
-            if path.exists() {
-                return Some(path);
-            }
+        if let Some(index_crate) = self
+            .sparse_indexes
+            .iter()
+            .find_map(|index| index.crate_from_cache(name).ok())
+        {
+            return Ok(index_crate);
         }
 
-        None
+        Err(Error::NotFound(format!(
+            "Crate '{}' not found in index",
+            name
+        )))
     }
 
     /// Helper method for making HTTP requests to crates.io API
     async fn make_crates_io_request(&self, url: &str) -> Result<reqwest::Response> {
         debug!("Making request to: {}", url);
 
-        let response = self
-            .http_client
-            .get(url)
-            .send()
-            .await
-            .with_context(|| format!("Failed to send request to {}", url))?;
+        let response = self.http_client.get(url).await?;
 
         if !response.status().is_success() {
             let status = response.status();
             if status == 404 {
-                return Err(anyhow::anyhow!("Resource not found at {}", url));
+                return Err(Error::NotFound(format!("Resource not found at {}", url)));
             }
             error!("Request failed with status: {} for URL: {}", status, url);
-            return Err(anyhow::anyhow!("Request failed: {}", status));
+            return Err(Error::UpstreamStatus {
+                url: url.to_string(),
+                status: status.as_u16(),
+            });
         }
 
         Ok(response)
@@ -191,11 +290,17 @@ impl CratesClient {
     ) -> Result<Vec<CrateSearchResult>> {
         // Input validation
         if query.trim().is_empty() {
-            return Err(anyhow::anyhow!("Search query cannot be empty"));
+            return Err(Error::InvalidInput(
+                "Search query cannot be empty".to_string(),
+            ));
         }
 
         let limit = limit.unwrap_or(10).min(100);
 
+        if self.offline {
+            return Ok(self.search_crates_offline(query, limit));
+        }
+
         // Add sort parameter to API query if sorting by downloads
         let mut query_params = format!("q={}", urlencoding::encode(query));
         if sort_by == "downloads" {
@@ -216,10 +321,9 @@ impl CratesClient {
         );
 
         let response = self.make_crates_io_request(&url).await?;
-        let search_response: CratesIoSearchResponse = response
-            .json()
-            .await
-            .context("Failed to parse search response")?;
+        let search_response: CratesIoSearchResponse = response.json().await.map_err(|e| {
+            Error::InvalidResponse(format!("Failed to parse search response: {}", e))
+        })?;
 
         let mut results: Vec<CrateSearchResult> = search_response
             .crates
@@ -235,7 +339,7 @@ impl CratesClient {
 
         // Apply additional sorting if needed (API sorting might not be sufficient)
         if sort_by == "downloads" {
-            results.sort_by(|a, b| b.downloads.cmp(&a.downloads));
+            results.sort_by_key(|c| std::cmp::Reverse(c.downloads));
         }
 
         // Limit to requested number after filtering
@@ -251,30 +355,70 @@ impl CratesClient {
         Ok(results)
     }
 
+    /// Search the crates already downloaded into the local cargo registry
+    ///
+    /// Download counts are unknown offline, so results are ordered by name and
+    /// the `sort_by`/`min_downloads` options do not apply.
+    fn search_crates_offline(&self, query: &str, limit: usize) -> Vec<CrateSearchResult> {
+        let mut matches = self.local_registry.search(query);
+        // An exact name match is the most relevant hit
+        matches.sort_by_key(|(name, _)| !name.eq_ignore_ascii_case(query));
+        matches.truncate(limit);
+
+        let results: Vec<CrateSearchResult> = matches
+            .into_iter()
+            .map(|(name, version)| {
+                let description = self
+                    .local_registry
+                    .read_package(&name, &version)
+                    .ok()
+                    .and_then(|p| p.description);
+                CrateSearchResult {
+                    name,
+                    max_version: version,
+                    description,
+                    downloads: 0,
+                }
+            })
+            .collect();
+
+        info!(
+            "Found {} locally downloaded crates for query '{}' (offline)",
+            results.len(),
+            query
+        );
+        results
+    }
+
     /// Get detailed information about a specific crate
     pub async fn get_crate_info(&self, name: &str) -> Result<CrateInfo> {
         // Input validation
         if name.trim().is_empty() {
-            return Err(anyhow::anyhow!("Crate name cannot be empty"));
+            return Err(Error::InvalidInput(
+                "Crate name cannot be empty".to_string(),
+            ));
+        }
+
+        if self.offline {
+            return self.get_crate_info_offline(name);
         }
 
         let url = format!("https://crates.io/api/v1/crates/{}", name);
         let response = self
             .make_crates_io_request(&url)
             .await
-            .with_context(|| format!("Failed to get info for crate '{}'", name))?;
+            .map_err(|e| e.or_not_found(|| format!("Crate '{}' not found on crates.io", name)))?;
 
-        let crate_response: CratesIoCrateResponse = response
-            .json()
-            .await
-            .context("Failed to parse crate info response")?;
+        let crate_response: CratesIoCrateResponse = response.json().await.map_err(|e| {
+            Error::InvalidResponse(format!("Failed to parse crate info response: {}", e))
+        })?;
 
         let latest_version = crate_response
             .versions
             .iter()
             .find(|v| !v.yanked)
             .or_else(|| crate_response.versions.first())
-            .context("No versions found for crate")?;
+            .ok_or_else(|| Error::NotFound(format!("No versions found for crate '{}'", name)))?;
 
         // Try to get additional metadata from git index
         let (authors, keywords, categories, license) = match &self.git_index {
@@ -322,6 +466,72 @@ impl CratesClient {
         Ok(crate_info)
     }
 
+    /// Build crate info from the index and the manifest of a locally downloaded copy
+    fn get_crate_info_offline(&self, name: &str) -> Result<CrateInfo> {
+        let local_versions = self.local_registry.downloaded_versions(name);
+
+        let version = match self.index_crate(name) {
+            Ok(index_crate) => index_crate
+                .highest_normal_version()
+                .unwrap_or_else(|| index_crate.highest_version())
+                .version()
+                .to_string(),
+            Err(e) => local_versions.first().cloned().ok_or_else(|| {
+                Error::NotFound(format!(
+                    "Crate '{}' is not available offline: not in the local cargo registry ({})",
+                    name, e
+                ))
+            })?,
+        };
+
+        // Prefer the manifest of the exact version, otherwise the newest downloaded copy
+        let package = self
+            .local_registry
+            .read_package(name, &version)
+            .ok()
+            .or_else(|| {
+                local_versions
+                    .iter()
+                    .find_map(|v| self.local_registry.read_package(name, v).ok())
+            });
+
+        let crate_info = match package {
+            Some(package) => CrateInfo {
+                name: name.to_string(),
+                version,
+                description: package.description,
+                documentation: package.documentation,
+                homepage: package.homepage,
+                repository: package.repository,
+                license: package.license,
+                authors: package.authors,
+                keywords: package.keywords,
+                categories: package.categories,
+                downloads: 0,
+                created_at: String::new(),
+                updated_at: String::new(),
+            },
+            None => CrateInfo {
+                name: name.to_string(),
+                version,
+                description: None,
+                documentation: None,
+                homepage: None,
+                repository: None,
+                license: None,
+                authors: vec![],
+                keywords: vec![],
+                categories: vec![],
+                downloads: 0,
+                created_at: String::new(),
+                updated_at: String::new(),
+            },
+        };
+
+        info!("Retrieved info for crate '{}' (offline)", name);
+        Ok(crate_info)
+    }
+
     /// Get version history for a crate
     pub async fn get_crate_versions(
         &self,
@@ -330,19 +540,24 @@ impl CratesClient {
     ) -> Result<Vec<CrateVersion>> {
         // Input validation
         if name.trim().is_empty() {
-            return Err(anyhow::anyhow!("Crate name cannot be empty"));
+            return Err(Error::InvalidInput(
+                "Crate name cannot be empty".to_string(),
+            ));
+        }
+
+        if self.offline {
+            return self.get_crate_versions_offline(name, limit);
         }
 
         let url = format!("https://crates.io/api/v1/crates/{}", name);
         let response = self
             .make_crates_io_request(&url)
             .await
-            .with_context(|| format!("Failed to get versions for crate '{}'", name))?;
+            .map_err(|e| e.or_not_found(|| format!("Crate '{}' not found on crates.io", name)))?;
 
-        let crate_response: CratesIoCrateResponse = response
-            .json()
-            .await
-            .context("Failed to parse crate versions response")?;
+        let crate_response: CratesIoCrateResponse = response.json().await.map_err(|e| {
+            Error::InvalidResponse(format!("Failed to parse crate versions response: {}", e))
+        })?;
 
         let mut versions: Vec<CrateVersion> = crate_response
             .versions
@@ -364,33 +579,67 @@ impl CratesClient {
         Ok(versions)
     }
 
-    /// Get dependencies for a specific version of a crate
-    /// Note: Requires git index to be available
+    /// Build the version history from the index, newest first
+    ///
+    /// The index does not record publish dates or download counts, so those are left empty.
+    fn get_crate_versions_offline(
+        &self,
+        name: &str,
+        limit: Option<usize>,
+    ) -> Result<Vec<CrateVersion>> {
+        let index_crate = self.index_crate(name)?;
+
+        let mut versions: Vec<CrateVersion> = index_crate
+            .versions()
+            .iter()
+            .rev()
+            .map(|v| CrateVersion {
+                num: v.version().to_string(),
+                created_at: String::new(),
+                downloads: 0,
+                features: serde_json::to_value(v.features()).unwrap_or_default(),
+                yanked: v.is_yanked(),
+            })
+            .collect();
+
+        if let Some(limit) = limit {
+            versions.truncate(limit);
+        }
+
+        info!(
+            "Retrieved {} versions for crate '{}' (offline)",
+            versions.len(),
+            name
+        );
+        Ok(versions)
+    }
+
+    /// Get dependencies for a specific version of a crate, along with the version
+    /// they were read from
+    /// Note: Requires the git index or cargo's sparse index cache to be available
     pub fn get_crate_dependencies(
         &self,
         name: &str,
         version: Option<&str>,
-    ) -> Result<Vec<CrateDependency>> {
+    ) -> Result<(String, Vec<CrateDependency>)> {
         // Input validation
         if name.trim().is_empty() {
-            return Err(anyhow::anyhow!("Crate name cannot be empty"));
+            return Err(Error::InvalidInput(
+                "Crate name cannot be empty".to_string(),
+            ));
         }
 
-        let git_index = self.git_index.as_ref().context(
-            "Git index not available. Dependency viewing requires the local crates.io git index. \
-            This may be due to a corrupted git index. Try deleting \
-            ~/.cargo/registry/index/github.com-1ecc6299db9ec823/ (or the equivalent on Windows) \
-            and restart the server to rebuild the index."
-        )?;
-
-        let index_crate = git_index.crate_(name).context("Crate not found in index")?;
+        let index_crate = self.index_crate(name)?;
 
         let version_info = match version {
             Some(v) => index_crate
                 .versions()
                 .iter()
                 .find(|ver| ver.version() == v)
-                .context("Version not found")?,
+                .ok_or_else(|| Error::VersionNotFound {
+                    name: name.to_string(),
+                    version: v.to_string(),
+                })?,
             None => index_crate.highest_version(),
         };
 
@@ -414,18 +663,1264 @@ impl CratesClient {
             name,
             version_info.version()
         );
-        Ok(dependencies)
+        Ok((version_info.version().to_string(), dependencies))
+    }
+
+    /// List the published versions of a crate that satisfy a version requirement
+    ///
+    /// Pre-releases only match requirements that mention a pre-release of the same
+    /// version; those left out by that rule are reported separately.
+    /// Note: Requires the git index or cargo's sparse index cache to be available
+    pub fn match_version_requirement(
+        &self,
+        name: &str,
+        requirement: &str,
+    ) -> Result<VersionRequirementMatch> {
+        // Input validation
+        if name.trim().is_empty() {
+            return Err(Error::InvalidInput(
+                "Crate name cannot be empty".to_string(),
+            ));
+        }
+        let req = semver::VersionReq::parse(requirement).map_err(|e| {
+            Error::InvalidInput(format!(
+                "Invalid version requirement '{}': {}",
+                requirement, e
+            ))
+        })?;
+
+        let index_crate = self.index_crate(name)?;
+        Ok(requirement_match(&index_crate, requirement, &req))
+    }
+
+    /// Resolve which features, optional dependencies and dependency features a
+    /// feature selection activates
+    /// Note: Requires the git index or cargo's sparse index cache to be available
+    pub fn resolve_features(
+        &self,
+        name: &str,
+        version: Option<&str>,
+        features: &[String],
+        default_features: bool,
+    ) -> Result<FeatureResolution> {
+        // Input validation
+        if name.trim().is_empty() {
+            return Err(Error::InvalidInput(
+                "Crate name cannot be empty".to_string(),
+            ));
+        }
+
+        let index_crate = self.index_crate(name)?;
+        let version_info = resolver::find_version(&index_crate, version)?;
+        let resolution = resolver::resolve_features(version_info, features, default_features);
+
+        info!(
+            "Resolved {} features for crate '{}' version '{}'",
+            resolution.features.len(),
+            name,
+            resolution.version
+        );
+        Ok(resolution)
+    }
+
+    /// Resolve the transitive dependency tree of a crate version against the index
+    /// Note: Requires the git index or cargo's sparse index cache to be available
+    pub fn get_dependency_tree(
+        &self,
+        name: &str,
+        version: Option<&str>,
+        options: &TreeOptions,
+    ) -> Result<DependencyTree> {
+        // Input validation
+        if name.trim().is_empty() {
+            return Err(Error::InvalidInput(
+                "Crate name cannot be empty".to_string(),
+            ));
+        }
+
+        let tree = resolver::resolve_tree(|n| self.index_crate(n), name, version, options)?;
+
+        info!(
+            "Resolved {} crates and {} edges for '{}'",
+            tree.nodes.len(),
+            tree.edges.len(),
+            tree.root
+        );
+        Ok(tree)
+    }
+
+    /// Check every dependency of a local Cargo.toml or workspace against the index
+    ///
+    /// Nothing about the manifest leaves the machine: versions come from the index,
+    /// licenses from the local registry and, unless offline, from crates.io by crate name.
+    pub async fn analyze_manifest(&self, path: &Path) -> Result<ManifestAnalysis> {
+        let packages = manifest::load_packages(path)?;
+        let mut licenses = HashMap::new();
+
+        let mut reports = Vec::new();
+        for package in packages {
+            let mut dependencies = Vec::new();
+            for dep in &package.dependencies {
+                dependencies.push(self.analyze_dependency(dep, &mut licenses).await);
+            }
+            reports.push(ManifestPackageReport {
+                name: package.name,
+                version: package.version,
+                manifest_path: package.manifest_path.display().to_string(),
+                dependencies,
+            });
+        }
+
+        info!(
+            "Analyzed {} packages of manifest {}",
+            reports.len(),
+            path.display()
+        );
+        Ok(ManifestAnalysis {
+            manifest: path.display().to_string(),
+            packages: reports,
+        })
+    }
+
+    async fn analyze_dependency(
+        &self,
+        dep: &ManifestDependency,
+        licenses: &mut HashMap<String, HashMap<String, Option<String>>>,
+    ) -> ManifestDependencyReport {
+        let mut report = ManifestDependencyReport {
+            name: dep.name.clone(),
+            package: dep.package.clone(),
+            kind: dep.kind.clone(),
+            target: dep.target.clone(),
+            optional: dep.optional,
+            source: dep.source.clone(),
+            version_req: dep.version_req.clone(),
+            latest_version: None,
+            resolved_version: None,
+            requirement_admits_latest: None,
+            yanked: None,
+            license: None,
+            note: None,
+        };
+
+        if dep.source != "registry" {
+            report.note = Some(format!("{} dependency, not looked up", dep.source));
+            return report;
+        }
+        if let Some(registry) = &dep.registry {
+            report.note = Some(format!("From registry '{}', not looked up", registry));
+            return report;
+        }
+
+        let index_crate = match self.index_crate(&dep.package) {
+            Ok(index_crate) => index_crate,
+            Err(e) => {
+                report.note = Some(e.to_string());
+                return report;
+            }
+        };
+
+        let latest = index_crate
+            .highest_normal_version()
+            .unwrap_or_else(|| index_crate.highest_version());
+        report.latest_version = Some(latest.version().to_string());
+
+        // A registry dependency without a version means any version
+        let req = dep.version_req.as_deref().unwrap_or("*");
+        let resolved = match semver::VersionReq::parse(req) {
+            Ok(req) => {
+                report.requirement_admits_latest = semver::Version::parse(latest.version())
+                    .ok()
+                    .map(|latest| req.matches(&latest));
+                resolver::highest_match(&index_crate, &req)
+            }
+            Err(e) => {
+                report.note = Some(format!("Invalid version requirement '{}': {}", req, e));
+                None
+            }
+        };
+        if let Some(resolved) = resolved {
+            report.resolved_version = Some(resolved.version().to_string());
+            report.yanked = Some(resolved.is_yanked());
+        } else if report.note.is_none() {
+            report.note = Some("No published version matches the requirement".to_string());
+        }
+
+        let version = resolved.unwrap_or(latest).version();
+        report.license = self.crate_license(&dep.package, version, licenses).await;
+        report
+    }
+
+    /// License of a crate version, from the local registry or crates.io
+    ///
+    /// crates.io answers are cached per crate in `licenses`, since one request
+    /// returns the license of every version.
+    async fn crate_license(
+        &self,
+        name: &str,
+        version: &str,
+        licenses: &mut HashMap<String, HashMap<String, Option<String>>>,
+    ) -> Option<String> {
+        if let Ok(package) = self.local_registry.read_package(name, version) {
+            return package.license;
+        }
+        if self.offline {
+            return None;
+        }
+
+        if !licenses.contains_key(name) {
+            let url = format!("https://crates.io/api/v1/crates/{}", name);
+            let versions = match self.make_crates_io_request(&url).await {
+                Ok(response) => response
+                    .json::<CratesIoCrateResponse>()
+                    .await
+                    .map(|crate_response| {
+                        crate_response
+                            .versions
+                            .into_iter()
+                            .map(|v| (v.num, v.license))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                Err(e) => {
+                    warn!("Failed to fetch license of crate '{}': {}", name, e);
+                    HashMap::new()
+                }
+            };
+            licenses.insert(name.to_string(), versions);
+        }
+
+        licenses.get(name)?.get(version).cloned().flatten()
+    }
+
+    /// Audit a Cargo.lock: yanked versions, how far each crates.io package is behind
+    /// its newest compatible release, and crates locked at several major versions
+    /// Note: Requires the git index or cargo's sparse index cache to be available
+    pub fn audit_lockfile(&self, path: &Path, only_issues: bool) -> Result<LockfileAudit> {
+        let lock = lockfile::load(path)?;
+        let crates_io: Vec<&LockedPackage> = lock
+            .packages
+            .iter()
+            .filter(|p| p.is_from_crates_io())
+            .collect();
+
+        let mut packages = Vec::new();
+        for package in &crates_io {
+            packages.push(self.audit_locked_package(package)?);
+        }
+        let duplicates = duplicate_crates(&crates_io);
+
+        let yanked: Vec<String> = packages
+            .iter()
+            .filter(|p| p.yanked == Some(true))
+            .map(|p| format!("{}@{}", p.name, p.version))
+            .collect();
+        let crates_io_packages = packages.len();
+        if only_issues {
+            packages.retain(|p| {
+                p.yanked == Some(true)
+                    || p.compatible_versions_behind
+                        .is_some_and(|behind| behind > 0)
+                    || p.note.is_some()
+            });
+        }
+
+        info!(
+            "Audited {} crates.io packages of {}: {} yanked, {} duplicated crates",
+            crates_io_packages,
+            path.display(),
+            yanked.len(),
+            duplicates.len()
+        );
+        Ok(LockfileAudit {
+            lockfile: lockfile::lockfile_path(path)?.display().to_string(),
+            format_version: lock.version,
+            total_packages: lock.packages.len(),
+            crates_io_packages,
+            yanked,
+            duplicates,
+            packages,
+        })
+    }
+
+    fn audit_locked_package(&self, package: &LockedPackage) -> Result<LockedPackageReport> {
+        match self.index_crate(&package.name) {
+            Ok(index_crate) => Ok(audit_against_index(package, &index_crate)),
+            Err(Error::NotFound(message)) => Ok(LockedPackageReport {
+                note: Some(message),
+                ..locked_package_report(package)
+            }),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Compare the dependencies of a published crate version with the newest releases
+    /// Note: Requires the git index or cargo's sparse index cache to be available
+    pub fn get_outdated_dependencies(
+        &self,
+        name: &str,
+        version: Option<&str>,
+        only_outdated: bool,
+    ) -> Result<OutdatedReport> {
+        // Input validation
+        if name.trim().is_empty() {
+            return Err(Error::InvalidInput(
+                "Crate name cannot be empty".to_string(),
+            ));
+        }
+
+        let index_crate = self.index_crate(name)?;
+        let version_info = resolver::find_version(&index_crate, version)?;
+
+        let mut dependencies = Vec::new();
+        for dep in version_info.dependencies() {
+            let mut outdated = OutdatedDependency {
+                name: dep.name().to_string(),
+                package: dep.crate_name().to_string(),
+                kind: format!("{:?}", dep.kind()).to_lowercase(),
+                target: dep.target().map(|t| t.to_string()),
+                optional: dep.is_optional(),
+                version_req: dep.requirement().to_string(),
+                newest_matching: None,
+                newest_version: None,
+                outdated: false,
+                breaking_upgrade: false,
+                note: None,
+            };
+            self.check_outdated(&mut outdated)?;
+            dependencies.push(outdated);
+        }
+
+        let package = OutdatedPackage {
+            name: name.to_string(),
+            version: Some(version_info.version().to_string()),
+            dependencies,
+        };
+        Ok(outdated_report(
+            format!("{}@{}", name, version_info.version()),
+            vec![package],
+            only_outdated,
+        ))
+    }
+
+    /// Compare the registry dependencies of a local Cargo.toml or workspace with the
+    /// newest releases
+    /// Note: Requires the git index or cargo's sparse index cache to be available
+    pub fn get_outdated_manifest_dependencies(
+        &self,
+        path: &Path,
+        only_outdated: bool,
+    ) -> Result<OutdatedReport> {
+        let mut packages = Vec::new();
+        for package in manifest::load_packages(path)? {
+            let mut dependencies = Vec::new();
+            for dep in package.dependencies {
+                if dep.source != "registry" || dep.registry.is_some() {
+                    continue;
+                }
+                let mut outdated = OutdatedDependency {
+                    name: dep.name,
+                    package: dep.package,
+                    kind: dep.kind,
+                    target: dep.target,
+                    optional: dep.optional,
+                    version_req: dep.version_req.unwrap_or_else(|| "*".to_string()),
+                    newest_matching: None,
+                    newest_version: None,
+                    outdated: false,
+                    breaking_upgrade: false,
+                    note: None,
+                };
+                self.check_outdated(&mut outdated)?;
+                dependencies.push(outdated);
+            }
+            packages.push(OutdatedPackage {
+                name: package.name,
+                version: package.version,
+                dependencies,
+            });
+        }
+
+        Ok(outdated_report(
+            path.display().to_string(),
+            packages,
+            only_outdated,
+        ))
+    }
+
+    /// Fill in the newest matching and newest overall version of a dependency
+    fn check_outdated(&self, dependency: &mut OutdatedDependency) -> Result<()> {
+        match self.index_crate(&dependency.package) {
+            Ok(index_crate) => compare_with_newest(dependency, &index_crate),
+            Err(Error::NotFound(message)) => dependency.note = Some(message),
+            Err(e) => return Err(e),
+        }
+        Ok(())
+    }
+
+    /// The advisory database, read from disk by the first call that needs it
+    ///
+    /// Updates to the clone are picked up after a restart. Failures are not
+    /// cached, so a database cloned later is found on the next call.
+    fn advisory_database(&self) -> Result<&AdvisoryDatabase> {
+        if let Some(database) = self.advisories.get() {
+            return Ok(database);
+        }
+        let path = self.advisory_db.as_deref().ok_or_else(|| {
+            Error::NotFound("No RustSec advisory database configured, pass --advisory-db".into())
+        })?;
+        let database = AdvisoryDatabase::open(path)?;
+        Ok(self.advisories.get_or_init(|| database))
+    }
+
+    /// Check a crate version, and optionally its resolved dependency tree, against
+    /// the local RustSec advisory database
+    pub fn check_advisories(
+        &self,
+        name: &str,
+        version: Option<&str>,
+        include_dependencies: bool,
+        target: Option<&str>,
+    ) -> Result<AdvisoryReport> {
+        // Input validation
+        if name.trim().is_empty() {
+            return Err(Error::InvalidInput(
+                "Crate name cannot be empty".to_string(),
+            ));
+        }
+
+        let database = self.advisory_database()?;
+
+        let packages = if include_dependencies {
+            self.tree_packages(name, version, target)?
+        } else {
+            let version = match version {
+                Some(version) => version.to_string(),
+                None => {
+                    let index_crate = self.index_crate(name)?;
+                    resolver::find_version(&index_crate, None)?
+                        .version()
+                        .to_string()
+                }
+            };
+            let id = format!("{}@{}", name, version);
+            vec![(name.to_string(), version, vec![id])]
+        };
+
+        Ok(advisory_report(database, packages))
+    }
+
+    /// Check every package of a Cargo.lock against the local RustSec advisory database
+    pub fn check_lockfile_advisories(&self, path: &Path) -> Result<AdvisoryReport> {
+        let packages = lockfile_packages(path)?;
+        let database = self.advisory_database()?;
+        Ok(advisory_report(database, packages))
+    }
+
+    /// Every crate of a resolved dependency tree as `(name, version, path from the root)`
+    fn tree_packages(
+        &self,
+        name: &str,
+        version: Option<&str>,
+        target: Option<&str>,
+    ) -> Result<Vec<(String, String, Vec<String>)>> {
+        let options = TreeOptions {
+            target: target.map(|t| t.to_string()),
+            max_depth: resolver::MAX_DEPTH_LIMIT,
+            ..TreeOptions::default()
+        };
+        let tree = self.get_dependency_tree(name, version, &options)?;
+        let edges: Vec<(String, String)> = tree
+            .edges
+            .iter()
+            .map(|edge| (edge.from.clone(), edge.to.clone()))
+            .collect();
+        let paths = graph::shortest_paths(std::slice::from_ref(&tree.root), &edges);
+
+        Ok(tree
+            .nodes
+            .into_iter()
+            .map(|node| {
+                let path = paths.get(&node.id).cloned().unwrap_or_default();
+                (node.name, node.version, path)
+            })
+            .collect())
+    }
+
+    /// Check the licenses of a crate and its resolved dependency tree against a policy
+    pub async fn check_licenses(
+        &self,
+        name: &str,
+        version: Option<&str>,
+        target: Option<&str>,
+        policy: &LicensePolicy,
+    ) -> Result<LicenseReport> {
+        // Input validation
+        if name.trim().is_empty() {
+            return Err(Error::InvalidInput(
+                "Crate name cannot be empty".to_string(),
+            ));
+        }
+
+        let packages = self.tree_packages(name, version, target)?;
+        let source = packages
+            .first()
+            .map(|(name, version, _)| format!("{}@{}", name, version))
+            .unwrap_or_else(|| name.to_string());
+        Ok(self.license_report(source, packages, policy).await)
+    }
+
+    /// Check the licenses of every crates.io package of a Cargo.lock against a policy
+    pub async fn check_lockfile_licenses(
+        &self,
+        path: &Path,
+        policy: &LicensePolicy,
+    ) -> Result<LicenseReport> {
+        let packages = lockfile_packages(path)?;
+        Ok(self
+            .license_report(path.display().to_string(), packages, policy)
+            .await)
+    }
+
+    async fn license_report(
+        &self,
+        source: String,
+        packages: Vec<(String, String, Vec<String>)>,
+        policy: &LicensePolicy,
+    ) -> LicenseReport {
+        let mut report = LicenseReport {
+            source,
+            allow: policy.allow.clone(),
+            deny: policy.deny.clone(),
+            checked_packages: packages.len(),
+            licenses: BTreeMap::new(),
+            offending: Vec::new(),
+            unknown: Vec::new(),
+        };
+
+        let mut licenses = HashMap::new();
+        for (name, version, path) in packages {
+            let license = self.crate_license(&name, &version, &mut licenses).await;
+            let finding = |license: Option<String>, reason: String| LicenseFinding {
+                name: name.clone(),
+                version: version.clone(),
+                license,
+                reason,
+                path: path.clone(),
+            };
+
+            let Some(license) = license else {
+                let reason = if self.offline {
+                    "License unknown: crate not downloaded and offline mode is on"
+                } else {
+                    "License unknown: no SPDX expression published"
+                };
+                report.unknown.push(finding(None, reason.to_string()));
+                continue;
+            };
+            *report.licenses.entry(license.clone()).or_default() += 1;
+
+            match policy.evaluate(&license) {
+                Verdict::Allowed => {}
+                Verdict::Rejected(reasons) => report
+                    .offending
+                    .push(finding(Some(license), reasons.join("; "))),
+                Verdict::Invalid(e) => report.unknown.push(finding(
+                    Some(license),
+                    format!("Invalid SPDX expression: {}", e),
+                )),
+            }
+        }
+
+        info!(
+            "Checked licenses of {} packages: {} offending, {} unknown",
+            report.checked_packages,
+            report.offending.len(),
+            report.unknown.len()
+        );
+        report
+    }
+
+    /// Get one page of crates that depend on a crate
+    ///
+    /// With `matching_version`, only dependents whose requirement admits that version
+    /// of the crate are returned. crates.io cannot filter server-side, so the filter
+    /// applies to the fetched page: `matching` counts what is left of it, while
+    /// `total` counts all dependents.
+    pub async fn get_reverse_dependencies(
+        &self,
+        name: &str,
+        page: Option<u32>,
+        per_page: Option<u32>,
+        matching_version: Option<&str>,
+    ) -> Result<ReverseDependencies> {
+        // Input validation
+        if name.trim().is_empty() {
+            return Err(Error::InvalidInput(
+                "Crate name cannot be empty".to_string(),
+            ));
+        }
+
+        let matching_version = matching_version
+            .map(|v| {
+                semver::Version::parse(v)
+                    .map_err(|e| Error::InvalidInput(format!("Invalid version '{}': {}", v, e)))
+            })
+            .transpose()?;
+
+        if self.offline {
+            return Err(Error::Offline(
+                "Reverse dependencies are not available in offline mode: they are only \
+                known to the crates.io API"
+                    .to_string(),
+            ));
+        }
+
+        let page = page.unwrap_or(1).max(1);
+        let per_page = per_page.unwrap_or(20).clamp(1, 100);
+
+        let url = format!(
+            "https://crates.io/api/v1/crates/{}/reverse_dependencies?page={}&per_page={}",
+            name, page, per_page
+        );
+        let response = self
+            .make_crates_io_request(&url)
+            .await
+            .map_err(|e| e.or_not_found(|| format!("Crate '{}' not found on crates.io", name)))?;
+
+        let reverse_response: CratesIoReverseDependenciesResponse =
+            response.json().await.map_err(|e| {
+                Error::InvalidResponse(format!(
+                    "Failed to parse reverse dependencies response: {}",
+                    e
+                ))
+            })?;
+
+        let reverse_dependencies = Self::reverse_dependencies_from_response(
+            name,
+            page,
+            per_page,
+            reverse_response,
+            matching_version,
+        );
+
+        info!(
+            "Retrieved {} reverse dependencies for crate '{}' (page {}, total {})",
+            reverse_dependencies.dependents.len(),
+            name,
+            page,
+            reverse_dependencies.total
+        );
+        Ok(reverse_dependencies)
+    }
+
+    /// Join the dependency records with the dependent versions they belong to
+    fn reverse_dependencies_from_response(
+        name: &str,
+        page: u32,
+        per_page: u32,
+        response: CratesIoReverseDependenciesResponse,
+        matching_version: Option<semver::Version>,
+    ) -> ReverseDependencies {
+        let dependents = response
+            .dependencies
+            .into_iter()
+            .filter_map(|dep| {
+                let version = response.versions.iter().find(|v| v.id == dep.version_id)?;
+                Some(ReverseDependency {
+                    name: version.crate_name.clone(),
+                    version: version.num.clone(),
+                    version_req: dep.req,
+                    downloads: dep.downloads,
+                    optional: dep.optional,
+                    kind: dep.kind,
+                })
+            })
+            .filter(|dep| match &matching_version {
+                // Unparseable requirements are kept rather than silently dropped
+                Some(version) => semver::VersionReq::parse(&dep.version_req)
+                    .map(|req| req.matches(version))
+                    .unwrap_or(true),
+                None => true,
+            })
+            .collect::<Vec<_>>();
+
+        ReverseDependencies {
+            name: name.to_string(),
+            total: response.meta.total,
+            page,
+            per_page,
+            matching: matching_version.map(|_| dependents.len()),
+            dependents,
+        }
+    }
+
+    /// List the examples of a published crate version, and read one of them
+    ///
+    /// `example` is a path below `examples/` or the name cargo runs it under.
+    /// Without a version the newest stable release is used.
+    pub async fn get_crate_examples(
+        &self,
+        name: &str,
+        version: Option<&str>,
+        example: Option<&str>,
+    ) -> Result<CrateExamples> {
+        // Input validation
+        if name.trim().is_empty() {
+            return Err(Error::InvalidInput(
+                "Crate name cannot be empty".to_string(),
+            ));
+        }
+
+        let version = self.resolve_version(name, version).await?;
+        let (dir, source) = self.crate_source(name, &version).await?;
+        let examples = crate_archive::examples(&source_cache::list_files(&dir)?);
+
+        let file = match example {
+            Some(example) => {
+                let found = crate_archive::find_example(&examples, example).ok_or_else(|| {
+                    Error::NotFound(format!(
+                        "Example '{}' not found in crate '{}' {}",
+                        example, name, version
+                    ))
+                })?;
+                Some(source_cache::read_file(
+                    &dir,
+                    &found.path,
+                    None,
+                    None,
+                    MAX_FILE_SIZE,
+                )?)
+            }
+            None => None,
+        };
+
+        info!(
+            "Found {} example files in crate '{}' {}",
+            examples.len(),
+            name,
+            version
+        );
+        Ok(CrateExamples {
+            name: name.to_string(),
+            version,
+            source: source.to_string(),
+            examples,
+            file,
+        })
+    }
+
+    /// List the files of the published source of a crate version, optionally
+    /// only those below a directory
+    pub async fn list_crate_files(
+        &self,
+        name: &str,
+        version: Option<&str>,
+        directory: Option<&str>,
+    ) -> Result<CrateFiles> {
+        // Input validation
+        if name.trim().is_empty() {
+            return Err(Error::InvalidInput(
+                "Crate name cannot be empty".to_string(),
+            ));
+        }
+
+        let version = self.resolve_version(name, version).await?;
+        let (dir, source) = self.crate_source(name, &version).await?;
+        let mut files = source_cache::list_files(&dir)?;
+
+        let directory = directory
+            .map(|d| d.trim_start_matches("./").trim_matches('/'))
+            .filter(|d| !d.is_empty() && *d != ".");
+        if let Some(directory) = directory {
+            let prefix = format!("{}/", directory);
+            files.retain(|file| file.path.starts_with(&prefix));
+            if files.is_empty() {
+                return Err(Error::NotFound(format!(
+                    "Directory '{}' not found in crate '{}' {}",
+                    directory, name, version
+                )));
+            }
+        }
+
+        info!(
+            "Listed {} files of crate '{}' {}",
+            files.len(),
+            name,
+            version
+        );
+        Ok(CrateFiles {
+            name: name.to_string(),
+            version,
+            source: source.to_string(),
+            files,
+        })
+    }
+
+    /// Read a file of the published source of a crate version, optionally only a
+    /// range of lines
+    pub async fn read_crate_file(
+        &self,
+        name: &str,
+        version: Option<&str>,
+        path: &str,
+        start_line: Option<usize>,
+        end_line: Option<usize>,
+    ) -> Result<CrateSourceFile> {
+        // Input validation
+        if name.trim().is_empty() {
+            return Err(Error::InvalidInput(
+                "Crate name cannot be empty".to_string(),
+            ));
+        }
+
+        let version = self.resolve_version(name, version).await?;
+        let (dir, source) = self.crate_source(name, &version).await?;
+        let file = source_cache::read_file(&dir, path, start_line, end_line, MAX_FILE_SIZE)
+            .map_err(|e| {
+                e.or_not_found(|| {
+                    format!("File '{}' not found in crate '{}' {}", path, name, version)
+                })
+            })?;
+
+        info!("Read {} of crate '{}' {}", file.path, name, version);
+        Ok(CrateSourceFile {
+            name: name.to_string(),
+            version,
+            source: source.to_string(),
+            file,
+        })
+    }
+
+    /// Search the published source of a crate version for a regex, line by line
+    pub async fn grep_crate_source(
+        &self,
+        name: &str,
+        version: Option<&str>,
+        pattern: &str,
+        options: &GrepOptions,
+    ) -> Result<SourceSearch> {
+        // Input validation
+        if name.trim().is_empty() {
+            return Err(Error::InvalidInput(
+                "Crate name cannot be empty".to_string(),
+            ));
+        }
+        let regex = RegexBuilder::new(pattern)
+            .case_insensitive(options.case_insensitive)
+            .size_limit(1 << 20)
+            .build()
+            .map_err(|e| Error::InvalidInput(format!("Invalid pattern '{}': {}", pattern, e)))?;
+        let filter = FileFilter::new(&options.include, &options.exclude)?;
+
+        let version = self.resolve_version(name, version).await?;
+        let (dir, source) = self.crate_source(name, &version).await?;
+        let files: Vec<_> = source_cache::list_files(&dir)?
+            .into_iter()
+            .filter(|file| filter.matches(&file.path))
+            .collect();
+        let (matches, truncated) =
+            source_cache::grep(&dir, &files, &regex, options.context, options.max_results)?;
+
+        info!(
+            "Found {} matches for '{}' in {} files of crate '{}' {}",
+            matches.len(),
+            pattern,
+            files.len(),
+            name,
+            version
+        );
+        Ok(SourceSearch {
+            name: name.to_string(),
+            version,
+            source: source.to_string(),
+            pattern: pattern.to_string(),
+            searched_files: files.len(),
+            matches,
+            truncated,
+        })
+    }
+
+    /// Check a requested version against the index, or pick the newest stable one
+    ///
+    /// Without an index, a requested version only has to be semver and the newest
+    /// one comes from crates.io.
+    async fn resolve_version(&self, name: &str, version: Option<&str>) -> Result<String> {
+        if !local_registry::is_crate_name(name) {
+            return Err(Error::InvalidInput(format!(
+                "Invalid crate name '{}'",
+                name
+            )));
+        }
+
+        match self.index_crate(name) {
+            Ok(index_crate) => match version {
+                Some(v) => index_crate
+                    .versions()
+                    .iter()
+                    .find(|ver| ver.version() == v)
+                    .map(|ver| ver.version().to_string())
+                    .ok_or_else(|| Error::VersionNotFound {
+                        name: name.to_string(),
+                        version: v.to_string(),
+                    }),
+                None => Ok(index_crate
+                    .highest_normal_version()
+                    .unwrap_or_else(|| index_crate.highest_version())
+                    .version()
+                    .to_string()),
+            },
+            Err(Error::IndexUnavailable(_)) => match version {
+                Some(v) => match semver::Version::parse(v) {
+                    Ok(parsed) => Ok(parsed.to_string()),
+                    Err(_) => Err(Error::InvalidInput(format!(
+                        "Invalid version '{}', expected a semver version like 1.2.3",
+                        v
+                    ))),
+                },
+                None => Ok(self.get_crate_info(name).await?.version),
+            },
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get the directory holding the published source of a crate version, with
+    /// where it came from
+    ///
+    /// Sources cargo has unpacked are used as they are. Otherwise the `.crate`
+    /// archive, from cargo's cache or crates.io, is checked against the index
+    /// checksum and unpacked into the source cache.
+    async fn crate_source(&self, name: &str, version: &str) -> Result<(PathBuf, &'static str)> {
+        if let Some(dir) = self.local_registry.source_dir(name, version) {
+            return Ok((dir, "local_registry"));
+        }
+        if let Some(dir) = self.source_cache.dir(name, version) {
+            return Ok((dir, "source_cache"));
+        }
+
+        let (archive, source) = match self.local_registry.archive_path(name, version) {
+            Some(path) => {
+                debug!("Reading crate archive from: {}", path.display());
+                let archive = std::fs::read(&path).map_err(|e| {
+                    Error::InvalidResponse(format!("Failed to read {}: {}", path.display(), e))
+                })?;
+                (archive, "local_registry")
+            }
+            None if self.offline => {
+                return Err(Error::Offline(format!(
+                    "Crate '{}' {} is not in the local cargo registry or the source cache, \
+                    and downloading it is disabled in offline mode",
+                    name, version
+                )));
+            }
+            None => (
+                crate_archive::download(&self.http_client, name, version).await?,
+                "crates_io",
+            ),
+        };
+
+        let checksum = self.crate_checksum(name, version).await?;
+        crate_archive::verify_checksum(&archive, &checksum, name, version)?;
+        let dir = self.source_cache.unpack(name, version, &archive)?;
+        Ok((dir, source))
+    }
+
+    /// The hex SHA-256 checksum of a crate version's `.crate` archive, from the
+    /// index or, without one, from crates.io
+    async fn crate_checksum(&self, name: &str, version: &str) -> Result<String> {
+        match self.index_crate(name) {
+            Ok(index_crate) => index_crate
+                .versions()
+                .iter()
+                .find(|ver| ver.version() == version)
+                .map(|ver| crate_archive::to_hex(ver.checksum()))
+                .ok_or_else(|| Error::VersionNotFound {
+                    name: name.to_string(),
+                    version: version.to_string(),
+                }),
+            Err(Error::IndexUnavailable(_)) if !self.offline => {
+                let url = format!("https://crates.io/api/v1/crates/{}/{}", name, version);
+                let response = self.make_crates_io_request(&url).await.map_err(|e| {
+                    e.or_not_found(|| {
+                        format!("Crate '{}' {} not found on crates.io", name, version)
+                    })
+                })?;
+                let version_response: CratesIoVersionResponse =
+                    response.json().await.map_err(|e| {
+                        Error::InvalidResponse(format!("Failed to parse version response: {}", e))
+                    })?;
+                Ok(version_response.version.checksum)
+            }
+            Err(e) => Err(e),
+        }
     }
 }
 
+/// Check a locked crates.io package against its index entry: yanked, and how
+/// many semver-compatible releases it is behind
+fn audit_against_index(
+    package: &LockedPackage,
+    index_crate: &crates_index::Crate,
+) -> LockedPackageReport {
+    let mut report = locked_package_report(package);
+
+    let Ok(locked) = semver::Version::parse(&package.version) else {
+        report.note = Some(format!("Invalid version '{}'", package.version));
+        return report;
+    };
+
+    let Some(locked_entry) = index_crate
+        .versions()
+        .iter()
+        .find(|v| v.version() == package.version)
+    else {
+        report.note = Some("Locked version is not in the index".to_string());
+        return report;
+    };
+    report.yanked = Some(locked_entry.is_yanked());
+
+    let newest = index_crate
+        .highest_normal_version()
+        .unwrap_or_else(|| index_crate.highest_version());
+    report.newest_version = Some(newest.version().to_string());
+
+    // Newer releases cargo would pick for `^locked`, without `cargo update --breaking`
+    let key = resolver::compatibility_key(&locked);
+    let newer_compatible: Vec<semver::Version> = index_crate
+        .versions()
+        .iter()
+        .filter(|v| !v.is_yanked())
+        .filter_map(|v| semver::Version::parse(v.version()).ok())
+        .filter(|v| v > &locked && v.pre.is_empty() && resolver::compatibility_key(v) == key)
+        .collect();
+    report.compatible_versions_behind = Some(newer_compatible.len());
+    report.newest_compatible = Some(newer_compatible.iter().max().unwrap_or(&locked).to_string());
+
+    report
+}
+
+fn locked_package_report(package: &LockedPackage) -> LockedPackageReport {
+    LockedPackageReport {
+        name: package.name.clone(),
+        version: package.version.clone(),
+        yanked: None,
+        newest_compatible: None,
+        compatible_versions_behind: None,
+        newest_version: None,
+        note: None,
+    }
+}
+
+/// Crates locked at more than one semver-incompatible version
+fn duplicate_crates(packages: &[&LockedPackage]) -> Vec<DuplicateCrate> {
+    let mut majors: BTreeMap<&str, BTreeMap<String, Vec<semver::Version>>> = BTreeMap::new();
+    for package in packages {
+        if let Ok(version) = semver::Version::parse(&package.version) {
+            majors
+                .entry(package.name.as_str())
+                .or_default()
+                .entry(resolver::compatibility_key(&version))
+                .or_default()
+                .push(version);
+        }
+    }
+    majors
+        .into_iter()
+        .filter(|(_, keys)| keys.len() > 1)
+        .map(|(name, keys)| {
+            let versions: BTreeSet<semver::Version> = keys.into_values().flatten().collect();
+            DuplicateCrate {
+                name: name.to_string(),
+                versions: versions.iter().map(|v| v.to_string()).collect(),
+            }
+        })
+        .collect()
+}
+
+/// The published versions of a crate matching a requirement, sorted newest first
+fn requirement_match(
+    index_crate: &crates_index::Crate,
+    requirement: &str,
+    req: &semver::VersionReq,
+) -> VersionRequirementMatch {
+    let mut versions: Vec<(semver::Version, bool)> = index_crate
+        .versions()
+        .iter()
+        .filter_map(|v| {
+            semver::Version::parse(v.version())
+                .ok()
+                .map(|parsed| (parsed, v.is_yanked()))
+        })
+        .collect();
+    versions.sort_by(|a, b| b.0.cmp(&a.0));
+
+    let matching: Vec<&(semver::Version, bool)> =
+        versions.iter().filter(|(v, _)| req.matches(v)).collect();
+    // Pre-releases within the bounds that still do not match are excluded by the
+    // pre-release rule
+    let excluded_prereleases: Vec<String> = versions
+        .iter()
+        .filter(|(v, _)| !v.pre.is_empty() && !req.matches(v))
+        .filter(|(v, _)| resolver::within_bounds(req, v))
+        .map(|(v, _)| v.to_string())
+        .collect();
+
+    VersionRequirementMatch {
+        name: index_crate.name().to_string(),
+        requirement: requirement.to_string(),
+        range: resolver::describe_requirement(req),
+        matching_versions: matching.iter().map(|(v, _)| v.to_string()).collect(),
+        yanked_matches: matching
+            .iter()
+            .filter(|(_, yanked)| *yanked)
+            .map(|(v, _)| v.to_string())
+            .collect(),
+        highest_match: matching
+            .iter()
+            .find(|(_, yanked)| !yanked)
+            .map(|(v, _)| v.to_string()),
+        prereleases_excluded: !excluded_prereleases.is_empty(),
+        excluded_prereleases,
+    }
+}
+
+/// Compare a dependency's requirement with the releases of the crate it names
+///
+/// Upgrading is breaking when the newest release is not semver-compatible with
+/// the version the requirement resolves to, or with its lower bound when no
+/// release matches, so `=1.2.0` to 1.3.0 is not.
+fn compare_with_newest(dependency: &mut OutdatedDependency, index_crate: &crates_index::Crate) {
+    let req = match semver::VersionReq::parse(&dependency.version_req) {
+        Ok(req) => req,
+        Err(e) => {
+            dependency.note = Some(format!("Invalid version requirement: {}", e));
+            return;
+        }
+    };
+
+    let newest = index_crate
+        .highest_normal_version()
+        .unwrap_or_else(|| index_crate.highest_version());
+    let newest_matching = resolver::highest_match(index_crate, &req);
+
+    dependency.newest_version = Some(newest.version().to_string());
+    dependency.newest_matching = newest_matching.map(|v| v.version().to_string());
+
+    if let Ok(newest) = semver::Version::parse(newest.version()) {
+        let matching = newest_matching.and_then(|v| semver::Version::parse(v.version()).ok());
+        dependency.outdated = matching.as_ref().is_none_or(|matching| matching < &newest);
+        dependency.breaking_upgrade = match matching.or_else(|| resolver::lower_bound(&req)) {
+            Some(current) => {
+                resolver::compatibility_key(&current) != resolver::compatibility_key(&newest)
+            }
+            None => !req.matches(&newest),
+        };
+    }
+}
+
+/// Every crates.io package of a Cargo.lock as `(name, version, path from a workspace member)`
+fn lockfile_packages(path: &Path) -> Result<Vec<(String, String, Vec<String>)>> {
+    let lock = lockfile::load(path)?;
+    let paths = graph::shortest_paths(&lock.roots(), &lock.edges());
+
+    Ok(lock
+        .packages
+        .iter()
+        .filter(|package| package.is_from_crates_io())
+        .map(|package| {
+            let path = paths.get(&package.id()).cloned().unwrap_or_default();
+            (package.name.clone(), package.version.clone(), path)
+        })
+        .collect())
+}
+
+/// Match `(name, version, dependency path)` entries against an advisory database,
+/// separating vulnerabilities from informational advisories
+fn advisory_report(
+    database: &AdvisoryDatabase,
+    packages: Vec<(String, String, Vec<String>)>,
+) -> AdvisoryReport {
+    let mut report = AdvisoryReport {
+        database: database.path().display().to_string(),
+        checked_packages: packages.len(),
+        vulnerabilities: Vec::new(),
+        warnings: Vec::new(),
+    };
+
+    for (name, version, path) in packages {
+        let Ok(parsed) = semver::Version::parse(&version) else {
+            continue;
+        };
+        for advisory in database.affecting(&name, &parsed) {
+            let metadata = &advisory.advisory;
+            let severity = advisory.severity();
+            let found = AdvisoryMatch {
+                id: metadata.id.clone(),
+                package: name.clone(),
+                version: version.clone(),
+                title: metadata.title.clone(),
+                date: metadata.date.clone(),
+                severity: severity.map(|(_, rating)| rating.to_string()),
+                cvss_score: severity.map(|(score, _)| score),
+                cvss: metadata.cvss.clone(),
+                informational: metadata.informational.clone(),
+                categories: metadata.categories.clone(),
+                aliases: metadata.aliases.clone(),
+                patched_versions: advisory.versions.patched.clone(),
+                url: format!("https://rustsec.org/advisories/{}.html", metadata.id),
+                reference_url: metadata.url.clone(),
+                path: path.clone(),
+            };
+            if found.informational.is_some() {
+                report.warnings.push(found);
+            } else {
+                report.vulnerabilities.push(found);
+            }
+        }
+    }
+
+    info!(
+        "Checked {} packages: {} vulnerabilities, {} warnings",
+        report.checked_packages,
+        report.vulnerabilities.len(),
+        report.warnings.len()
+    );
+    report
+}
+
+/// Assemble an outdated report, optionally dropping up-to-date dependencies
+fn outdated_report(
+    source: String,
+    mut packages: Vec<OutdatedPackage>,
+    only_outdated: bool,
+) -> OutdatedReport {
+    if only_outdated {
+        for package in &mut packages {
+            package
+                .dependencies
+                .retain(|dep| dep.outdated || dep.note.is_some());
+        }
+    }
+    OutdatedReport { source, packages }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use anyhow::Result;
+    use serde_json::json;
+
+    async fn test_client() -> Result<CratesClient> {
+        let config = ServerConfig::default();
+        Ok(CratesClient::new(&config, HttpClient::new(&config)?).await?)
+    }
 
     #[tokio::test]
     async fn test_search_crates() -> Result<()> {
-        let client = CratesClient::new().await?;
-        let results = client.search_crates("serde", Some(5), "relevance", 0).await?;
+        let client = test_client().await?;
+        let results = client
+            .search_crates("serde", Some(5), "relevance", 0)
+            .await?;
 
         assert!(!results.is_empty());
         assert!(results.len() <= 5);
@@ -436,8 +1931,10 @@ mod tests {
 
     #[tokio::test]
     async fn test_search_crates_by_downloads() -> Result<()> {
-        let client = CratesClient::new().await?;
-        let results = client.search_crates("http", Some(3), "downloads", 100000).await?;
+        let client = test_client().await?;
+        let results = client
+            .search_crates("http", Some(3), "downloads", 100000)
+            .await?;
 
         assert!(!results.is_empty());
         assert!(results.len() <= 3);
@@ -449,7 +1946,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_crate_info() -> Result<()> {
-        let client = CratesClient::new().await?;
+        let client = test_client().await?;
         let info = client.get_crate_info("serde").await?;
 
         assert_eq!(info.name, "serde");
@@ -459,13 +1956,235 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_reverse_dependencies_from_response() -> Result<()> {
+        let fixture = r#"{
+                "dependencies": [
+                    {"id": 1, "version_id": 10, "crate_id": "serde", "req": "^1.0.100",
+                     "optional": false, "default_features": true, "features": [],
+                     "target": null, "kind": "normal", "downloads": 5000},
+                    {"id": 2, "version_id": 20, "crate_id": "serde", "req": "^0.9",
+                     "optional": true, "default_features": true, "features": [],
+                     "target": null, "kind": "dev", "downloads": 42}
+                ],
+                "versions": [
+                    {"id": 10, "crate": "serde_json", "num": "1.0.140", "downloads": 100},
+                    {"id": 20, "crate": "old-thing", "num": "0.1.0", "downloads": 1}
+                ],
+                "meta": {"total": 2}
+            }"#;
+
+        let response: CratesIoReverseDependenciesResponse = serde_json::from_str(fixture)?;
+        let all = CratesClient::reverse_dependencies_from_response("serde", 1, 20, response, None);
+        assert_eq!(all.total, 2);
+        assert_eq!(all.matching, None);
+        assert_eq!(all.dependents.len(), 2);
+        assert_eq!(all.dependents[0].name, "serde_json");
+        assert_eq!(all.dependents[0].version_req, "^1.0.100");
+        assert_eq!(all.dependents[0].downloads, 5000);
+        assert_eq!(all.dependents[1].kind, "dev");
+
+        let response: CratesIoReverseDependenciesResponse = serde_json::from_str(fixture)?;
+        let filtered = CratesClient::reverse_dependencies_from_response(
+            "serde",
+            1,
+            20,
+            response,
+            Some(semver::Version::parse("1.0.200")?),
+        );
+        assert_eq!(filtered.total, 2);
+        assert_eq!(filtered.matching, Some(1));
+        assert_eq!(filtered.dependents.len(), 1);
+        assert_eq!(filtered.dependents[0].name, "serde_json");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_audit_lockfile_packages() -> Result<()> {
+        let lock = lockfile::load(Path::new("tests/fixtures/lockfile/Cargo.lock"))?;
+        let crates_io: Vec<&LockedPackage> = lock
+            .packages
+            .iter()
+            .filter(|p| p.is_from_crates_io())
+            .collect();
+
+        // `helper` is locked at three majors, but only from path, git and another registry
+        let duplicates = duplicate_crates(&crates_io);
+        assert_eq!(duplicates.len(), 1);
+        assert_eq!(duplicates[0].name, "dep");
+        assert_eq!(duplicates[0].versions, vec!["1.2.0", "2.0.0"]);
+
+        let dep = resolver::index_crate(
+            "dep",
+            &[
+                ("1.2.0", json!([]), json!({}), true),
+                ("1.2.1", json!([]), json!({}), false),
+                ("1.3.0", json!([]), json!({}), false),
+                ("1.4.0-beta.1", json!([]), json!({}), false),
+                ("2.0.0", json!([]), json!({}), false),
+                ("2.1.0", json!([]), json!({}), true),
+            ],
+            None,
+        );
+        let yanked = audit_against_index(crates_io[0], &dep);
+        assert_eq!(yanked.yanked, Some(true));
+        assert_eq!(yanked.compatible_versions_behind, Some(2));
+        assert_eq!(yanked.newest_compatible.as_deref(), Some("1.3.0"));
+        assert_eq!(yanked.newest_version.as_deref(), Some("2.0.0"));
+
+        // Yanked releases are not counted as newer
+        let current = audit_against_index(crates_io[1], &dep);
+        assert_eq!(current.yanked, Some(false));
+        assert_eq!(current.compatible_versions_behind, Some(0));
+        assert_eq!(current.newest_compatible.as_deref(), Some("2.0.0"));
+
+        let unknown = audit_against_index(
+            crates_io[2],
+            &resolver::index_crate("log", &[("0.4.21", json!([]), json!({}), false)], None),
+        );
+        assert_eq!(unknown.yanked, None);
+        assert!(unknown.note.is_some());
+        Ok(())
+    }
+
+    #[test]
+    fn test_advisory_report() -> Result<()> {
+        let database = AdvisoryDatabase::open(Path::new("tests/fixtures/advisory-db"))?;
+        let package = |name: &str, version: &str| {
+            let id = format!("{}@{}", name, version);
+            (
+                name.to_string(),
+                version.to_string(),
+                vec!["app@0.1.0".to_string(), id],
+            )
+        };
+        let report = advisory_report(
+            &database,
+            vec![
+                package("demo", "1.2.0"),
+                package("demo", "1.2.3"),
+                package("old-thing", "0.1.0"),
+            ],
+        );
+
+        assert_eq!(report.checked_packages, 3);
+        assert_eq!(report.vulnerabilities.len(), 1);
+        let vulnerability = &report.vulnerabilities[0];
+        assert_eq!(vulnerability.id, "RUSTSEC-2099-0001");
+        assert_eq!(vulnerability.version, "1.2.0");
+        assert_eq!(vulnerability.severity.as_deref(), Some("critical"));
+        assert_eq!(vulnerability.path, vec!["app@0.1.0", "demo@1.2.0"]);
+
+        assert_eq!(report.warnings.len(), 1);
+        assert_eq!(
+            report.warnings[0].informational.as_deref(),
+            Some("unmaintained")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_requirement_match() {
+        let index_crate = resolver::index_crate(
+            "dep",
+            &[
+                ("1.1.0", json!([]), json!({}), false),
+                ("1.2.0-rc.1", json!([]), json!({}), false),
+                ("1.2.0", json!([]), json!({}), false),
+                ("1.4.0-beta.1", json!([]), json!({}), false),
+                ("1.4.0", json!([]), json!({}), true),
+                ("2.0.0-alpha", json!([]), json!({}), false),
+                ("2.0.0", json!([]), json!({}), false),
+            ],
+            None,
+        );
+        let requirement_match = |requirement: &str| {
+            let req = semver::VersionReq::parse(requirement).unwrap();
+            requirement_match(&index_crate, requirement, &req)
+        };
+
+        let caret = requirement_match("^1.2");
+        assert_eq!(caret.range, ">=1.2.0, <2.0.0");
+        assert_eq!(caret.matching_versions, vec!["1.4.0", "1.2.0"]);
+        assert_eq!(caret.yanked_matches, vec!["1.4.0"]);
+        assert_eq!(caret.highest_match.as_deref(), Some("1.2.0"));
+        // 1.2.0-rc.1 is below the range anyway, 2.0.0-alpha is only left out by the
+        // pre-release rule
+        assert_eq!(
+            caret.excluded_prereleases,
+            vec!["2.0.0-alpha", "1.4.0-beta.1"]
+        );
+        assert!(caret.prereleases_excluded);
+
+        let pre = requirement_match("^1.2.0-rc.1");
+        assert_eq!(pre.matching_versions, vec!["1.4.0", "1.2.0", "1.2.0-rc.1"]);
+        assert_eq!(
+            pre.excluded_prereleases,
+            vec!["2.0.0-alpha", "1.4.0-beta.1"]
+        );
+
+        let exact = requirement_match("=1.1.0");
+        assert_eq!(exact.matching_versions, vec!["1.1.0"]);
+        assert!(!exact.prereleases_excluded);
+    }
+
+    #[test]
+    fn test_compare_with_newest() {
+        let index_crate = resolver::index_crate(
+            "dep",
+            &[
+                ("0.9.0", json!([]), json!({}), false),
+                ("1.2.0", json!([]), json!({}), false),
+                ("1.3.0", json!([]), json!({}), false),
+                ("2.0.0-rc.1", json!([]), json!({}), false),
+            ],
+            None,
+        );
+
+        let compare = |version_req: &str| {
+            let mut dependency = OutdatedDependency {
+                name: "dep".to_string(),
+                package: "dep".to_string(),
+                kind: "normal".to_string(),
+                target: None,
+                optional: false,
+                version_req: version_req.to_string(),
+                newest_matching: None,
+                newest_version: None,
+                outdated: false,
+                breaking_upgrade: false,
+                note: None,
+            };
+            compare_with_newest(&mut dependency, &index_crate);
+            dependency
+        };
+
+        let pinned = compare("=1.2.0");
+        assert_eq!(pinned.newest_matching.as_deref(), Some("1.2.0"));
+        assert_eq!(pinned.newest_version.as_deref(), Some("1.3.0"));
+        assert!(pinned.outdated);
+        // The requirement has to be relaxed, but 1.3.0 is semver-compatible with 1.2.0
+        assert!(!pinned.breaking_upgrade);
+        assert!(!compare("~1.2").breaking_upgrade);
+        assert!(!compare("^1.3").outdated);
+
+        let old = compare("^0.9");
+        assert!(old.outdated && old.breaking_upgrade);
+        // Nothing matches, so the lower bound decides
+        let unreleased = compare("^0.5");
+        assert_eq!(unreleased.newest_matching, None);
+        assert!(unreleased.outdated && unreleased.breaking_upgrade);
+        assert!(compare("not a requirement").note.is_some());
+    }
+
     #[tokio::test]
     async fn test_get_crate_dependencies() -> Result<()> {
-        let client = CratesClient::new().await?;
+        let client = test_client().await?;
 
         // This test may fail if git index is not available, which is expected
         match client.get_crate_dependencies("serde", None) {
-            Ok(deps) => {
+            Ok((_, deps)) => {
                 // serde should have some dependencies (at least serde_derive optionally)
                 assert!(!deps.is_empty());
             }
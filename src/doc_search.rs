@@ -0,0 +1,335 @@
+use fuzzy_matcher::FuzzyMatcher;
+use fuzzy_matcher::skim::SkimMatcherV2;
+
+use crate::types::DocumentationItem;
+
+/// Words in rendered types that are not type names
+const TYPE_KEYWORDS: &[&str] = &[
+    "mut", "dyn", "impl", "const", "fn", "for", "as", "unsafe", "extern",
+];
+
+/// A documented item as it is searched
+#[derive(Debug, Clone)]
+pub struct SearchEntry {
+    pub item: DocumentationItem,
+    /// Parameter types of a function or method, with `self` as the type it belongs to
+    pub inputs: Vec<String>,
+    pub output: Option<String>,
+}
+
+impl SearchEntry {
+    /// An item without parameter and return types, which only name queries find
+    pub fn without_signature(item: DocumentationItem) -> Self {
+        Self {
+            item,
+            inputs: Vec::new(),
+            output: None,
+        }
+    }
+
+    fn is_function(&self) -> bool {
+        matches!(
+            self.item.kind.as_str(),
+            "function" | "method" | "required_method" | "provided_method"
+        )
+    }
+}
+
+/// Search items by name, or by type signature for queries like `fn(&str) -> Result`,
+/// `(&str, usize)` or `-> Vec<u8>`, best matches first
+pub fn search(entries: &[SearchEntry], query: &str, limit: usize) -> Vec<DocumentationItem> {
+    let query = query.trim();
+    let mut scored: Vec<(i64, &SearchEntry)> = match SignatureQuery::parse(query) {
+        Some(signature) => entries
+            .iter()
+            .filter_map(|entry| Some((signature.score(entry)?, entry)))
+            .collect(),
+        None => {
+            let matcher = SkimMatcherV2::default().ignore_case();
+            entries
+                .iter()
+                .filter_map(|entry| Some((name_score(&matcher, entry, query)?, entry)))
+                .collect()
+        }
+    };
+
+    // Ties go to the shorter path, which is usually the more prominent item
+    scored.sort_by(|(a_score, a), (b_score, b)| {
+        b_score
+            .cmp(a_score)
+            .then_with(|| a.item.path.len().cmp(&b.item.path.len()))
+            .then_with(|| a.item.path.cmp(&b.item.path))
+    });
+    scored
+        .into_iter()
+        .take(limit)
+        .map(|(_, entry)| entry.item.clone())
+        .collect()
+}
+
+/// Whether a query searches by type signature rather than by name
+pub fn is_signature_query(query: &str) -> bool {
+    SignatureQuery::parse(query.trim()).is_some()
+}
+
+/// Fuzzy score of an item for a name query
+///
+/// Queries with `::` are matched against paths. Otherwise exact and prefix name
+/// matches rank above fuzzy ones, and the name counts more than the path.
+fn name_score(matcher: &SkimMatcherV2, entry: &SearchEntry, query: &str) -> Option<i64> {
+    if query.contains("::") {
+        let bonus = if entry.item.path.ends_with(query) {
+            1000
+        } else {
+            0
+        };
+        return matcher
+            .fuzzy_match(&entry.item.path, query)
+            .map(|score| score + bonus);
+    }
+
+    let name = entry.item.name.to_lowercase();
+    let lowercase_query = query.to_lowercase();
+    let bonus = if name == lowercase_query {
+        1000
+    } else if name.starts_with(&lowercase_query) {
+        500
+    } else {
+        0
+    };
+    let name_score = matcher
+        .fuzzy_match(&entry.item.name, query)
+        .map(|score| score * 2 + bonus);
+    let path_score = matcher.fuzzy_match(&entry.item.path, query);
+    name_score.max(path_score)
+}
+
+/// Type names of a signature query, per parameter and for the return type
+#[derive(Debug, PartialEq)]
+struct SignatureQuery {
+    inputs: Vec<Vec<String>>,
+    output: Option<Vec<String>>,
+}
+
+impl SignatureQuery {
+    fn parse(query: &str) -> Option<Self> {
+        let rest = match query.strip_prefix("fn") {
+            Some(rest) if rest.trim_start().starts_with('(') => rest.trim_start(),
+            _ if query.starts_with('(') || query.contains("->") => query,
+            _ => return None,
+        };
+
+        let (inputs, output) = match rest.split_once("->") {
+            Some((inputs, output)) => (inputs, Some(output)),
+            None => (rest, None),
+        };
+        let inputs = inputs.trim();
+        let inputs = inputs
+            .strip_prefix('(')
+            .and_then(|inputs| inputs.strip_suffix(')'))
+            .unwrap_or(inputs);
+
+        Some(Self {
+            inputs: split_top_level(inputs)
+                .into_iter()
+                .map(type_names)
+                .filter(|names| !names.is_empty())
+                .collect(),
+            output: output.map(type_names).filter(|names| !names.is_empty()),
+        })
+    }
+
+    /// Every queried parameter has to match a distinct parameter, in any order, and
+    /// the queried return type the return type; unmatched parameters lower the score
+    fn score(&self, entry: &SearchEntry) -> Option<i64> {
+        if !entry.is_function() {
+            return None;
+        }
+
+        let mut score = 100;
+        let mut available: Vec<Vec<String>> =
+            entry.inputs.iter().map(|input| type_names(input)).collect();
+        for wanted in &self.inputs {
+            let position = available
+                .iter()
+                .position(|have| wanted.iter().all(|name| have.contains(name)))?;
+            if available[position] == *wanted {
+                score += 5;
+            }
+            available.remove(position);
+        }
+        if let Some(wanted) = &self.output {
+            let have = entry.output.as_deref().map(type_names).unwrap_or_default();
+            if !wanted.iter().all(|name| have.contains(name)) {
+                return None;
+            }
+            if have == *wanted {
+                score += 5;
+            }
+        }
+        Some(score - 10 * available.len() as i64)
+    }
+}
+
+/// Split a parameter list at the commas that are not nested in brackets
+fn split_top_level(list: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    for (i, c) in list.char_indices() {
+        match c {
+            '<' | '(' | '[' => depth += 1,
+            '>' | ')' | ']' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                parts.push(&list[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&list[start..]);
+    parts
+        .into_iter()
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+        .collect()
+}
+
+/// Lowercase type names in a rendered type, without lifetimes and keywords,
+/// e.g. `result`, `str` and `error` for `Result<&'a str, io::Error>`
+fn type_names(ty: &str) -> Vec<String> {
+    let mut names = Vec::new();
+    let mut start = None;
+    for (i, c) in ty.char_indices().chain([(ty.len(), ' ')]) {
+        if c.is_alphanumeric() || c == '_' {
+            start.get_or_insert(i);
+            continue;
+        }
+        let Some(begin) = start.take() else {
+            continue;
+        };
+        let word = &ty[begin..i];
+        let is_lifetime = ty[..begin].ends_with('\'');
+        // Path prefixes like `std::io` are left out, the last segment names the type
+        let is_prefix = ty[i..].starts_with("::");
+        if word != "_" && !is_lifetime && !is_prefix && !TYPE_KEYWORDS.contains(&word) {
+            names.push(word.to_lowercase());
+        }
+    }
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, kind: &str, inputs: &[&str], output: Option<&str>) -> SearchEntry {
+        SearchEntry {
+            item: DocumentationItem {
+                name: path.rsplit("::").next().unwrap().to_string(),
+                kind: kind.to_string(),
+                path: path.to_string(),
+                ..Default::default()
+            },
+            inputs: inputs.iter().map(|input| input.to_string()).collect(),
+            output: output.map(|output| output.to_string()),
+        }
+    }
+
+    fn paths(results: Vec<DocumentationItem>) -> Vec<String> {
+        results.into_iter().map(|item| item.path).collect()
+    }
+
+    #[test]
+    fn test_parse_signature_query() {
+        assert_eq!(
+            SignatureQuery::parse("fn(&str, HashMap<String, u8>) -> Result"),
+            Some(SignatureQuery {
+                inputs: vec![
+                    vec!["str".to_string()],
+                    vec![
+                        "hashmap".to_string(),
+                        "string".to_string(),
+                        "u8".to_string()
+                    ],
+                ],
+                output: Some(vec!["result".to_string()]),
+            })
+        );
+        assert_eq!(
+            type_names("Result<&'a mut str, std::io::Error>"),
+            vec!["result", "str", "error"]
+        );
+        assert_eq!(SignatureQuery::parse("from_str"), None);
+        assert!(SignatureQuery::parse("-> Vec<u8>").is_some());
+        assert!(is_signature_query(" fn(&str) -> Result"));
+        assert!(!is_signature_query("sync::Mutex"));
+    }
+
+    #[test]
+    fn test_search() {
+        let entries = vec![
+            entry("demo::sync::Mutex", "struct", &[], None),
+            entry(
+                "demo::sync::Mutex::lock",
+                "method",
+                &["Mutex<T>"],
+                Some("&mut T"),
+            ),
+            entry(
+                "demo::sync::Mutex::try_lock",
+                "method",
+                &["Mutex<T>"],
+                Some("Result<&T, TryLockError>"),
+            ),
+            entry("demo::sync::MutexGuard", "struct", &[], None),
+            entry(
+                "demo::parse",
+                "function",
+                &["&str"],
+                Some("Result<Config, Error>"),
+            ),
+            entry(
+                "demo::parse_with",
+                "function",
+                &["&str", "Options"],
+                Some("Result<Config, Error>"),
+            ),
+            entry(
+                "demo::parse_bytes",
+                "function",
+                &["&[u8]"],
+                Some("Result<Config, Error>"),
+            ),
+        ];
+
+        // Exact names first, then prefixes, then fuzzy matches
+        assert_eq!(
+            paths(search(&entries, "mutex", 3)),
+            vec![
+                "demo::sync::Mutex",
+                "demo::sync::MutexGuard",
+                "demo::sync::Mutex::lock"
+            ]
+        );
+        assert_eq!(
+            paths(search(&entries, "Mutex::lock", 1)),
+            vec!["demo::sync::Mutex::lock"]
+        );
+        assert_eq!(
+            paths(search(&entries, "mtxgrd", 10)),
+            vec!["demo::sync::MutexGuard"]
+        );
+
+        // Functions taking a string and returning a Result, best fit first
+        assert_eq!(
+            paths(search(&entries, "fn(&str) -> Result", 10)),
+            vec!["demo::parse", "demo::parse_with"]
+        );
+        assert_eq!(
+            paths(search(&entries, "-> Result<_, TryLockError>", 10)),
+            vec!["demo::sync::Mutex::try_lock"]
+        );
+        assert!(search(&entries, "fn(Options, u64)", 10).is_empty());
+    }
+}
@@ -1,9 +1,26 @@
-use anyhow::{Context, Result};
-use reqwest::Client;
 use serde::Deserialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
 use tracing::{debug, info};
 
-use crate::types::{CrateDocumentation, DocumentationItem};
+use crate::api_diff;
+use crate::config::ServerConfig;
+use crate::crate_archive;
+use crate::doc_search;
+use crate::error::{Error, Result};
+use crate::http::HttpClient;
+use crate::local_registry::{self, LocalRegistry};
+use crate::rustdoc_html;
+use crate::rustdoc_json::{self, RustdocCrate};
+use crate::rustdoc_markdown::{self, ItemPage};
+use crate::types::{
+    ApiDiff, CrateDocumentation, DocumentationItem, DocumentationSearch, ItemDocumentation,
+    SemverBump,
+};
+
+/// Total size of the rustdoc JSON kept parsed between calls; the parsed values
+/// take several times as much memory
+const RUSTDOC_CACHE_SIZE: u64 = 256 * 1024 * 1024;
 
 #[derive(Deserialize)]
 #[allow(dead_code)]
@@ -31,18 +48,22 @@ struct DocsRsCrateResponse {
 
 /// Client for interacting with docs.rs
 pub struct DocsClient {
-    pub(crate) http_client: Client,
+    pub(crate) http_client: HttpClient,
+    pub(crate) offline: bool,
+    local_registry: LocalRegistry,
+    /// Parsed rustdoc JSON by crate name and version, least recently used first
+    rustdoc_cache: Mutex<VecDeque<(String, String, Arc<RustdocCrate>)>>,
 }
 
 impl DocsClient {
     /// Create a new DocsClient
-    pub fn new() -> Self {
-        let http_client = Client::builder()
-            .user_agent(format!("{}/{}", env!("CARGO_PKG_NAME"), env!("CARGO_PKG_VERSION")))
-            .build()
-            .expect("Failed to create HTTP client");
-
-        Self { http_client }
+    pub fn new(config: &ServerConfig, http_client: HttpClient) -> Self {
+        Self {
+            http_client,
+            offline: config.offline,
+            local_registry: LocalRegistry::discover(),
+            rustdoc_cache: Mutex::new(VecDeque::new()),
+        }
     }
 
     /// Get documentation information for a crate from docs.rs
@@ -53,9 +74,13 @@ impl DocsClient {
     ) -> Result<CrateDocumentation> {
         // Input validation
         if name.trim().is_empty() {
-            return Err(anyhow::anyhow!("Crate name cannot be empty"));
+            return Err(Error::InvalidInput(
+                "Crate name cannot be empty".to_string(),
+            ));
         }
 
+        self.ensure_online()?;
+
         // Use correct docs.rs URL pattern
         let docs_url = match version {
             Some(v) => format!("https://docs.rs/{}/{}/{}/", name, v, name),
@@ -64,24 +89,22 @@ impl DocsClient {
         debug!("Fetching docs from: {}", docs_url);
 
         // Get the documentation page
-        let response = self
-            .http_client
-            .get(&docs_url)
-            .send()
-            .await
-            .context("Failed to get documentation page")?;
+        let response = self.http_client.get(&docs_url).await?;
 
         let final_url = response.url().clone();
         let status = response.status();
 
         if !status.is_success() {
             if status == 404 {
-                return Err(anyhow::anyhow!(
+                return Err(Error::NotFound(format!(
                     "Documentation for crate '{}' not found on docs.rs",
                     name
-                ));
+                )));
             }
-            return Err(anyhow::anyhow!("Failed to access docs.rs: {}", status));
+            return Err(Error::UpstreamStatus {
+                url: docs_url,
+                status: status.as_u16(),
+            });
         }
 
         // Extract version from final URL if not provided
@@ -91,143 +114,463 @@ impl DocsClient {
             self.extract_version_from_url(&final_url, name)?
         };
 
-        // Try to get README content
-        let readme = self.get_readme_content(name, &actual_version).await.ok();
-
-        // Get documentation structure by scraping the main docs page
-        let (modules, items) = self
-            .get_documentation_structure(name, &actual_version)
-            .await?;
-
-        let doc = CrateDocumentation {
-            name: name.to_string(),
-            version: actual_version,
-            description: None, // We'll need to enhance this by parsing the docs page
-            readme,
-            modules,
-            items,
+        let (readme, readme_source) = match self.get_readme_content(name, &actual_version).await {
+            Ok((readme, source)) => (Some(readme), Some(source.to_string())),
+            Err(e) => {
+                debug!("No README for crate '{}' {}: {}", name, actual_version, e);
+                (None, None)
+            }
+        };
+
+        // Prefer rustdoc JSON, and scrape the main docs page for releases without it
+        let rustdoc = self.get_usable_rustdoc_json(name, &actual_version).await;
+
+        let doc = match rustdoc {
+            Some(krate) => CrateDocumentation {
+                name: name.to_string(),
+                version: actual_version,
+                description: krate.description(),
+                docs: krate.docs(),
+                readme,
+                readme_source,
+                modules: krate.module_paths(),
+                items: krate.items(),
+                source: "rustdoc_json".to_string(),
+                format_version: Some(krate.format_version),
+            },
+            None => {
+                let (page, items) = self
+                    .get_documentation_structure(name, &actual_version)
+                    .await?;
+                CrateDocumentation {
+                    name: name.to_string(),
+                    version: actual_version,
+                    description: page.docs.as_deref().and_then(rustdoc_json::summary),
+                    docs: page.docs,
+                    readme,
+                    readme_source,
+                    modules: rustdoc_html::module_paths(&items),
+                    items,
+                    source: "html".to_string(),
+                    format_version: None,
+                }
+            }
         };
 
         info!("Retrieved documentation structure for crate '{}'", name);
         Ok(doc)
     }
 
-    async fn get_readme_content(&self, name: &str, version: &str) -> Result<String> {
-        // Try multiple potential README locations
-        let readme_urls = vec![
-            format!(
-                "https://docs.rs/{}/{}/src/{}/README.md",
-                name, version, name
-            ),
-            format!(
-                "https://docs.rs/{}/{}/src/{}/readme.md",
-                name, version, name
-            ),
-            format!(
-                "https://docs.rs/{}/{}/src/{}/Readme.md",
-                name, version, name
-            ),
-        ];
+    /// Get the documentation of a single item, e.g. `tokio::sync::Mutex`
+    ///
+    /// Read from the rustdoc JSON docs.rs built for the release, or from the item's
+    /// page for releases without it.
+    pub async fn get_item_documentation(
+        &self,
+        name: &str,
+        version: Option<&str>,
+        path: &str,
+    ) -> Result<ItemDocumentation> {
+        // Input validation
+        if name.trim().is_empty() {
+            return Err(Error::InvalidInput(
+                "Crate name cannot be empty".to_string(),
+            ));
+        }
+        if path.trim().is_empty() {
+            return Err(Error::InvalidInput("Item path cannot be empty".to_string()));
+        }
 
-        for url in readme_urls {
-            debug!("Trying README at: {}", url);
+        self.ensure_online()?;
+
+        // docs.rs resolves `latest` for rustdoc JSON downloads and pages
+        let version = version.unwrap_or("latest");
+        let rustdoc = self.get_usable_rustdoc_json(name, version).await;
+
+        let doc = match rustdoc {
+            Some(krate) => krate.item_documentation(path).ok_or_else(|| {
+                Error::NotFound(format!(
+                    "No public item '{}' in crate '{}' {}",
+                    path,
+                    name,
+                    krate.crate_version.as_deref().unwrap_or(version)
+                ))
+            })?,
+            None => self.get_item_page(name, version, path).await?,
+        };
 
-            match self.http_client.get(&url).send().await {
-                Ok(response) if response.status().is_success() => match response.text().await {
-                    Ok(content) => {
-                        info!("Found README for crate '{}' at: {}", name, url);
-                        return Ok(content);
-                    }
-                    Err(e) => debug!("Failed to read README content: {}", e),
-                },
-                Ok(response) => debug!("README not found at {}: {}", url, response.status()),
-                Err(e) => debug!("Failed to fetch README from {}: {}", url, e),
-            }
+        info!(
+            "Retrieved documentation of '{}' in crate '{}'",
+            doc.path, name
+        );
+        Ok(doc)
+    }
+
+    /// Read an item's documentation from its docs.rs page
+    ///
+    /// The page is found through the listing of the item's module, so associated
+    /// items such as methods cannot be looked up this way.
+    async fn get_item_page(
+        &self,
+        name: &str,
+        version: &str,
+        path: &str,
+    ) -> Result<ItemDocumentation> {
+        let not_found = || {
+            Error::NotFound(format!(
+                "No public item '{}' in crate '{}' {}",
+                path, name, version
+            ))
+        };
+
+        let lib = name.replace('-', "_");
+        let mut segments: Vec<&str> = path
+            .split("::")
+            .map(str::trim)
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        if segments
+            .first()
+            .is_some_and(|first| first.replace('-', "_") == lib)
+        {
+            segments.remove(0);
         }
+        let Some((item_name, modules)) = segments.split_last() else {
+            return Err(not_found());
+        };
 
-        Err(anyhow::anyhow!("README not found"))
+        let module_url = format!(
+            "https://docs.rs/{}/{}/{}/{}index.html",
+            name,
+            version,
+            lib,
+            modules
+                .iter()
+                .map(|module| format!("{}/", module))
+                .collect::<String>()
+        );
+        let (module_html, module_url) = self.get_page(&module_url).await?.ok_or_else(not_found)?;
+        let module_path = std::iter::once(lib.as_str())
+            .chain(modules.iter().copied())
+            .collect::<Vec<_>>()
+            .join("::");
+        let (item, href) =
+            rustdoc_html::find_item(&module_html, &module_path, item_name).ok_or_else(not_found)?;
+
+        let item_url = module_url.join(&href).map_err(|e| {
+            Error::InvalidResponse(format!(
+                "Invalid link to '{}' on {}: {}",
+                path, module_url, e
+            ))
+        })?;
+        let (html, item_url) = self
+            .get_page(item_url.as_str())
+            .await?
+            .ok_or_else(not_found)?;
+        let page = rustdoc_markdown::item_page(&html, item_url.as_str());
+        let docs = [page.docs, page.sections]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join("\n\n");
+
+        Ok(ItemDocumentation {
+            crate_name: lib,
+            version: self
+                .extract_version_from_url(&item_url, name)
+                .unwrap_or_else(|_| version.to_string()),
+            path: item.path,
+            name: item.name,
+            kind: item.kind,
+            signature: page.declaration,
+            docs: Some(docs).filter(|docs| !docs.is_empty()),
+            deprecation: page.deprecation,
+            methods: Vec::new(),
+            trait_impls: Vec::new(),
+            source: "html".to_string(),
+            format_version: None,
+        })
     }
 
-    async fn get_documentation_structure(
+    fn ensure_online(&self) -> Result<()> {
+        if self.offline {
+            return Err(Error::Offline(
+                "Documentation is not available in offline mode: it is served by docs.rs, \
+                which cannot be reached without network access"
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Get the README of a crate version as markdown, and where it was read from
+    ///
+    /// The local cargo registry is tried first, then the README crates.io renders,
+    /// then the `.crate` archive, at the path its `Cargo.toml` names.
+    async fn get_readme_content(
         &self,
         name: &str,
         version: &str,
-    ) -> Result<(Vec<String>, Vec<DocumentationItem>)> {
-        let docs_url = format!("https://docs.rs/{}/{}/{}/", name, version, name);
-        debug!("Fetching documentation structure from: {}", docs_url);
+    ) -> Result<(String, &'static str)> {
+        match self.local_registry.read_readme(name, version) {
+            Ok(readme) => {
+                info!(
+                    "Read README of crate '{}' {} from the local registry",
+                    name, version
+                );
+                return Ok((readme, "local_registry"));
+            }
+            Err(e) => debug!("No local README for crate '{}' {}: {:#}", name, version, e),
+        }
 
-        let response = self
-            .http_client
-            .get(&docs_url)
-            .send()
-            .await
-            .context("Failed to get documentation page")?;
+        match self.get_crates_io_readme(name, version).await {
+            Ok(Some(readme)) => {
+                info!("Read README of crate '{}' {} from crates.io", name, version);
+                return Ok((readme, "crates_io"));
+            }
+            Ok(None) => debug!("crates.io has no README for crate '{}' {}", name, version),
+            Err(e) => debug!(
+                "Failed to get README of crate '{}' from crates.io: {}",
+                name, e
+            ),
+        }
 
-        if !response.status().is_success() {
-            return Err(anyhow::anyhow!(
-                "Failed to access documentation page: {}",
-                response.status()
-            ));
+        let archive = crate_archive::download(&self.http_client, name, version).await?;
+        let readme = local_registry::read_readme(|path| {
+            local_registry::read_crate_file(archive.as_slice(), path)
+        })
+        .map_err(|e| {
+            Error::NotFound(format!(
+                "README not found for crate '{}' {}: {:#}",
+                name, version, e
+            ))
+        })?;
+        info!(
+            "Read README of crate '{}' {} from its .crate archive",
+            name, version
+        );
+        Ok((readme, "crate_archive"))
+    }
+
+    /// Get the README crates.io rendered for a crate version, converted back to markdown
+    async fn get_crates_io_readme(&self, name: &str, version: &str) -> Result<Option<String>> {
+        let readme_url = format!(
+            "https://crates.io/api/v1/crates/{}/{}/readme",
+            name, version
+        );
+        debug!("Fetching README from: {}", readme_url);
+
+        let response = self.http_client.get(&readme_url).await?;
+
+        // Missing READMEs come back as 404 from crates.io, or 403 from its storage
+        let status = response.status();
+        if status == 404 || status == 403 {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(Error::UpstreamStatus {
+                url: readme_url,
+                status: status.as_u16(),
+            });
         }
 
-        let html_content = response
+        let html = response
             .text()
             .await
-            .context("Failed to read documentation page")?;
+            .map_err(|e| Error::InvalidResponse(format!("Failed to read README: {}", e)))?;
+        let readme = rustdoc_markdown::html_to_markdown(&html);
+        Ok(Some(readme).filter(|readme| !readme.is_empty()))
+    }
 
-        // Simple HTML parsing to extract module and item information
-        // In a production implementation, you might want to use a proper HTML parser like scraper
-        let modules = self.extract_modules_from_html(&html_content);
-        let items = self.extract_items_from_html(&html_content, name);
+    /// Download the rustdoc JSON docs.rs built for a crate version
+    ///
+    /// Returns `None` when there is none: docs.rs only builds rustdoc JSON for
+    /// releases published since it started doing so.
+    async fn get_rustdoc_json(
+        &self,
+        name: &str,
+        version: &str,
+    ) -> Result<Option<Arc<RustdocCrate>>> {
+        if let Some(krate) = self.cached_rustdoc(name, version) {
+            return Ok(Some(krate));
+        }
 
-        Ok((modules, items))
+        let json_url = format!("https://docs.rs/crate/{}/{}/json", name, version);
+        debug!("Fetching rustdoc JSON from: {}", json_url);
+
+        let response = self.http_client.get(&json_url).await?;
+
+        let status = response.status();
+        if status == 404 {
+            debug!("No rustdoc JSON for crate '{}' {}", name, version);
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(Error::UpstreamStatus {
+                url: json_url,
+                status: status.as_u16(),
+            });
+        }
+        if response
+            .content_length()
+            .is_some_and(|length| length > rustdoc_json::MAX_RUSTDOC_JSON_SIZE)
+        {
+            return Err(Error::InvalidResponse(format!(
+                "The rustdoc JSON of '{}' {} is larger than {} bytes",
+                name,
+                version,
+                rustdoc_json::MAX_RUSTDOC_JSON_SIZE
+            )));
+        }
+
+        // `latest` redirects to the release, which may be cached already
+        let resolved = self
+            .extract_version_from_url(response.url(), name)
+            .ok()
+            .filter(|resolved| semver::Version::parse(resolved).is_ok());
+        if let Some(krate) = resolved
+            .as_deref()
+            .and_then(|resolved| self.cached_rustdoc(name, resolved))
+        {
+            return Ok(Some(krate));
+        }
+
+        let bytes = response
+            .bytes()
+            .await
+            .map_err(|e| Error::InvalidResponse(format!("Failed to read rustdoc JSON: {}", e)))?;
+        let krate = Arc::new(rustdoc_json::parse(&bytes)?);
+
+        let version = krate
+            .crate_version
+            .clone()
+            .or(resolved)
+            .unwrap_or_else(|| version.to_string());
+        info!(
+            "Read rustdoc JSON (format version {}) for crate '{}' {}",
+            krate.format_version, name, version
+        );
+        self.cache_rustdoc(name, version, krate.clone());
+        Ok(Some(krate))
     }
 
-    fn extract_modules_from_html(&self, html: &str) -> Vec<String> {
-        let mut modules = Vec::new();
-
-        // Look for module links in the documentation
-        // This is a simplified implementation - a real parser would be more robust
-        for line in html.lines() {
-            if line.contains("href=") && line.contains("/struct.")
-                || line.contains("/enum.")
-                || line.contains("/trait.")
-                || line.contains("/fn.")
-            {
-                // Extract module names from href attributes
-                // This is a basic implementation
-                continue;
-            }
-            if line.contains("mod ") || line.contains("module") {
-                // Extract module information
-                continue;
+    /// Rustdoc JSON read before for a crate version, `None` for `latest` and
+    /// anything else that names no single release
+    fn cached_rustdoc(&self, name: &str, version: &str) -> Option<Arc<RustdocCrate>> {
+        semver::Version::parse(version).ok()?;
+        let mut cache = self.rustdoc_cache.lock().unwrap_or_else(|e| e.into_inner());
+        let position = cache.iter().position(|(cached_name, cached_version, _)| {
+            cached_name == name && cached_version == version
+        })?;
+        let entry = cache.remove(position)?;
+        let krate = entry.2.clone();
+        cache.push_back(entry);
+        Some(krate)
+    }
+
+    fn cache_rustdoc(&self, name: &str, version: String, krate: Arc<RustdocCrate>) {
+        let mut cache = self.rustdoc_cache.lock().unwrap_or_else(|e| e.into_inner());
+        cache.retain(|(cached_name, cached_version, _)| {
+            cached_name != name || *cached_version != version
+        });
+        cache.push_back((name.to_string(), version, krate));
+        // The newest entry is kept even when it alone is over the limit
+        while cache.len() > 1
+            && cache
+                .iter()
+                .map(|(_, _, krate)| krate.json_size)
+                .sum::<u64>()
+                > RUSTDOC_CACHE_SIZE
+        {
+            cache.pop_front();
+        }
+    }
+
+    /// Rustdoc JSON of a release for lookups with an HTML fallback, `None` when
+    /// there is none or it cannot be read
+    async fn get_usable_rustdoc_json(
+        &self,
+        name: &str,
+        version: &str,
+    ) -> Option<Arc<RustdocCrate>> {
+        match self.get_rustdoc_json(name, version).await {
+            Ok(rustdoc) => rustdoc,
+            Err(e) => {
+                debug!("Unusable rustdoc JSON for crate '{}': {}", name, e);
+                None
             }
         }
+    }
+
+    /// Read every item of a release from its `all.html` page, with the version
+    /// docs.rs resolved
+    async fn get_all_items(
+        &self,
+        name: &str,
+        version: &str,
+    ) -> Result<(String, Vec<DocumentationItem>)> {
+        let lib_name = name.replace('-', "_");
+        let all_url = format!("https://docs.rs/{}/{}/{}/all.html", name, version, lib_name);
+        let (html, page_url) = self.get_page(&all_url).await?.ok_or_else(|| {
+            Error::NotFound(format!(
+                "Documentation for crate '{}' {} not found on docs.rs",
+                name, version
+            ))
+        })?;
+
+        let version = self
+            .extract_version_from_url(&page_url, name)
+            .unwrap_or_else(|_| version.to_string());
+        Ok((version, rustdoc_html::extract_all_items(&html, &lib_name)))
+    }
+
+    /// Read the crate root page: the crate-level docs and the items it lists
+    async fn get_documentation_structure(
+        &self,
+        name: &str,
+        version: &str,
+    ) -> Result<(ItemPage, Vec<DocumentationItem>)> {
+        let docs_url = format!("https://docs.rs/{}/{}/{}/", name, version, name);
+        debug!("Fetching documentation structure from: {}", docs_url);
 
-        // For now, return some common Rust modules as placeholders
-        modules.extend_from_slice(&["lib".to_string(), "prelude".to_string()]);
+        let (html_content, page_url) = self.get_page(&docs_url).await?.ok_or_else(|| {
+            Error::NotFound(format!(
+                "Documentation for crate '{}' {} not found on docs.rs",
+                name, version
+            ))
+        })?;
 
-        modules
+        // Rustdoc paths use the library name, which has underscores instead of hyphens
+        let items = rustdoc_html::extract_items(&html_content, &name.replace('-', "_"));
+        let page = rustdoc_markdown::item_page(&html_content, page_url.as_str());
+
+        Ok((page, items))
     }
 
-    fn extract_items_from_html(&self, _html: &str, crate_name: &str) -> Vec<DocumentationItem> {
-        let mut items = Vec::new();
-
-        // This is a simplified implementation
-        // In practice, you'd want to parse the HTML properly to extract:
-        // - Structs, enums, traits, functions
-        // - Their descriptions
-        // - Their paths within the crate
-
-        // For now, add some placeholder items
-        items.push(DocumentationItem {
-            name: "lib".to_string(),
-            kind: "module".to_string(),
-            path: format!("{}/lib", crate_name),
-            description: Some("Main library module".to_string()),
-        });
+    /// Fetch a docs.rs page, following redirects such as `latest` to the release
+    ///
+    /// Returns the page with its final URL, or `None` when there is no such page.
+    async fn get_page(&self, url: &str) -> Result<Option<(String, reqwest::Url)>> {
+        debug!("Fetching documentation page: {}", url);
 
-        items
+        let response = self.http_client.get(url).await?;
+
+        let status = response.status();
+        if status == 404 {
+            return Ok(None);
+        }
+        if !status.is_success() {
+            return Err(Error::UpstreamStatus {
+                url: url.to_string(),
+                status: status.as_u16(),
+            });
+        }
+
+        let final_url = response.url().clone();
+        let html = response.text().await.map_err(|e| {
+            Error::InvalidResponse(format!("Failed to read documentation page: {}", e))
+        })?;
+        Ok(Some((html, final_url)))
     }
 
     fn extract_version_from_url(&self, url: &reqwest::Url, name: &str) -> Result<String> {
@@ -241,72 +584,210 @@ impl DocsClient {
             }
         }
 
-        Err(anyhow::anyhow!(
+        Err(Error::InvalidResponse(format!(
             "Could not extract version from URL: {}",
             url
-        ))
+        )))
     }
 
-    #[allow(dead_code)]
-    pub async fn search_documentation(&self, query: &str) -> Result<Vec<CrateDocumentation>> {
-        // docs.rs doesn't have a direct search API, so this is a placeholder
-        // In practice, you might want to use the crates.io search and then
-        // try to get documentation for each result
-
-        debug!("Searching documentation for query: {}", query);
+    /// Compare the public API of two releases of a crate, classifying each change
+    /// by the semver bump it requires
+    ///
+    /// Items come from rustdoc JSON when docs.rs has it for both releases, and
+    /// from the `all.html` item lists otherwise.
+    pub async fn diff_api(
+        &self,
+        name: &str,
+        old_version: &str,
+        new_version: Option<&str>,
+    ) -> Result<ApiDiff> {
+        // Input validation
+        if name.trim().is_empty() {
+            return Err(Error::InvalidInput(
+                "Crate name cannot be empty".to_string(),
+            ));
+        }
+        if old_version.trim().is_empty() {
+            return Err(Error::InvalidInput(
+                "Old version cannot be empty".to_string(),
+            ));
+        }
 
-        // For now, return empty results
-        Ok(vec![])
-    }
+        self.ensure_online()?;
 
-    #[allow(dead_code)]
-    pub async fn get_crate_examples(&self, name: &str, version: &str) -> Result<Vec<String>> {
-        let examples_url = format!(
-            "https://docs.rs/{}/{}/src/{}/examples/",
-            name, version, name
-        );
-        debug!("Fetching examples from: {}", examples_url);
+        let new_version = new_version.unwrap_or("latest");
+        let old_rustdoc = self.get_usable_rustdoc_json(name, old_version).await;
+        let new_rustdoc = match old_rustdoc {
+            Some(_) => self.get_usable_rustdoc_json(name, new_version).await,
+            None => None,
+        };
 
-        let response = self.http_client.get(&examples_url).send().await;
+        let (source, (old_version, old_items), (new_version, new_items)) =
+            match (old_rustdoc, new_rustdoc) {
+                (Some(old), Some(new)) => (
+                    "rustdoc_json",
+                    (
+                        old.crate_version
+                            .clone()
+                            .unwrap_or_else(|| old_version.to_string()),
+                        old.api_items(),
+                    ),
+                    (
+                        new.crate_version
+                            .clone()
+                            .unwrap_or_else(|| new_version.to_string()),
+                        new.api_items(),
+                    ),
+                ),
+                _ => (
+                    "html",
+                    self.get_all_items(name, old_version).await?,
+                    self.get_all_items(name, new_version).await?,
+                ),
+            };
+
+        let changes = api_diff::diff(&old_items, &new_items);
+        let count = |change: &str| changes.iter().filter(|c| c.change == change).count();
+        let api_diff = ApiDiff {
+            crate_name: name.to_string(),
+            declared_bump: api_diff::declared_bump(&old_version, &new_version),
+            required_bump: changes
+                .iter()
+                .map(|c| c.severity)
+                .max()
+                .unwrap_or(SemverBump::None),
+            added: count("added"),
+            removed: count("removed"),
+            changed: count("changed"),
+            source: source.to_string(),
+            old_version,
+            new_version,
+            changes,
+        };
 
-        match response {
-            Ok(resp) if resp.status().is_success() => {
-                let html_content = resp.text().await.context("Failed to read examples page")?;
-                let examples = self.extract_examples_from_html(&html_content);
-                Ok(examples)
-            }
-            _ => {
-                debug!("Examples not found for crate '{}'", name);
-                Ok(vec![])
-            }
-        }
+        info!(
+            "Compared the API of crate '{}' {} and {}: {} changes",
+            name,
+            api_diff.old_version,
+            api_diff.new_version,
+            api_diff.changes.len()
+        );
+        Ok(api_diff)
     }
 
-    #[allow(dead_code)]
-    fn extract_examples_from_html(&self, html: &str) -> Vec<String> {
-        let examples = Vec::new();
-
-        // Extract example file names from the directory listing
-        // This is a simplified implementation
-        for line in html.lines() {
-            if line.contains(".rs") && line.contains("href=") {
-                // Extract example file names
-                // In practice, you'd want proper HTML parsing
-                continue;
-            }
+    /// Search the items of a crate by name or path, fuzzily, or by type signature
+    /// with queries like `fn(&str) -> Result`
+    ///
+    /// Type signatures come from rustdoc JSON; releases without it can only be
+    /// searched by name.
+    pub async fn search_documentation(
+        &self,
+        name: &str,
+        version: Option<&str>,
+        query: &str,
+        limit: usize,
+    ) -> Result<DocumentationSearch> {
+        // Input validation
+        if name.trim().is_empty() {
+            return Err(Error::InvalidInput(
+                "Crate name cannot be empty".to_string(),
+            ));
+        }
+        if query.trim().is_empty() {
+            return Err(Error::InvalidInput(
+                "Search query cannot be empty".to_string(),
+            ));
         }
 
-        examples
+        self.ensure_online()?;
+
+        // Releases without rustdoc JSON are searched by name in their list of all items
+        let version = version.unwrap_or("latest");
+        let (crate_name, version, entries, source) =
+            match self.get_usable_rustdoc_json(name, version).await {
+                Some(krate) => (
+                    krate.crate_name(),
+                    krate
+                        .crate_version
+                        .clone()
+                        .unwrap_or_else(|| version.to_string()),
+                    krate.search_entries(),
+                    "rustdoc_json",
+                ),
+                None if doc_search::is_signature_query(query) => {
+                    return Err(Error::NotFound(format!(
+                        "docs.rs has no rustdoc JSON for crate '{}' {}, which searching by \
+                        type signature needs",
+                        name, version
+                    )));
+                }
+                None => {
+                    let (version, items) = self.get_all_items(name, version).await?;
+                    let entries = items
+                        .into_iter()
+                        .map(doc_search::SearchEntry::without_signature)
+                        .collect();
+                    (name.replace('-', "_"), version, entries, "html")
+                }
+            };
+        let results = doc_search::search(&entries, query, limit);
+
+        info!(
+            "Found {} documented items matching '{}' in crate '{}'",
+            results.len(),
+            query,
+            name
+        );
+        Ok(DocumentationSearch {
+            crate_name,
+            version,
+            query: query.to_string(),
+            results,
+            source: source.to_string(),
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use anyhow::Result;
+
+    fn test_client() -> DocsClient {
+        let config = ServerConfig::default();
+        let http_client = HttpClient::new(&config).expect("Failed to create HTTP client");
+        DocsClient::new(&config, http_client)
+    }
+
+    #[test]
+    fn test_rustdoc_cache() -> Result<()> {
+        let client = test_client();
+        let mut krate =
+            rustdoc_json::parse(include_bytes!("../tests/fixtures/rustdoc/demo-v57.json"))?;
+        // Room for four releases
+        krate.json_size = RUSTDOC_CACHE_SIZE / 4;
+        let krate = Arc::new(krate);
+
+        client.cache_rustdoc("demo", "0.1.0".to_string(), krate.clone());
+        let cached = client.cached_rustdoc("demo", "0.1.0").unwrap();
+        assert!(Arc::ptr_eq(&cached, &krate));
+        // `latest` may have moved on since, it is only resolved through docs.rs
+        assert!(client.cached_rustdoc("demo", "latest").is_none());
+
+        for patch in 1..4 {
+            client.cache_rustdoc("demo", format!("0.2.{}", patch), krate.clone());
+        }
+        // Reading 0.1.0 again keeps it when the next release evicts the oldest one
+        assert!(client.cached_rustdoc("demo", "0.1.0").is_some());
+        client.cache_rustdoc("demo", "0.3.0".to_string(), krate.clone());
+        assert!(client.cached_rustdoc("demo", "0.1.0").is_some());
+        assert!(client.cached_rustdoc("demo", "0.2.1").is_none());
+        Ok(())
+    }
 
     #[tokio::test]
     async fn test_get_crate_documentation() -> Result<()> {
-        let client = DocsClient::new();
+        let client = test_client();
 
         // Test with a well-known crate that should have docs
         match client.get_crate_documentation("serde", None).await {
@@ -325,11 +806,11 @@ mod tests {
 
     #[tokio::test]
     async fn test_get_readme_content() -> Result<()> {
-        let client = DocsClient::new();
+        let client = test_client();
 
         // Test README retrieval - this might fail if the exact path doesn't exist
         match client.get_readme_content("serde", "1.0.0").await {
-            Ok(readme) => {
+            Ok((readme, _source)) => {
                 assert!(!readme.is_empty());
             }
             Err(_) => {
@@ -343,7 +824,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_docs_rs_url_accessibility() -> Result<()> {
-        let client = DocsClient::new();
+        let client = test_client();
 
         // Test correct docs.rs URL patterns to ensure they work
         let test_urls = vec![
@@ -355,7 +836,7 @@ mod tests {
         for url in test_urls {
             println!("Testing URL accessibility: {}", url);
 
-            match client.http_client.get(url).send().await {
+            match client.http_client.get(url).await {
                 Ok(response) => {
                     let status = response.status();
                     println!("  Status: {}", status);
@@ -378,7 +859,7 @@ mod tests {
         let bad_url = "https://docs.rs/crate/serde/target-redirect";
         println!("Testing problematic URL: {}", bad_url);
 
-        match client.http_client.get(bad_url).send().await {
+        match client.http_client.get(bad_url).await {
             Ok(response) => {
                 let status = response.status();
                 println!("  Problematic URL status: {}", status);
@@ -400,7 +881,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_fixed_crate_documentation_integration() -> Result<()> {
-        let client = DocsClient::new();
+        let client = test_client();
 
         // Test the fixed implementation with a well-known crate
         println!("Testing fixed get_crate_documentation with 'anyhow'");
@@ -444,10 +925,10 @@ mod tests {
 
     #[tokio::test]
     async fn test_ratatui_docs_specifically() -> Result<()> {
-        let client = DocsClient::new();
+        let client = test_client();
 
         println!("Testing ratatui documentation retrieval...");
-        
+
         match client.get_crate_documentation("ratatui", None).await {
             Ok(docs) => {
                 println!("✅ Successfully retrieved ratatui docs!");
@@ -459,10 +940,12 @@ mod tests {
             Err(e) => {
                 println!("❌ Failed to retrieve ratatui docs: {}", e);
                 // Don't fail the test since network issues can happen
-                println!("This may be due to network issues or docs.rs being temporarily unavailable");
+                println!(
+                    "This may be due to network issues or docs.rs being temporarily unavailable"
+                );
             }
         }
-        
+
         Ok(())
     }
 }
@@ -0,0 +1,170 @@
+use serde_json::json;
+use std::time::Duration;
+use thiserror::Error;
+
+/// JSON-RPC error codes for protocol-level faults
+pub mod rpc {
+    pub const PARSE_ERROR: i32 = -32700;
+    pub const INVALID_REQUEST: i32 = -32600;
+    pub const METHOD_NOT_FOUND: i32 = -32601;
+    pub const INVALID_PARAMS: i32 = -32602;
+}
+
+/// Errors returned by the crates.io and docs.rs clients and surfaced by the tools
+#[derive(Debug, Error)]
+pub enum Error {
+    /// A tool argument is missing or malformed
+    #[error("{0}")]
+    InvalidInput(String),
+
+    /// The crate or upstream resource does not exist
+    #[error("{0}")]
+    NotFound(String),
+
+    /// The crate exists but the requested version does not
+    #[error("Version '{version}' of crate '{name}' not found")]
+    VersionNotFound { name: String, version: String },
+
+    /// The upstream kept answering 429 until retries were exhausted
+    #[error("Request to {url} was rate limited after {attempts} attempt{}", plural(.attempts))]
+    RateLimited {
+        url: String,
+        attempts: u32,
+        retry_after: Option<Duration>,
+    },
+
+    /// The upstream could not be reached or kept failing with 5xx
+    #[error("{message} after {attempts} attempt{}", plural(.attempts))]
+    UpstreamUnavailable { message: String, attempts: u32 },
+
+    /// The upstream answered with an unexpected, non-retryable status
+    #[error("Request to {url} failed: {status}")]
+    UpstreamStatus { url: String, status: u16 },
+
+    /// The upstream answered with something that could not be parsed
+    #[error("{0}")]
+    InvalidResponse(String),
+
+    /// Neither the git index nor the sparse index cache can be used
+    #[error("{0}")]
+    IndexUnavailable(String),
+
+    /// The tool needs network access but the server runs in offline mode
+    #[error("{0}")]
+    Offline(String),
+
+    /// Anything else, e.g. I/O or serialization failures
+    #[error(transparent)]
+    Internal(anyhow::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+fn plural(count: &u32) -> &'static str {
+    if *count == 1 { "" } else { "s" }
+}
+
+impl Error {
+    /// Stable machine-readable code for this error
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::InvalidInput(_) => "invalid_input",
+            Error::NotFound(_) => "not_found",
+            Error::VersionNotFound { .. } => "version_not_found",
+            Error::RateLimited { .. } => "rate_limited",
+            Error::UpstreamUnavailable { .. } => "upstream_unavailable",
+            Error::UpstreamStatus { .. } => "upstream_status",
+            Error::InvalidResponse(_) => "invalid_response",
+            Error::IndexUnavailable(_) => "index_unavailable",
+            Error::Offline(_) => "offline",
+            Error::Internal(_) => "internal_error",
+        }
+    }
+
+    /// Replace the message of a `NotFound` error, leaving other errors untouched
+    pub fn or_not_found(self, message: impl FnOnce() -> String) -> Self {
+        match self {
+            Error::NotFound(_) => Error::NotFound(message()),
+            e => e,
+        }
+    }
+
+    /// Structured representation used in tool results
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut error = json!({
+            "code": self.code(),
+            "message": self.to_string(),
+        });
+
+        match self {
+            Error::RateLimited {
+                attempts,
+                retry_after,
+                ..
+            } => {
+                error["attempts"] = json!(attempts);
+                if let Some(retry_after) = retry_after {
+                    error["retry_after_secs"] = json!(retry_after.as_secs());
+                }
+            }
+            Error::UpstreamUnavailable { attempts, .. } => {
+                error["attempts"] = json!(attempts);
+            }
+            Error::UpstreamStatus { status, .. } => {
+                error["status"] = json!(status);
+            }
+            _ => {}
+        }
+
+        error
+    }
+}
+
+impl From<anyhow::Error> for Error {
+    fn from(error: anyhow::Error) -> Self {
+        // Keep typed errors that passed through an anyhow context intact
+        match error.downcast::<Error>() {
+            Ok(error) => error,
+            Err(error) => Error::Internal(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_codes_and_json() {
+        let error = Error::RateLimited {
+            url: "https://crates.io/api/v1/crates/serde".to_string(),
+            attempts: 4,
+            retry_after: Some(Duration::from_secs(60)),
+        };
+        assert_eq!(error.code(), "rate_limited");
+        assert_eq!(
+            error.to_json(),
+            json!({
+                "code": "rate_limited",
+                "message": "Request to https://crates.io/api/v1/crates/serde was rate limited after 4 attempts",
+                "attempts": 4,
+                "retry_after_secs": 60
+            })
+        );
+
+        let error =
+            Error::NotFound("gone".to_string()).or_not_found(|| "Crate 'x' not found".into());
+        assert_eq!(error.to_string(), "Crate 'x' not found");
+
+        // Typed errors survive a round trip through anyhow
+        let wrapped: Error = anyhow::Error::new(Error::VersionNotFound {
+            name: "serde".to_string(),
+            version: "9.9.9".to_string(),
+        })
+        .into();
+        assert_eq!(wrapped.code(), "version_not_found");
+
+        let internal: Error = anyhow::anyhow!("disk on fire").into();
+        assert_eq!(internal.code(), "internal_error");
+    }
+}
@@ -0,0 +1,271 @@
+use anyhow::Context;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::Write;
+
+use crate::error::{Error, Result};
+use crate::types::{CrateDependency, DependencyEdge, DependencyGraph, DependencyTree, GraphNode};
+
+/// Output format of a dependency listing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// The listing as the tool returns it by default
+    List,
+    /// A node/edge JSON document
+    Json,
+    /// Graphviz DOT
+    Dot,
+    /// Mermaid flowchart
+    Mermaid,
+}
+
+impl GraphFormat {
+    pub fn parse(format: Option<&str>) -> Result<Self> {
+        match format.unwrap_or("list") {
+            "list" => Ok(GraphFormat::List),
+            "json" => Ok(GraphFormat::Json),
+            "dot" => Ok(GraphFormat::Dot),
+            "mermaid" => Ok(GraphFormat::Mermaid),
+            other => Err(Error::InvalidInput(format!(
+                "Unknown format '{}', expected one of: list, json, dot, mermaid",
+                other
+            ))),
+        }
+    }
+}
+
+/// Graph of the direct dependencies of a crate version
+///
+/// Dependencies are not resolved, so their nodes are labelled with the requirement.
+/// A crate required once per kind or target with the same requirement is one node;
+/// different requirements of the same crate are separate nodes.
+pub fn from_dependencies(
+    name: &str,
+    version: &str,
+    dependencies: &[CrateDependency],
+) -> DependencyGraph {
+    // Requirement node ids contain a space, so they never collide with the root
+    let root = format!("{}@{}", name, version);
+    let mut nodes = vec![GraphNode {
+        id: root.clone(),
+        label: format!("{} {}", name, version),
+    }];
+    let mut edges = Vec::new();
+
+    for dep in dependencies {
+        let id = format!("{} {}", dep.name, dep.version_req);
+        if !nodes.iter().any(|node| node.id == id) {
+            nodes.push(GraphNode {
+                id: id.clone(),
+                label: id.clone(),
+            });
+        }
+        edges.push(DependencyEdge {
+            from: root.clone(),
+            to: id,
+            version_req: dep.version_req.clone(),
+            kind: dep.kind.clone(),
+            optional: dep.optional,
+            target: dep.target.clone(),
+        });
+    }
+
+    DependencyGraph { nodes, edges }
+}
+
+/// Graph of a resolved dependency tree
+pub fn from_tree(tree: &DependencyTree) -> DependencyGraph {
+    DependencyGraph {
+        nodes: tree
+            .nodes
+            .iter()
+            .map(|node| GraphNode {
+                id: node.id.clone(),
+                label: format!("{} {}", node.name, node.version),
+            })
+            .collect(),
+        edges: tree.edges.clone(),
+    }
+}
+
+/// Shortest path from one of the roots to every reachable node, roots included
+///
+/// Used to explain why a crate ends up in a build.
+pub fn shortest_paths(
+    roots: &[String],
+    edges: &[(String, String)],
+) -> HashMap<String, Vec<String>> {
+    let mut children: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (from, to) in edges {
+        children.entry(from).or_default().push(to);
+    }
+
+    let mut paths: HashMap<String, Vec<String>> = HashMap::new();
+    let mut queue = VecDeque::new();
+    for root in roots {
+        paths.insert(root.clone(), vec![root.clone()]);
+        queue.push_back(root.as_str());
+    }
+    while let Some(node) = queue.pop_front() {
+        for &child in children.get(node).into_iter().flatten() {
+            if !paths.contains_key(child) {
+                let mut path = paths[node].clone();
+                path.push(child.to_string());
+                paths.insert(child.to_string(), path);
+                queue.push_back(child);
+            }
+        }
+    }
+    paths
+}
+
+/// Edge label listing kind, optional and target
+fn edge_label(edge: &DependencyEdge) -> String {
+    let mut parts = vec![edge.kind.clone()];
+    if edge.optional {
+        parts.push("optional".to_string());
+    }
+    if let Some(target) = &edge.target {
+        parts.push(target.clone());
+    }
+    parts.join(", ")
+}
+
+fn dot_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn mermaid_escape(value: &str) -> String {
+    value.replace('"', "#quot;")
+}
+
+/// Render a graph as Graphviz DOT
+pub fn to_dot(graph: &DependencyGraph) -> String {
+    let mut dot = String::from("digraph dependencies {\n    node [shape=box];\n");
+    for node in &graph.nodes {
+        let _ = writeln!(
+            dot,
+            "    \"{}\" [label=\"{}\"];",
+            dot_escape(&node.id),
+            dot_escape(&node.label)
+        );
+    }
+    for edge in &graph.edges {
+        let style = if edge.optional { ", style=dashed" } else { "" };
+        let _ = writeln!(
+            dot,
+            "    \"{}\" -> \"{}\" [label=\"{}\"{}];",
+            dot_escape(&edge.from),
+            dot_escape(&edge.to),
+            dot_escape(&edge_label(edge)),
+            style
+        );
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+/// Render a graph as a Mermaid flowchart
+///
+/// Node ids are replaced by `n0`, `n1`, ... since crate ids contain characters
+/// Mermaid does not accept in identifiers.
+pub fn to_mermaid(graph: &DependencyGraph) -> String {
+    let index = |id: &str| graph.nodes.iter().position(|node| node.id == id);
+
+    let mut mermaid = String::from("graph TD\n");
+    for (i, node) in graph.nodes.iter().enumerate() {
+        let _ = writeln!(mermaid, "    n{}[\"{}\"]", i, mermaid_escape(&node.label));
+    }
+    for edge in &graph.edges {
+        let (Some(from), Some(to)) = (index(&edge.from), index(&edge.to)) else {
+            continue;
+        };
+        let arrow = if edge.optional { "-.->" } else { "-->" };
+        let _ = writeln!(
+            mermaid,
+            "    n{} {}|\"{}\"| n{}",
+            from,
+            arrow,
+            mermaid_escape(&edge_label(edge)),
+            to
+        );
+    }
+    mermaid
+}
+
+/// Render a graph in the requested format
+///
+/// `GraphFormat::List` is handled by the callers, which serialize their own listing.
+pub fn render(graph: &DependencyGraph, format: GraphFormat) -> Result<String> {
+    match format {
+        GraphFormat::Dot => Ok(to_dot(graph)),
+        GraphFormat::Mermaid => Ok(to_mermaid(graph)),
+        GraphFormat::List | GraphFormat::Json => {
+            Ok(serde_json::to_string_pretty(graph).context("Failed to serialize graph")?)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dependency(
+        name: &str,
+        version_req: &str,
+        kind: &str,
+        optional: bool,
+        target: Option<&str>,
+    ) -> CrateDependency {
+        CrateDependency {
+            name: name.to_string(),
+            version_req: version_req.to_string(),
+            optional,
+            default_features: true,
+            features: vec![],
+            target: target.map(|t| t.to_string()),
+            kind: kind.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_graph_rendering() {
+        let graph = from_dependencies(
+            "app",
+            "0.1.0",
+            &[
+                dependency("serde", "^1.0", "normal", true, None),
+                dependency("winapi", "^0.3", "normal", false, Some("cfg(windows)")),
+                dependency("cc", "^1.0", "build", false, None),
+                dependency("serde", "^1.0", "dev", false, None),
+                dependency("cc", "^1.2", "normal", false, None),
+                dependency("app", "^0.1", "dev", false, None),
+            ],
+        );
+        // serde is one node for both kinds, cc one per requirement, and the app
+        // dependency is not the root
+        assert_eq!(graph.nodes.len(), 6);
+        assert_eq!(graph.edges.len(), 6);
+
+        let dot = to_dot(&graph);
+        assert!(dot.starts_with("digraph dependencies {"));
+        assert!(dot.contains("\"app@0.1.0\" [label=\"app 0.1.0\"];"));
+        assert!(dot.contains(
+            "\"app@0.1.0\" -> \"serde ^1.0\" [label=\"normal, optional\", style=dashed];"
+        ));
+        assert!(dot.contains("\"app@0.1.0\" -> \"winapi ^0.3\" [label=\"normal, cfg(windows)\"];"));
+        assert!(dot.contains("\"app@0.1.0\" -> \"cc ^1.0\" [label=\"build\"];"));
+        assert!(dot.contains("\"app@0.1.0\" -> \"cc ^1.2\" [label=\"normal\"];"));
+        assert!(dot.contains("\"app@0.1.0\" -> \"app ^0.1\" [label=\"dev\"];"));
+
+        let mermaid = to_mermaid(&graph);
+        assert!(mermaid.starts_with("graph TD\n"));
+        assert!(mermaid.contains("n0[\"app 0.1.0\"]"));
+        assert!(mermaid.contains("n0 -.->|\"normal, optional\"| n1"));
+        assert!(mermaid.contains("n0 -->|\"dev\"| n1"));
+
+        assert_eq!(
+            GraphFormat::parse(Some("svg")).unwrap_err().code(),
+            "invalid_input"
+        );
+    }
+}
@@ -0,0 +1,314 @@
+use anyhow::Context;
+use reqwest::header::{HeaderMap, RETRY_AFTER};
+use reqwest::{Client, Response, StatusCode, Url};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use tracing::{debug, warn};
+
+use crate::config::ServerConfig;
+use crate::error::{Error, Result};
+use crate::rate_limit::RateLimiter;
+
+/// Retry and timeout settings applied to every upstream HTTP request
+#[derive(Debug, Clone)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one
+    pub max_attempts: u32,
+    /// Backoff before the first retry, doubled on every further retry
+    pub initial_backoff: Duration,
+    /// Upper bound for a single backoff, also the longest `Retry-After` we are willing to wait
+    pub max_backoff: Duration,
+    /// Timeout for a single attempt, from connecting until the body is read
+    pub request_timeout: Duration,
+    /// Timeout for establishing the connection of a single attempt
+    pub connect_timeout: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 4,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            request_timeout: Duration::from_secs(30),
+            connect_timeout: Duration::from_secs(10),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff with jitter for the given retry (1 = first retry)
+    ///
+    /// Half of the delay is fixed and half is random, so concurrent clients spread out
+    /// without ever retrying immediately.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let exponential = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry.saturating_sub(1)))
+            .min(self.max_backoff);
+        let half = exponential / 2;
+        let jitter_nanos = random_u64() % (half.as_nanos() as u64).max(1);
+        half + Duration::from_nanos(jitter_nanos)
+    }
+}
+
+/// Whether a response status is worth retrying
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// Whether a transport error is worth retrying
+fn is_retryable_error(error: &reqwest::Error) -> bool {
+    error.is_connect() || error.is_timeout()
+}
+
+/// Parse a `Retry-After` header, given either as delay seconds or as an HTTP date
+fn parse_retry_after(headers: &HeaderMap) -> Option<Duration> {
+    let value = headers.get(RETRY_AFTER)?.to_str().ok()?.trim();
+
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO),
+    )
+}
+
+fn random_u64() -> u64 {
+    RandomState::new().build_hasher().finish()
+}
+
+/// HTTP client shared by the crates.io and docs.rs clients, applying the retry policy
+/// and the per-host rate limits
+///
+/// Clones share the same connection pool and rate limiter.
+#[derive(Clone)]
+pub struct HttpClient {
+    client: Client,
+    policy: RetryPolicy,
+    limiter: Arc<RateLimiter>,
+    offline: bool,
+}
+
+impl HttpClient {
+    /// Create a new HttpClient from the server configuration
+    pub fn new(config: &ServerConfig) -> Result<Self> {
+        let client = Client::builder()
+            .user_agent(config.user_agent())
+            .connect_timeout(config.retry.connect_timeout)
+            .build()
+            .context("Failed to create HTTP client")?;
+
+        Ok(Self {
+            client,
+            policy: config.retry.clone(),
+            limiter: Arc::new(RateLimiter::new(config.rate_limit.clone())),
+            offline: config.offline,
+        })
+    }
+
+    /// Send a GET request, retrying on 429, 5xx, connect errors and timeouts
+    ///
+    /// Every attempt first waits for the rate limiter of the target host.
+    /// Responses with any other status, including 404, are returned to the caller as-is.
+    /// When retries are exhausted the error says how many attempts were made.
+    pub async fn get(&self, url: &str) -> Result<Response> {
+        if self.offline {
+            return Err(Error::Offline(format!(
+                "Network access is disabled in offline mode (attempted request to {})",
+                url
+            )));
+        }
+
+        let host = Url::parse(url)
+            .map_err(|e| Error::InvalidInput(format!("Invalid URL {}: {}", url, e)))?
+            .host_str()
+            .unwrap_or_default()
+            .to_string();
+
+        let max_attempts = self.policy.max_attempts.max(1);
+        let mut attempt = 0;
+
+        loop {
+            attempt += 1;
+            self.limiter.acquire(&host).await;
+            debug!("GET {} (attempt {}/{})", url, attempt, max_attempts);
+
+            let result = self
+                .client
+                .get(url)
+                .timeout(self.policy.request_timeout)
+                .send()
+                .await;
+
+            let retry_after = match result {
+                Ok(response) if !is_retryable_status(response.status()) => return Ok(response),
+                Ok(response) => {
+                    let status = response.status();
+                    let retry_after = parse_retry_after(response.headers());
+                    let waits_too_long =
+                        retry_after.is_some_and(|delay| delay > self.policy.max_backoff);
+
+                    if attempt >= max_attempts || waits_too_long {
+                        if status == StatusCode::TOO_MANY_REQUESTS {
+                            return Err(Error::RateLimited {
+                                url: url.to_string(),
+                                attempts: attempt,
+                                retry_after,
+                            });
+                        }
+                        return Err(Error::UpstreamUnavailable {
+                            message: format!("Request to {} failed with status {}", url, status),
+                            attempts: attempt,
+                        });
+                    }
+
+                    warn!("Request to {} returned {}, retrying", url, status);
+                    retry_after
+                }
+                Err(e) if is_retryable_error(&e) && attempt < max_attempts => {
+                    warn!("Request to {} failed: {}, retrying", url, e);
+                    None
+                }
+                Err(e) => {
+                    return Err(Error::UpstreamUnavailable {
+                        message: format!("Failed to send request to {}: {}", url, e),
+                        attempts: attempt,
+                    });
+                }
+            };
+
+            let delay = retry_after.unwrap_or_else(|| self.policy.backoff(attempt));
+            debug!("Waiting {:?} before retrying {}", delay, url);
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rate_limit::RateLimitConfig;
+    use anyhow::Result;
+    use reqwest::header::HeaderValue;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    #[test]
+    fn test_backoff_grows_and_is_capped() {
+        let policy = RetryPolicy {
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(1000),
+            ..RetryPolicy::default()
+        };
+
+        for _ in 0..20 {
+            let first = policy.backoff(1);
+            assert!(first >= Duration::from_millis(50) && first <= Duration::from_millis(100));
+
+            let third = policy.backoff(3);
+            assert!(third >= Duration::from_millis(200) && third <= Duration::from_millis(400));
+
+            let capped = policy.backoff(20);
+            assert!(capped >= Duration::from_millis(500) && capped <= Duration::from_millis(1000));
+        }
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(parse_retry_after(&headers), None);
+
+        headers.insert(RETRY_AFTER, HeaderValue::from_static("7"));
+        assert_eq!(parse_retry_after(&headers), Some(Duration::from_secs(7)));
+
+        // Dates in the past mean "retry now"
+        headers.insert(
+            RETRY_AFTER,
+            HeaderValue::from_static("Wed, 21 Oct 2015 07:28:00 GMT"),
+        );
+        assert_eq!(parse_retry_after(&headers), Some(Duration::ZERO));
+    }
+
+    /// Serve the given raw HTTP responses, one per connection, and return the base URL
+    async fn serve(responses: Vec<&'static str>) -> Result<String> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let addr = listener.local_addr()?;
+        tokio::spawn(async move {
+            for response in responses {
+                let Ok((mut socket, _)) = listener.accept().await else {
+                    return;
+                };
+                let mut buf = [0u8; 1024];
+                let _ = socket.read(&mut buf).await;
+                let _ = socket.write_all(response.as_bytes()).await;
+            }
+        });
+        Ok(format!("http://{}", addr))
+    }
+
+    fn test_client(max_attempts: u32) -> Result<HttpClient> {
+        let config = ServerConfig {
+            retry: RetryPolicy {
+                max_attempts,
+                initial_backoff: Duration::from_millis(1),
+                ..RetryPolicy::default()
+            },
+            rate_limit: RateLimitConfig {
+                default_rate: 0.0,
+                ..RateLimitConfig::default()
+            },
+            ..ServerConfig::default()
+        };
+        Ok(HttpClient::new(&config)?)
+    }
+
+    #[tokio::test]
+    async fn test_retries_until_success() -> Result<()> {
+        let url = serve(vec![
+            "HTTP/1.1 503 Service Unavailable\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+            "HTTP/1.1 429 Too Many Requests\r\nretry-after: 0\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+            "HTTP/1.1 200 OK\r\ncontent-length: 2\r\nconnection: close\r\n\r\nok",
+        ])
+        .await?;
+
+        let response = test_client(3)?.get(&url).await?;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.text().await?, "ok");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reports_attempts_when_exhausted() -> Result<()> {
+        let url = serve(vec![
+            "HTTP/1.1 500 Internal Server Error\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+            "HTTP/1.1 500 Internal Server Error\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+        ])
+        .await?;
+
+        let error = test_client(2)?.get(&url).await.unwrap_err();
+        assert_eq!(error.code(), "upstream_unavailable");
+        assert!(error.to_string().contains("after 2 attempts"), "{}", error);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_does_not_retry_not_found() -> Result<()> {
+        let url = serve(vec![
+            "HTTP/1.1 404 Not Found\r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+        ])
+        .await?;
+
+        let response = test_client(3)?.get(&url).await?;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        Ok(())
+    }
+}
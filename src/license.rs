@@ -0,0 +1,111 @@
+use spdx::{Expression, LicenseItem, LicenseReq, ParseMode};
+
+/// Licenses accepted or rejected when checking a dependency tree
+///
+/// An empty allow list accepts every license that is not denied.
+#[derive(Debug, Clone, Default)]
+pub struct LicensePolicy {
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+}
+
+/// Outcome of checking one license expression against a policy
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    Allowed,
+    /// The expression cannot be satisfied; lists why each failing term was rejected
+    Rejected(Vec<String>),
+    /// The expression is not valid SPDX
+    Invalid(String),
+}
+
+impl LicensePolicy {
+    fn listed(list: &[String], req: &LicenseReq) -> bool {
+        let id = match &req.license {
+            LicenseItem::Spdx { id, .. } => id.name.to_string(),
+            LicenseItem::Other { lic_ref, .. } => format!("LicenseRef-{}", lic_ref),
+        };
+        let full = req.to_string();
+        list.iter()
+            .any(|entry| entry.eq_ignore_ascii_case(&id) || entry.eq_ignore_ascii_case(&full))
+    }
+
+    fn permits(&self, req: &LicenseReq) -> bool {
+        !Self::listed(&self.deny, req) && (self.allow.is_empty() || Self::listed(&self.allow, req))
+    }
+
+    /// Evaluate an SPDX expression such as `MIT OR Apache-2.0` or
+    /// `GPL-2.0 WITH Classpath-exception-2.0 AND MIT`
+    ///
+    /// `OR` needs one side to be permitted, `AND` both. Legacy crates.io
+    /// spellings like `MIT/Apache-2.0` are accepted.
+    pub fn evaluate(&self, expression: &str) -> Verdict {
+        let parsed = match Expression::parse_mode(expression, ParseMode::LAX) {
+            Ok(parsed) => parsed,
+            Err(e) => return Verdict::Invalid(e.to_string()),
+        };
+
+        match parsed.evaluate_with_failures(|req| self.permits(req)) {
+            Ok(()) => Verdict::Allowed,
+            Err(failures) => Verdict::Rejected(
+                failures
+                    .into_iter()
+                    .map(|failure| {
+                        if Self::listed(&self.deny, &failure.req) {
+                            format!("{} is denied", failure.req)
+                        } else {
+                            format!("{} is not allowed", failure.req)
+                        }
+                    })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_evaluate_license_policy() {
+        let policy = LicensePolicy {
+            allow: vec![
+                "MIT".to_string(),
+                "Apache-2.0".to_string(),
+                "GPL-2.0 WITH Classpath-exception-2.0".to_string(),
+            ],
+            deny: vec!["GPL-3.0".to_string()],
+        };
+
+        assert_eq!(policy.evaluate("MIT OR GPL-3.0"), Verdict::Allowed);
+        assert_eq!(policy.evaluate("MIT/Apache-2.0"), Verdict::Allowed);
+        assert_eq!(
+            policy.evaluate("GPL-2.0 WITH Classpath-exception-2.0"),
+            Verdict::Allowed
+        );
+        assert_eq!(
+            policy.evaluate("MIT AND GPL-3.0"),
+            Verdict::Rejected(vec!["GPL-3.0 is denied".to_string()])
+        );
+        assert_eq!(
+            policy.evaluate("MPL-2.0"),
+            Verdict::Rejected(vec!["MPL-2.0 is not allowed".to_string()])
+        );
+        assert!(matches!(
+            policy.evaluate("MIT AND (Apache-2.0"),
+            Verdict::Invalid(_)
+        ));
+
+        // Without an allow list only denied licenses are rejected
+        let deny_only = LicensePolicy {
+            allow: vec![],
+            deny: vec!["AGPL-3.0".to_string()],
+        };
+        assert_eq!(deny_only.evaluate("BSD-3-Clause"), Verdict::Allowed);
+        assert!(matches!(
+            deny_only.evaluate("AGPL-3.0"),
+            Verdict::Rejected(_)
+        ));
+    }
+}
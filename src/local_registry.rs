@@ -0,0 +1,494 @@
+use anyhow::{Context, Result};
+use flate2::read::GzDecoder;
+use serde::Deserialize;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io::Read;
+use std::path::{Component, Path, PathBuf};
+use tracing::debug;
+
+/// The `[package]` table of a crate manifest found in the local cargo registry
+#[derive(Debug, Clone, Deserialize)]
+pub struct LocalPackage {
+    pub description: Option<String>,
+    pub documentation: Option<String>,
+    pub homepage: Option<String>,
+    pub repository: Option<String>,
+    pub license: Option<String>,
+    #[serde(default)]
+    pub authors: Vec<String>,
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    #[serde(default)]
+    pub categories: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct LocalManifest {
+    package: LocalPackage,
+}
+
+/// Read-only view of the crates cargo has already downloaded
+/// (`~/.cargo/registry/src` and `~/.cargo/registry/cache`)
+pub struct LocalRegistry {
+    registry_path: Option<PathBuf>,
+}
+
+impl LocalRegistry {
+    /// Locate the cargo registry directory of the current user
+    pub fn discover() -> Self {
+        let registry_path = Self::get_cargo_registry_path();
+        match &registry_path {
+            Some(path) => debug!("Using local cargo registry at: {}", path.display()),
+            None => debug!("No local cargo registry found"),
+        }
+        Self { registry_path }
+    }
+
+    /// Use an explicit registry directory (the `registry` folder inside a cargo home)
+    #[cfg(test)]
+    pub fn at_path(registry_path: impl Into<PathBuf>) -> Self {
+        Self {
+            registry_path: Some(registry_path.into()),
+        }
+    }
+
+    /// Get the cargo registry path
+    pub fn get_cargo_registry_path() -> Option<PathBuf> {
+        // CARGO_HOME takes precedence, just like it does for cargo itself
+        if let Some(cargo_home) = std::env::var_os("CARGO_HOME") {
+            let mut path = PathBuf::from(cargo_home);
+            path.push("registry");
+            if path.exists() {
+                return Some(path);
+            }
+        }
+
+        if let Some(home) = std::env::var_os("HOME") {
+            let mut path = PathBuf::from(home);
+            path.push(".cargo/registry");
+            if path.exists() {
+                return Some(path);
+            }
+        }
+
+        // Try Windows equivalent
+        if let Some(appdata) = std::env::var_os("APPDATA") {
+            let mut path = PathBuf::from(appdata);
+            // On Windows, cargo uses different paths, check common locations
+            if path.join(".cargo").exists() {
+                path.push(".cargo/registry");
+                if path.exists() {
+                    return Some(path);
+                }
+            }
+        }
+
+        None
+    }
+
+    /// List the per-registry directories below `registry/<kind>` (e.g. `src/index.crates.io-...`)
+    fn registry_dirs(&self, kind: &str) -> Vec<PathBuf> {
+        let Some(registry_path) = &self.registry_path else {
+            return vec![];
+        };
+
+        match fs::read_dir(registry_path.join(kind)) {
+            Ok(entries) => entries
+                .filter_map(|e| e.ok())
+                .map(|e| e.path())
+                .filter(|p| p.is_dir())
+                .collect(),
+            Err(_) => vec![],
+        }
+    }
+
+    /// All `(name, version)` pairs available locally, either unpacked or as `.crate` archives
+    pub fn downloaded_crates(&self) -> Vec<(String, String)> {
+        let mut crates = BTreeSet::new();
+
+        for dir in self.registry_dirs("src") {
+            for entry in fs::read_dir(&dir)
+                .into_iter()
+                .flatten()
+                .filter_map(|e| e.ok())
+            {
+                if let Some((name, version)) =
+                    entry.file_name().to_str().and_then(split_name_version)
+                {
+                    crates.insert((name, version));
+                }
+            }
+        }
+
+        for dir in self.registry_dirs("cache") {
+            for entry in fs::read_dir(&dir)
+                .into_iter()
+                .flatten()
+                .filter_map(|e| e.ok())
+            {
+                if let Some((name, version)) = entry
+                    .file_name()
+                    .to_str()
+                    .and_then(|f| f.strip_suffix(".crate"))
+                    .and_then(split_name_version)
+                {
+                    crates.insert((name, version));
+                }
+            }
+        }
+
+        crates.into_iter().collect()
+    }
+
+    /// Locally available versions of a crate, newest first
+    pub fn downloaded_versions(&self, name: &str) -> Vec<String> {
+        let mut versions: Vec<semver::Version> = self
+            .downloaded_crates()
+            .into_iter()
+            .filter(|(n, _)| n == name)
+            .filter_map(|(_, v)| semver::Version::parse(&v).ok())
+            .collect();
+        versions.sort();
+        versions.dedup();
+        versions.into_iter().rev().map(|v| v.to_string()).collect()
+    }
+
+    /// Path of the unpacked sources of a crate version, if cargo has extracted it
+    pub fn source_dir(&self, name: &str, version: &str) -> Option<PathBuf> {
+        let dir_name = crate_dir_name(name, version)?;
+        self.registry_dirs("src")
+            .into_iter()
+            .filter_map(|dir| child_path(&dir, &dir_name))
+            .find(|p| p.is_dir())
+    }
+
+    /// Path of the downloaded `.crate` archive of a crate version
+    pub fn archive_path(&self, name: &str, version: &str) -> Option<PathBuf> {
+        let file_name = format!("{}.crate", crate_dir_name(name, version)?);
+        self.registry_dirs("cache")
+            .into_iter()
+            .filter_map(|dir| child_path(&dir, &file_name))
+            .find(|p| p.is_file())
+    }
+
+    /// Read a file of a locally downloaded crate version, relative to the crate root
+    pub fn read_file(&self, name: &str, version: &str, relative_path: &str) -> Result<String> {
+        if !is_package_path(relative_path) {
+            return Err(anyhow::anyhow!(
+                "File path '{}' must be relative to the crate root",
+                relative_path
+            ));
+        }
+
+        if let Some(dir) = self.source_dir(name, version) {
+            fs::read_to_string(dir.join(relative_path))
+                .with_context(|| format!("Failed to read {} in {}", relative_path, dir.display()))
+        } else if let Some(archive) = self.archive_path(name, version) {
+            read_archive_file(&archive, relative_path)
+        } else {
+            Err(anyhow::anyhow!(
+                "Crate '{}' version '{}' is not in the local cargo registry",
+                name,
+                version
+            ))
+        }
+    }
+
+    /// Read the `[package]` metadata of a locally downloaded crate version
+    pub fn read_package(&self, name: &str, version: &str) -> Result<LocalPackage> {
+        let manifest = self.read_file(name, version, "Cargo.toml")?;
+        let manifest: LocalManifest = toml::from_str(&manifest)
+            .with_context(|| format!("Failed to parse Cargo.toml of {}-{}", name, version))?;
+        Ok(manifest.package)
+    }
+
+    /// Read the README of a locally downloaded crate version
+    pub fn read_readme(&self, name: &str, version: &str) -> Result<String> {
+        read_readme(|path| self.read_file(name, version, path))
+    }
+
+    /// Find locally downloaded crates whose name contains the query, newest version of each
+    pub fn search(&self, query: &str) -> Vec<(String, String)> {
+        let query = query.to_lowercase().replace('-', "_");
+        let mut newest: BTreeMap<String, semver::Version> = BTreeMap::new();
+
+        for (name, version) in self.downloaded_crates() {
+            if !name.to_lowercase().replace('-', "_").contains(&query) {
+                continue;
+            }
+            let Ok(version) = semver::Version::parse(&version) else {
+                continue;
+            };
+            match newest.get(&name) {
+                Some(existing) if *existing >= version => {}
+                _ => {
+                    newest.insert(name, version);
+                }
+            }
+        }
+
+        newest
+            .into_iter()
+            .map(|(name, version)| (name, version.to_string()))
+            .collect()
+    }
+}
+
+/// Split a `name-version` directory or archive stem into its parts
+///
+/// Crate names may contain dashes, so the split point is the first dash
+/// followed by a valid semver version.
+fn split_name_version(stem: &str) -> Option<(String, String)> {
+    stem.match_indices('-').find_map(|(i, _)| {
+        let (name, version) = (&stem[..i], &stem[i + 1..]);
+        semver::Version::parse(version)
+            .ok()
+            .map(|_| (name.to_string(), version.to_string()))
+    })
+}
+
+/// Whether a name can be a crate name: ASCII letters, digits, `-` and `_`
+pub fn is_crate_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// `name-version`, the directory a crate version is unpacked into
+///
+/// `None` unless the name is a crate name and the version is semver, so the
+/// result is a single path component.
+pub fn crate_dir_name(name: &str, version: &str) -> Option<String> {
+    (is_crate_name(name) && semver::Version::parse(version).is_ok())
+        .then(|| format!("{}-{}", name, version))
+}
+
+/// `root/child` if it exists and, with symlinks resolved, is still inside `root`
+pub fn child_path(root: &Path, child: &str) -> Option<PathBuf> {
+    let path = root.join(child);
+    let resolved = path.canonicalize().ok()?;
+    resolved
+        .starts_with(root.canonicalize().ok()?)
+        .then_some(path)
+}
+
+/// Read a single file from a `.crate` archive, relative to its `name-version/` root
+pub fn read_archive_file(archive: &Path, relative_path: &str) -> Result<String> {
+    let file =
+        fs::File::open(archive).with_context(|| format!("Failed to open {}", archive.display()))?;
+    read_crate_file(file, relative_path)
+        .with_context(|| format!("Failed to read {}", archive.display()))
+}
+
+/// Read a single file from the gzipped tarball of a `.crate`, relative to its `name-version/` root
+pub fn read_crate_file(archive: impl Read, relative_path: &str) -> Result<String> {
+    let mut tar = tar::Archive::new(GzDecoder::new(archive));
+
+    for entry in tar.entries().context("Failed to read crate archive")? {
+        let mut entry = entry.context("Failed to read crate archive entry")?;
+        let path = entry.path().context("Invalid path in crate archive")?;
+        // Every entry is prefixed with the `name-version/` directory
+        let matches =
+            path.components().skip(1).collect::<PathBuf>().as_path() == Path::new(relative_path);
+        if matches {
+            let mut content = String::new();
+            entry
+                .read_to_string(&mut content)
+                .with_context(|| format!("Failed to read {} from crate archive", relative_path))?;
+            return Ok(content);
+        }
+    }
+
+    Err(anyhow::anyhow!(
+        "{} not found in crate archive",
+        relative_path
+    ))
+}
+
+/// Files cargo picks up as the README when `package.readme` is not set
+const DEFAULT_READMES: &[&str] = &["README.md", "README.txt", "README"];
+
+/// Whether a path stays inside the package it is relative to: no root, `..` or `.`
+pub fn is_package_path(path: &str) -> bool {
+    let path = Path::new(path.trim_start_matches("./"));
+    !path.as_os_str().is_empty()
+        && path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+}
+
+/// Where the README of a packaged crate may be, from its `Cargo.toml`
+///
+/// Empty when `package.readme` is `false`. A README outside the package directory
+/// (`readme = "../README.md"`) is packaged at the crate root, so only its file
+/// name is tried.
+pub fn readme_paths(manifest: &str) -> Result<Vec<String>> {
+    let manifest: toml::Value = toml::from_str(manifest).context("Failed to parse Cargo.toml")?;
+    let readme = manifest
+        .get("package")
+        .and_then(|package| package.get("readme"));
+
+    Ok(match readme {
+        Some(toml::Value::Boolean(false)) => Vec::new(),
+        Some(toml::Value::String(path)) => {
+            let path = path.trim_start_matches("./").to_string();
+            let file_name = Path::new(&path)
+                .file_name()
+                .and_then(|name| name.to_str())
+                .map(|name| name.to_string());
+            Some(path.clone())
+                .filter(|path| is_package_path(path))
+                .into_iter()
+                .chain(file_name.filter(|name| *name != path))
+                .collect()
+        }
+        _ => DEFAULT_READMES
+            .iter()
+            .map(|path| path.to_string())
+            .collect(),
+    })
+}
+
+/// Read the README of a crate, given a way to read the files of its package
+pub fn read_readme(read_file: impl Fn(&str) -> Result<String>) -> Result<String> {
+    let manifest = read_file("Cargo.toml")?;
+    let paths = readme_paths(&manifest)?;
+    if paths.is_empty() {
+        return Err(anyhow::anyhow!(
+            "The package has no README (readme = false)"
+        ));
+    }
+
+    paths
+        .iter()
+        .find_map(|path| read_file(path).ok())
+        .ok_or_else(|| {
+            anyhow::anyhow!(
+                "README not found in the package, tried {}",
+                paths.join(", ")
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::Compression;
+    use flate2::write::GzEncoder;
+
+    const MANIFEST: &str = r#"
+[package]
+name = "demo-crate"
+version = "1.2.0-beta.1"
+description = "A demo"
+license = "MIT OR Apache-2.0"
+keywords = ["demo"]
+"#;
+
+    #[test]
+    fn test_split_name_version() {
+        assert_eq!(
+            split_name_version("serde_json-1.0.140"),
+            Some(("serde_json".to_string(), "1.0.140".to_string()))
+        );
+        assert_eq!(
+            split_name_version("demo-crate-1.2.0-beta.1"),
+            Some(("demo-crate".to_string(), "1.2.0-beta.1".to_string()))
+        );
+        assert_eq!(split_name_version("not-a-crate"), None);
+    }
+
+    #[test]
+    fn test_crate_dir_name() {
+        assert_eq!(
+            crate_dir_name("serde_json", "1.0.140").as_deref(),
+            Some("serde_json-1.0.140")
+        );
+        assert_eq!(crate_dir_name("demo", "1.0.0/../../.."), None);
+        assert_eq!(crate_dir_name("../demo", "1.0.0"), None);
+        assert_eq!(crate_dir_name("demo", "latest"), None);
+    }
+
+    #[test]
+    fn test_read_package_from_src_and_cache() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("crates-mcp-registry-{}", std::process::id()));
+        let src = root.join("src/index.crates.io-test/demo-crate-1.2.0-beta.1");
+        let cache = root.join("cache/index.crates.io-test");
+        fs::create_dir_all(&src)?;
+        fs::create_dir_all(&cache)?;
+        fs::write(src.join("Cargo.toml"), MANIFEST)?;
+
+        // A second version that only exists as a packaged .crate archive
+        let archive = fs::File::create(cache.join("demo-crate-1.3.0.crate"))?;
+        let mut builder = tar::Builder::new(GzEncoder::new(archive, Compression::default()));
+        let manifest = MANIFEST.replace("1.2.0-beta.1", "1.3.0");
+        let mut header = tar::Header::new_gnu();
+        header.set_size(manifest.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(
+            &mut header,
+            "demo-crate-1.3.0/Cargo.toml",
+            manifest.as_bytes(),
+        )?;
+        let readme = "# demo-crate\n";
+        let mut header = tar::Header::new_gnu();
+        header.set_size(readme.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, "demo-crate-1.3.0/README.md", readme.as_bytes())?;
+        builder.into_inner()?.finish()?;
+
+        let registry = LocalRegistry::at_path(&root);
+        assert_eq!(
+            registry.downloaded_versions("demo-crate"),
+            vec!["1.3.0", "1.2.0-beta.1"]
+        );
+
+        let unpacked = registry.read_package("demo-crate", "1.2.0-beta.1")?;
+        assert_eq!(unpacked.license.as_deref(), Some("MIT OR Apache-2.0"));
+
+        let packed = registry.read_package("demo-crate", "1.3.0")?;
+        assert_eq!(packed.description.as_deref(), Some("A demo"));
+        assert_eq!(packed.keywords, vec!["demo"]);
+
+        assert_eq!(
+            registry.search("demo_crate"),
+            vec![("demo-crate".to_string(), "1.3.0".to_string())]
+        );
+        assert!(registry.read_package("demo-crate", "9.9.9").is_err());
+
+        assert_eq!(
+            registry.read_readme("demo-crate", "1.3.0")?,
+            "# demo-crate\n"
+        );
+        assert!(registry.read_readme("demo-crate", "1.2.0-beta.1").is_err());
+
+        fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_readme_paths() -> Result<()> {
+        assert_eq!(
+            readme_paths("[package]\nname = \"demo\"\nreadme = \"../README.md\"\n")?,
+            vec!["README.md"]
+        );
+        assert_eq!(
+            readme_paths("[package]\nname = \"demo\"\nreadme = \"/etc/passwd\"\n")?,
+            vec!["passwd"]
+        );
+        assert_eq!(
+            readme_paths("[package]\nname = \"demo\"\nreadme = \"docs/intro.md\"\n")?,
+            vec!["docs/intro.md", "intro.md"]
+        );
+        assert_eq!(
+            readme_paths("[package]\nname = \"demo\"\n")?,
+            vec!["README.md", "README.txt", "README"]
+        );
+        assert!(readme_paths("[package]\nname = \"demo\"\nreadme = false\n")?.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,171 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{Error, Result};
+
+/// Sources cargo writes for packages from crates.io
+const CRATES_IO_SOURCES: &[&str] = &[
+    "registry+https://github.com/rust-lang/crates.io-index",
+    "sparse+https://index.crates.io/",
+];
+
+/// A `[[package]]` entry of a `Cargo.lock`
+#[derive(Debug, Clone, Deserialize)]
+pub struct LockedPackage {
+    pub name: String,
+    pub version: String,
+    pub source: Option<String>,
+    /// Dependencies as `name`, `name version` or `name version (source)`
+    #[serde(default)]
+    pub dependencies: Vec<String>,
+}
+
+impl LockedPackage {
+    /// Identifier used in dependency paths, e.g. `serde@1.0.219`
+    pub fn id(&self) -> String {
+        format!("{}@{}", self.name, self.version)
+    }
+
+    /// Whether the package was locked from crates.io, as opposed to a path, git
+    /// or alternative registry source
+    pub fn is_from_crates_io(&self) -> bool {
+        self.source
+            .as_deref()
+            .is_some_and(|source| CRATES_IO_SOURCES.contains(&source))
+    }
+}
+
+/// A parsed `Cargo.lock`
+#[derive(Debug, Clone, Deserialize)]
+pub struct Lockfile {
+    /// Format version, written since v3; older lockfiles have none
+    pub version: Option<u32>,
+    #[serde(default, rename = "package")]
+    pub packages: Vec<LockedPackage>,
+}
+
+impl Lockfile {
+    /// Dependency edges between locked packages, as pairs of package ids
+    pub fn edges(&self) -> Vec<(String, String)> {
+        let mut by_name: HashMap<&str, Vec<&LockedPackage>> = HashMap::new();
+        for package in &self.packages {
+            by_name.entry(&package.name).or_default().push(package);
+        }
+
+        let mut edges = Vec::new();
+        for package in &self.packages {
+            for dependency in &package.dependencies {
+                let mut parts = dependency.split_whitespace();
+                let name = parts.next().unwrap_or_default();
+                let version = parts.next();
+                // The version is only written when several versions are locked
+                let target = by_name
+                    .get(name)
+                    .into_iter()
+                    .flatten()
+                    .find(|candidate| version.is_none_or(|v| candidate.version == v));
+                if let Some(target) = target {
+                    edges.push((package.id(), target.id()));
+                }
+            }
+        }
+        edges
+    }
+
+    /// Ids of the workspace packages, the only ones without a source
+    pub fn roots(&self) -> Vec<String> {
+        self.packages
+            .iter()
+            .filter(|package| package.source.is_none())
+            .map(LockedPackage::id)
+            .collect()
+    }
+}
+
+/// Resolve a lockfile path or a directory containing one
+pub fn lockfile_path(path: &Path) -> Result<PathBuf> {
+    let lockfile = if path.is_dir() {
+        path.join("Cargo.lock")
+    } else {
+        path.to_path_buf()
+    };
+    if !lockfile.is_file() {
+        return Err(Error::NotFound(format!(
+            "No Cargo.lock found at {}",
+            path.display()
+        )));
+    }
+    Ok(lockfile)
+}
+
+/// Read a `Cargo.lock` in the v3 or v4 format
+pub fn load(path: &Path) -> Result<Lockfile> {
+    let path = lockfile_path(path)?;
+    let content = fs::read_to_string(&path)
+        .map_err(|e| Error::InvalidInput(format!("Failed to read {}: {}", path.display(), e)))?;
+    parse(&content)
+        .map_err(|e| Error::InvalidInput(format!("Failed to parse {}: {}", path.display(), e)))
+}
+
+fn parse(content: &str) -> std::result::Result<Lockfile, String> {
+    let lockfile: Lockfile = toml::from_str(content).map_err(|e| e.to_string())?;
+    match lockfile.version {
+        Some(3 | 4) => Ok(lockfile),
+        Some(version) => Err(format!("unsupported lockfile version {}", version)),
+        None => Err("lockfile without a version field, only v3 and v4 are supported".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LOCKFILE: &str = r#"
+# This file is automatically @generated by Cargo.
+# It is not intended for manual editing.
+version = 4
+
+[[package]]
+name = "app"
+version = "0.1.0"
+dependencies = [
+ "serde",
+]
+
+[[package]]
+name = "serde"
+version = "1.0.219"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "5f0e2c6ed6606019b4e29e69dbaba95b11854410e5347d525002456dbbb786b6"
+
+[[package]]
+name = "private"
+version = "0.2.0"
+source = "sparse+https://my-registry.example.com/index/"
+"#;
+
+    #[test]
+    fn test_parse_lockfile() {
+        let lockfile = parse(LOCKFILE).unwrap();
+        assert_eq!(lockfile.version, Some(4));
+        assert_eq!(lockfile.packages.len(), 3);
+
+        let from_crates_io: Vec<&str> = lockfile
+            .packages
+            .iter()
+            .filter(|p| p.is_from_crates_io())
+            .map(|p| p.name.as_str())
+            .collect();
+        assert_eq!(from_crates_io, vec!["serde"]);
+        assert_eq!(lockfile.roots(), vec!["app@0.1.0"]);
+        assert_eq!(
+            lockfile.edges(),
+            vec![("app@0.1.0".to_string(), "serde@1.0.219".to_string())]
+        );
+
+        assert!(parse(&LOCKFILE.replace("version = 4", "version = 3")).is_ok());
+        assert!(parse("[[package]]\nname = \"a\"\nversion = \"1.0.0\"\n").is_err());
+    }
+}
@@ -1,16 +1,90 @@
+mod advisories;
+mod api_diff;
+mod config;
+mod crate_archive;
 mod crates_client;
+mod doc_search;
 mod docs_client;
+mod error;
+mod graph;
+mod http;
+mod license;
+mod local_registry;
+mod lockfile;
+mod manifest;
 mod mcp_server;
+mod rate_limit;
+mod resolver;
+mod rustdoc_html;
+mod rustdoc_json;
+mod rustdoc_markdown;
+mod source_cache;
 mod types;
 
 use anyhow::Result;
 use clap::Parser;
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use crate::config::ServerConfig;
+use crate::http::RetryPolicy;
+use crate::license::LicensePolicy;
+use crate::rate_limit::RateLimitConfig;
 
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 struct Args {
     #[arg(short, long, default_value = "stdio")]
     transport: String,
+
+    /// Answer only from local data (crate index and cargo registry), without network access
+    #[arg(long)]
+    offline: bool,
+
+    /// How often a failed upstream request (429, 5xx, connection error) is retried
+    #[arg(long, default_value_t = 3)]
+    max_retries: u32,
+
+    /// Timeout in seconds for a single upstream request attempt
+    #[arg(long, default_value_t = 30)]
+    request_timeout: u64,
+
+    /// Maximum requests per second to crates.io (its data access policy asks for at most 1)
+    #[arg(long, default_value_t = 1.0)]
+    crates_io_rate: f64,
+
+    /// Maximum requests per second to any other upstream host, 0 disables the limit
+    #[arg(long, default_value_t = 5.0)]
+    default_rate: f64,
+
+    /// Number of requests a host may receive in a burst before throttling kicks in
+    #[arg(long, default_value_t = 1)]
+    rate_burst: u32,
+
+    /// Contact email or URL added to the User-Agent, as the crates.io crawler policy asks
+    #[arg(long)]
+    contact: Option<String>,
+
+    /// Full User-Agent override for upstream requests (should include contact information)
+    #[arg(long)]
+    user_agent: Option<String>,
+
+    /// Path to a local clone of the RustSec advisory database (default: ~/.cargo/advisory-db)
+    #[arg(long)]
+    advisory_db: Option<PathBuf>,
+
+    /// SPDX licenses accepted by license checks, comma separated (default: any not denied)
+    #[arg(long, value_delimiter = ',')]
+    allow_license: Vec<String>,
+
+    /// SPDX licenses rejected by license checks, comma separated
+    #[arg(long, value_delimiter = ',')]
+    deny_license: Vec<String>,
+
+    /// Directory downloaded crate sources are unpacked into (default: ~/.cache/crates-mcp/src)
+    #[arg(long)]
+    source_cache: Option<PathBuf>,
 }
 
 #[tokio::main]
@@ -19,7 +93,29 @@ async fn main() -> Result<()> {
 
     let args = Args::parse();
 
-    let server = mcp_server::CratesIoMcpServer::new().await?;
+    let config = ServerConfig {
+        offline: args.offline,
+        retry: RetryPolicy {
+            max_attempts: args.max_retries.saturating_add(1),
+            request_timeout: Duration::from_secs(args.request_timeout),
+            ..RetryPolicy::default()
+        },
+        rate_limit: RateLimitConfig {
+            default_rate: args.default_rate,
+            host_rates: HashMap::from([("crates.io".to_string(), args.crates_io_rate)]),
+            burst: args.rate_burst,
+        },
+        contact: args.contact,
+        user_agent: args.user_agent,
+        advisory_db: args.advisory_db,
+        license_policy: LicensePolicy {
+            allow: args.allow_license,
+            deny: args.deny_license,
+        },
+        source_cache: args.source_cache,
+    };
+
+    let server = mcp_server::CratesIoMcpServer::new(config).await?;
     server.run(&args.transport).await?;
 
     Ok(())
@@ -0,0 +1,376 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+use tracing::{debug, warn};
+
+use crate::error::{Error, Result};
+
+/// Dependency tables of a manifest and the kind they declare
+const DEPENDENCY_TABLES: &[(&str, &str)] = &[
+    ("dependencies", "normal"),
+    ("dev-dependencies", "dev"),
+    ("dev_dependencies", "dev"),
+    ("build-dependencies", "build"),
+    ("build_dependencies", "build"),
+];
+
+/// A dependency declared in a local `Cargo.toml`
+#[derive(Debug, Clone, PartialEq)]
+pub struct ManifestDependency {
+    /// Key in the dependency table, which differs from `package` for renamed dependencies
+    pub name: String,
+    /// Name of the crate on the registry
+    pub package: String,
+    pub version_req: Option<String>,
+    pub kind: String,
+    pub target: Option<String>,
+    pub optional: bool,
+    /// `registry`, `path` or `git`
+    pub source: String,
+    /// Alternative registry the dependency comes from, `None` for crates.io
+    pub registry: Option<String>,
+}
+
+/// A package of a local workspace, or the single package of a manifest
+#[derive(Debug, Clone)]
+pub struct ManifestPackage {
+    pub name: String,
+    pub version: Option<String>,
+    pub manifest_path: PathBuf,
+    pub dependencies: Vec<ManifestDependency>,
+}
+
+/// Settings a workspace root shares with its members
+#[derive(Default)]
+struct WorkspaceInheritance {
+    dependencies: Table,
+    package: Table,
+}
+
+/// Resolve a manifest path or a directory containing one
+fn manifest_path(path: &Path) -> Result<PathBuf> {
+    let manifest = if path.is_dir() {
+        path.join("Cargo.toml")
+    } else {
+        path.to_path_buf()
+    };
+    if !manifest.is_file() {
+        return Err(Error::NotFound(format!(
+            "No Cargo.toml found at {}",
+            path.display()
+        )));
+    }
+    Ok(manifest)
+}
+
+fn read_manifest(path: &Path) -> Result<Table> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| Error::InvalidInput(format!("Failed to read {}: {}", path.display(), e)))?;
+    toml::from_str(&content)
+        .map_err(|e| Error::InvalidInput(format!("Failed to parse {}: {}", path.display(), e)))
+}
+
+fn inheritance(workspace: &Table) -> WorkspaceInheritance {
+    let table = |key: &str| {
+        workspace
+            .get(key)
+            .and_then(Value::as_table)
+            .cloned()
+            .unwrap_or_default()
+    };
+    WorkspaceInheritance {
+        dependencies: table("dependencies"),
+        package: table("package"),
+    }
+}
+
+/// Find the workspace a standalone member manifest belongs to, by walking up
+/// the directory tree like cargo does
+fn find_workspace(manifest: &Path) -> WorkspaceInheritance {
+    for dir in manifest
+        .parent()
+        .into_iter()
+        .flat_map(Path::ancestors)
+        .skip(1)
+    {
+        let candidate = dir.join("Cargo.toml");
+        if !candidate.is_file() {
+            continue;
+        }
+        if let Ok(root) = read_manifest(&candidate)
+            && let Some(workspace) = root.get("workspace").and_then(Value::as_table)
+        {
+            debug!("Using workspace at {}", candidate.display());
+            return inheritance(workspace);
+        }
+    }
+    WorkspaceInheritance::default()
+}
+
+/// Load every package of a local manifest
+///
+/// A workspace root yields all its members (and the root package, if any); any
+/// other manifest yields its own package. Dependencies declared with
+/// `workspace = true` are merged with `[workspace.dependencies]`.
+pub fn load_packages(path: &Path) -> Result<Vec<ManifestPackage>> {
+    let manifest = manifest_path(path)?;
+    let root = read_manifest(&manifest)?;
+
+    let Some(workspace) = root.get("workspace").and_then(Value::as_table) else {
+        let inherited = find_workspace(&manifest);
+        return Ok(vec![parse_package(&manifest, &root, &inherited)?]);
+    };
+
+    let inherited = inheritance(workspace);
+    let mut packages = Vec::new();
+    if root.contains_key("package") {
+        packages.push(parse_package(&manifest, &root, &inherited)?);
+    }
+
+    let root_dir = manifest.parent().unwrap_or(Path::new("."));
+    let excluded: Vec<PathBuf> = string_array(workspace.get("exclude"))
+        .iter()
+        .map(|dir| root_dir.join(dir))
+        .collect();
+
+    for pattern in string_array(workspace.get("members")) {
+        let pattern = root_dir.join(pattern).to_string_lossy().into_owned();
+        let dirs = glob::glob(&pattern).map_err(|e| {
+            Error::InvalidInput(format!(
+                "Invalid workspace member pattern {}: {}",
+                pattern, e
+            ))
+        })?;
+
+        for dir in dirs.flatten() {
+            let member = dir.join("Cargo.toml");
+            if excluded.contains(&dir) || !member.is_file() {
+                continue;
+            }
+            match read_manifest(&member).and_then(|m| parse_package(&member, &m, &inherited)) {
+                Ok(package) => packages.push(package),
+                Err(e) => warn!("Skipping workspace member {}: {}", member.display(), e),
+            }
+        }
+    }
+
+    Ok(packages)
+}
+
+fn string_array(value: Option<&Value>) -> Vec<String> {
+    value
+        .and_then(Value::as_array)
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(Value::as_str)
+                .map(|s| s.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn parse_package(
+    manifest: &Path,
+    root: &Table,
+    inherited: &WorkspaceInheritance,
+) -> Result<ManifestPackage> {
+    let package = root
+        .get("package")
+        .and_then(Value::as_table)
+        .ok_or_else(|| {
+            Error::InvalidInput(format!("{} has no [package] table", manifest.display()))
+        })?;
+
+    let name = package
+        .get("name")
+        .and_then(Value::as_str)
+        .unwrap_or_default()
+        .to_string();
+    let version = match package.get("version") {
+        Some(Value::String(version)) => Some(version.clone()),
+        Some(Value::Table(table)) if is_inherited(table) => inherited
+            .package
+            .get("version")
+            .and_then(Value::as_str)
+            .map(|s| s.to_string()),
+        _ => None,
+    };
+
+    let mut dependencies = parse_dependency_tables(root, None, inherited);
+    if let Some(targets) = root.get("target").and_then(Value::as_table) {
+        for (target, table) in targets {
+            if let Some(table) = table.as_table() {
+                dependencies.extend(parse_dependency_tables(table, Some(target), inherited));
+            }
+        }
+    }
+
+    Ok(ManifestPackage {
+        name,
+        version,
+        manifest_path: manifest.to_path_buf(),
+        dependencies,
+    })
+}
+
+fn is_inherited(table: &Table) -> bool {
+    table.get("workspace").and_then(Value::as_bool) == Some(true)
+}
+
+fn parse_dependency_tables(
+    table: &Table,
+    target: Option<&str>,
+    inherited: &WorkspaceInheritance,
+) -> Vec<ManifestDependency> {
+    let mut dependencies = Vec::new();
+    for (key, kind) in DEPENDENCY_TABLES {
+        let Some(entries) = table.get(*key).and_then(Value::as_table) else {
+            continue;
+        };
+        for (name, spec) in entries {
+            dependencies.push(parse_dependency(name, spec, kind, target, inherited));
+        }
+    }
+    dependencies
+}
+
+fn parse_dependency(
+    name: &str,
+    spec: &Value,
+    kind: &str,
+    target: Option<&str>,
+    inherited: &WorkspaceInheritance,
+) -> ManifestDependency {
+    let mut table = match spec {
+        Value::String(req) => {
+            Table::from_iter([("version".to_string(), Value::String(req.clone()))])
+        }
+        Value::Table(table) => table.clone(),
+        _ => Table::new(),
+    };
+
+    // `optional` and `features` stay with the member, everything else comes from the workspace
+    if is_inherited(&table) {
+        match inherited.dependencies.get(name) {
+            Some(Value::String(req)) => {
+                table.insert("version".to_string(), Value::String(req.clone()));
+            }
+            Some(Value::Table(shared)) => {
+                for (key, value) in shared {
+                    if key != "optional" && key != "features" {
+                        table.entry(key.clone()).or_insert_with(|| value.clone());
+                    }
+                }
+            }
+            _ => warn!(
+                "Dependency {} is inherited but not in [workspace.dependencies]",
+                name
+            ),
+        }
+    }
+
+    let text = |key: &str| {
+        table
+            .get(key)
+            .and_then(Value::as_str)
+            .map(|s| s.to_string())
+    };
+    let source = if table.contains_key("path") {
+        "path"
+    } else if table.contains_key("git") {
+        "git"
+    } else {
+        "registry"
+    };
+
+    ManifestDependency {
+        name: name.to_string(),
+        package: text("package").unwrap_or_else(|| name.to_string()),
+        version_req: text("version"),
+        kind: kind.to_string(),
+        target: target.map(|t| t.to_string()),
+        optional: table
+            .get("optional")
+            .and_then(Value::as_bool)
+            .unwrap_or(false),
+        source: source.to_string(),
+        registry: text("registry"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const WORKSPACE: &str = r#"
+[workspace]
+members = ["crates/*"]
+exclude = ["crates/ignored"]
+
+[workspace.package]
+version = "0.3.0"
+
+[workspace.dependencies]
+serde = { version = "1.0", features = ["derive"] }
+anyhow = "1"
+"#;
+
+    const MEMBER: &str = r#"
+[package]
+name = "member"
+version.workspace = true
+
+[dependencies]
+serde = { workspace = true, optional = true }
+json = { package = "serde_json", version = "1.0.100" }
+local = { path = "../local" }
+
+[dev-dependencies]
+anyhow.workspace = true
+
+[target.'cfg(windows)'.build-dependencies]
+winres = "0.1"
+"#;
+
+    #[test]
+    fn test_load_workspace_packages() -> anyhow::Result<()> {
+        let root =
+            std::env::temp_dir().join(format!("crates-mcp-workspace-{}", std::process::id()));
+        fs::create_dir_all(root.join("crates/member"))?;
+        fs::create_dir_all(root.join("crates/ignored"))?;
+        fs::write(root.join("Cargo.toml"), WORKSPACE)?;
+        fs::write(root.join("crates/member/Cargo.toml"), MEMBER)?;
+        fs::write(root.join("crates/ignored/Cargo.toml"), "not toml [")?;
+
+        let packages = load_packages(&root)?;
+        assert_eq!(packages.len(), 1);
+        let member = &packages[0];
+        assert_eq!(member.name, "member");
+        assert_eq!(member.version.as_deref(), Some("0.3.0"));
+
+        let find = |name: &str| member.dependencies.iter().find(|d| d.name == name).unwrap();
+        let serde = find("serde");
+        assert_eq!(serde.version_req.as_deref(), Some("1.0"));
+        assert!(serde.optional);
+        assert_eq!(find("json").package, "serde_json");
+        assert_eq!(find("local").source, "path");
+        assert_eq!(find("anyhow").kind, "dev");
+        assert_eq!(find("anyhow").version_req.as_deref(), Some("1"));
+        let winres = find("winres");
+        assert_eq!(winres.kind, "build");
+        assert_eq!(winres.target.as_deref(), Some("cfg(windows)"));
+
+        // A member manifest on its own still sees the workspace it belongs to
+        let packages = load_packages(&root.join("crates/member/Cargo.toml"))?;
+        assert_eq!(packages[0].version.as_deref(), Some("0.3.0"));
+
+        assert_eq!(
+            load_packages(&root.join("missing")).unwrap_err().code(),
+            "not_found"
+        );
+
+        fs::remove_dir_all(&root)?;
+        Ok(())
+    }
+}
@@ -1,26 +1,47 @@
 use anyhow::{Context, Result};
 use serde_json::json;
 use std::io::{self, BufRead, BufReader, Write};
-use tracing::{debug, error, info};
+use std::path::Path;
+use tracing::{debug, error, info, warn};
 
+use crate::config::ServerConfig;
 use crate::crates_client::CratesClient;
 use crate::docs_client::DocsClient;
+use crate::error::{self, Error, rpc};
+use crate::graph::{self, GraphFormat};
+use crate::http::HttpClient;
+use crate::license::LicensePolicy;
+use crate::resolver::{self, TreeOptions};
+use crate::source_cache::GrepOptions;
 
 /// MCP Server for providing Rust crate information
 pub struct CratesIoMcpServer {
     crates_client: CratesClient,
     docs_client: DocsClient,
+    license_policy: LicensePolicy,
 }
 
 impl CratesIoMcpServer {
     /// Create a new MCP server instance
-    pub async fn new() -> Result<Self> {
-        let crates_client = CratesClient::new().await?;
-        let docs_client = DocsClient::new();
+    pub async fn new(config: ServerConfig) -> Result<Self> {
+        if !config.offline && !config.has_contact() {
+            warn!("No contact information configured for requests to the public crates.io API!");
+            warn!(
+                "The crates.io data access policy asks automated clients to identify themselves; \
+                pass --contact <email-or-url> or --user-agent <agent> to comply"
+            );
+        }
+        info!("Using User-Agent: {}", config.user_agent());
+
+        // A single HTTP client so that both clients share the per-host rate limits
+        let http_client = HttpClient::new(&config)?;
+        let crates_client = CratesClient::new(&config, http_client.clone()).await?;
+        let docs_client = DocsClient::new(&config, http_client);
 
         Ok(Self {
             crates_client,
             docs_client,
+            license_policy: config.license_policy,
         })
     }
 
@@ -65,6 +86,26 @@ impl CratesIoMcpServer {
         })
     }
 
+    /// Helper method to create a failed tool result carrying a stable error code
+    fn create_tool_error_response(id: Option<serde_json::Value>, e: &Error) -> serde_json::Value {
+        json!({
+            "jsonrpc": "2.0",
+            "id": id,
+            "result": {
+                "content": [
+                    {
+                        "type": "text",
+                        "text": format!("Error ({}): {}", e.code(), e)
+                    }
+                ],
+                "structuredContent": {
+                    "error": e.to_json()
+                },
+                "isError": true
+            }
+        })
+    }
+
     /// Run the MCP server using stdio transport
     async fn run_stdio(self) -> Result<()> {
         let stdin = io::stdin();
@@ -90,7 +131,8 @@ impl CratesIoMcpServer {
                 }
                 Err(e) => {
                     error!("Failed to parse request: {}", e);
-                    let error_response = Self::create_error_response(None, -32700, "Parse error");
+                    let error_response =
+                        Self::create_error_response(None, rpc::PARSE_ERROR, "Parse error");
                     let response_str = serde_json::to_string(&error_response)?;
                     writeln!(stdout, "{}", response_str)?;
                     stdout.flush()?;
@@ -102,8 +144,13 @@ impl CratesIoMcpServer {
     }
 
     async fn handle_request(&self, request: serde_json::Value) -> serde_json::Value {
-        let method = request.get("method").and_then(|m| m.as_str()).unwrap_or("");
         let id = request.get("id").cloned();
+        let method = match request.get("method").and_then(|m| m.as_str()) {
+            Some(method) => method,
+            None => {
+                return Self::create_error_response(id, rpc::INVALID_REQUEST, "Invalid Request");
+            }
+        };
 
         match method {
             "initialize" => {
@@ -121,7 +168,7 @@ impl CratesIoMcpServer {
             }
             "tools/list" => self.handle_list_tools(id).await,
             "tools/call" => self.handle_call_tool(request, id).await,
-            _ => Self::create_error_response(id, -32601, "Method not found"),
+            _ => Self::create_error_response(id, rpc::METHOD_NOT_FOUND, "Method not found"),
         }
     }
 
@@ -209,6 +256,252 @@ impl CratesIoMcpServer {
                                 "version": {
                                     "type": "string",
                                     "description": "Specific version (defaults to latest)"
+                                },
+                                "format": {
+                                    "type": "string",
+                                    "enum": ["list", "json", "dot", "mermaid"],
+                                    "description": "Output format: the plain listing, a node/edge JSON graph, Graphviz DOT or Mermaid (default: list)"
+                                }
+                            },
+                            "required": ["name"]
+                        }
+                    },
+                    {
+                        "name": "match_version_requirement",
+                        "description": "List the published versions of a Rust crate that satisfy a version requirement such as ^0.3 or ~1.2.3, the highest non-yanked match, and which pre-releases the requirement excludes",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {
+                                "name": {
+                                    "type": "string",
+                                    "description": "Name of the crate"
+                                },
+                                "requirement": {
+                                    "type": "string",
+                                    "description": "Version requirement in Cargo syntax, e.g. ^0.3, ~1.2.3, >=1.0, <2 or 1.*"
+                                }
+                            },
+                            "required": ["name", "requirement"]
+                        }
+                    },
+                    {
+                        "name": "get_dependency_tree",
+                        "description": "Resolve the transitive dependency tree of a Rust crate to concrete versions from the crate index, honouring features and target platforms",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {
+                                "name": {
+                                    "type": "string",
+                                    "description": "Name of the crate"
+                                },
+                                "version": {
+                                    "type": "string",
+                                    "description": "Specific version (defaults to latest)"
+                                },
+                                "features": {
+                                    "type": "array",
+                                    "items": { "type": "string" },
+                                    "description": "Features to enable on the crate"
+                                },
+                                "default_features": {
+                                    "type": "boolean",
+                                    "description": "Whether the crate's default features are enabled (default: true)"
+                                },
+                                "target": {
+                                    "type": "string",
+                                    "description": "Target triple used to evaluate platform-specific dependencies, e.g. x86_64-unknown-linux-gnu (default: all platforms)"
+                                },
+                                "max_depth": {
+                                    "type": "integer",
+                                    "description": "How many levels of dependencies to resolve (default: 10, max: 50)",
+                                    "minimum": 1,
+                                    "maximum": 50
+                                },
+                                "include_dev": {
+                                    "type": "boolean",
+                                    "description": "Include the crate's own dev-dependencies (default: false)"
+                                },
+                                "format": {
+                                    "type": "string",
+                                    "enum": ["list", "json", "dot", "mermaid"],
+                                    "description": "Output format: the plain listing, a node/edge JSON graph, Graphviz DOT or Mermaid (default: list)"
+                                }
+                            },
+                            "required": ["name"]
+                        }
+                    },
+                    {
+                        "name": "resolve_features",
+                        "description": "Resolve the features a feature selection activates for a Rust crate version, including enabled optional dependencies and the dep:/crate/feature edges that fired",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {
+                                "name": {
+                                    "type": "string",
+                                    "description": "Name of the crate"
+                                },
+                                "version": {
+                                    "type": "string",
+                                    "description": "Specific version (defaults to latest)"
+                                },
+                                "features": {
+                                    "type": "array",
+                                    "items": { "type": "string" },
+                                    "description": "Features to enable"
+                                },
+                                "default_features": {
+                                    "type": "boolean",
+                                    "description": "Whether the default features are enabled (default: true)"
+                                }
+                            },
+                            "required": ["name"]
+                        }
+                    },
+                    {
+                        "name": "analyze_manifest",
+                        "description": "Analyze the dependencies of a local Cargo.toml or workspace: latest version, whether the requirement admits it, yanked status and license",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {
+                                "path": {
+                                    "type": "string",
+                                    "description": "Path to a Cargo.toml, or a directory containing one (a workspace root analyzes every member)"
+                                }
+                            },
+                            "required": ["path"]
+                        }
+                    },
+                    {
+                        "name": "audit_lockfile",
+                        "description": "Audit a local Cargo.lock (v3/v4) against the crate index: yanked versions, how far each package is behind its newest compatible release, and crates locked at several major versions",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {
+                                "path": {
+                                    "type": "string",
+                                    "description": "Path to a Cargo.lock, or a directory containing one"
+                                },
+                                "only_issues": {
+                                    "type": "boolean",
+                                    "description": "Only list packages that are yanked or behind their newest compatible release (default: false)"
+                                }
+                            },
+                            "required": ["path"]
+                        }
+                    },
+                    {
+                        "name": "get_outdated_dependencies",
+                        "description": "Report outdated dependencies of a Rust crate version or of a local Cargo.toml/workspace: requirement, newest matching version, newest version overall and whether upgrading is semver-breaking",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {
+                                "name": {
+                                    "type": "string",
+                                    "description": "Name of a published crate (either this or path is required)"
+                                },
+                                "version": {
+                                    "type": "string",
+                                    "description": "Specific version of the crate (defaults to latest)"
+                                },
+                                "path": {
+                                    "type": "string",
+                                    "description": "Path to a local Cargo.toml, or a directory containing one"
+                                },
+                                "only_outdated": {
+                                    "type": "boolean",
+                                    "description": "Only list dependencies with a newer release than their requirement admits (default: false)"
+                                }
+                            }
+                        }
+                    },
+                    {
+                        "name": "check_advisories",
+                        "description": "Check a Rust crate version, its dependency tree or a local Cargo.lock against a local clone of the RustSec advisory database: advisory ID, severity, patched versions and informational kinds (unmaintained, unsound)",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {
+                                "name": {
+                                    "type": "string",
+                                    "description": "Name of a published crate (either this or lockfile is required)"
+                                },
+                                "version": {
+                                    "type": "string",
+                                    "description": "Specific version of the crate (defaults to latest)"
+                                },
+                                "include_dependencies": {
+                                    "type": "boolean",
+                                    "description": "Also check the crate's resolved dependency tree (default: false)"
+                                },
+                                "target": {
+                                    "type": "string",
+                                    "description": "Target triple used to resolve platform-specific dependencies (default: all platforms)"
+                                },
+                                "lockfile": {
+                                    "type": "string",
+                                    "description": "Path to a local Cargo.lock, or a directory containing one"
+                                }
+                            }
+                        }
+                    },
+                    {
+                        "name": "check_licenses",
+                        "description": "Check the SPDX licenses of a Rust crate's resolved dependency tree, or of a local Cargo.lock, against an allow/deny policy and list offending crates with the path that pulled them in",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {
+                                "name": {
+                                    "type": "string",
+                                    "description": "Name of a published crate (either this or lockfile is required)"
+                                },
+                                "version": {
+                                    "type": "string",
+                                    "description": "Specific version of the crate (defaults to latest)"
+                                },
+                                "target": {
+                                    "type": "string",
+                                    "description": "Target triple used to resolve platform-specific dependencies (default: all platforms)"
+                                },
+                                "lockfile": {
+                                    "type": "string",
+                                    "description": "Path to a local Cargo.lock, or a directory containing one"
+                                },
+                                "allow": {
+                                    "type": "array",
+                                    "items": { "type": "string" },
+                                    "description": "SPDX licenses to accept (default: the server's --allow-license list; empty accepts anything not denied)"
+                                },
+                                "deny": {
+                                    "type": "array",
+                                    "items": { "type": "string" },
+                                    "description": "SPDX licenses to reject (default: the server's --deny-license list)"
+                                }
+                            }
+                        }
+                    },
+                    {
+                        "name": "get_reverse_dependencies",
+                        "description": "List crates that depend on a Rust crate (from crates.io), to judge the impact of changing or replacing it",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {
+                                "name": {
+                                    "type": "string",
+                                    "description": "Name of the crate"
+                                },
+                                "page": {
+                                    "type": "integer",
+                                    "description": "Page number, starting at 1 (default: 1)",
+                                    "minimum": 1
+                                },
+                                "per_page": {
+                                    "type": "integer",
+                                    "description": "Results per page (default: 20, max: 100)",
+                                    "minimum": 1,
+                                    "maximum": 100
+                                },
+                                "version": {
+                                    "type": "string",
+                                    "description": "Only include dependents whose version requirement admits this version of the crate. The filter applies to the requested page: `matching` counts the dependents left on it, `total` counts all of them"
                                 }
                             },
                             "required": ["name"]
@@ -231,6 +524,202 @@ impl CratesIoMcpServer {
                             },
                             "required": ["name"]
                         }
+                    },
+                    {
+                        "name": "get_item_documentation",
+                        "description": "Get the documentation of one item of a Rust crate from docs.rs: its signature, full doc comment as markdown, methods and trait implementations",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {
+                                "name": {
+                                    "type": "string",
+                                    "description": "Name of the crate"
+                                },
+                                "path": {
+                                    "type": "string",
+                                    "description": "Path of the item, e.g. tokio::sync::Mutex, serde::Deserialize or tokio::sync::Mutex::lock"
+                                },
+                                "version": {
+                                    "type": "string",
+                                    "description": "Specific version (defaults to latest)"
+                                }
+                            },
+                            "required": ["name", "path"]
+                        }
+                    },
+                    {
+                        "name": "search_crate_docs",
+                        "description": "Search the items of a Rust crate's docs.rs documentation by name or path, fuzzily, or by type signature such as \"fn(&str) -> Result\"",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {
+                                "name": {
+                                    "type": "string",
+                                    "description": "Name of the crate"
+                                },
+                                "query": {
+                                    "type": "string",
+                                    "description": "Item name or path (e.g. Mutex, sync::mutex), or a type signature (e.g. fn(&str) -> Result, (&[u8]), -> Vec<u8>)"
+                                },
+                                "version": {
+                                    "type": "string",
+                                    "description": "Specific version (defaults to latest)"
+                                },
+                                "limit": {
+                                    "type": "integer",
+                                    "description": "Maximum number of results to return (default: 20, max: 100)",
+                                    "minimum": 1,
+                                    "maximum": 100
+                                }
+                            },
+                            "required": ["name", "query"]
+                        }
+                    },
+                    {
+                        "name": "get_crate_examples",
+                        "description": "List the files in the examples/ directory of a published Rust crate version, and read one of them (up to 100 KB)",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {
+                                "name": {
+                                    "type": "string",
+                                    "description": "Name of the crate"
+                                },
+                                "version": {
+                                    "type": "string",
+                                    "description": "Specific version (defaults to latest)"
+                                },
+                                "example": {
+                                    "type": "string",
+                                    "description": "Example to read: the name cargo runs it under (e.g. basic) or its path (e.g. examples/server/handler.rs)"
+                                }
+                            },
+                            "required": ["name"]
+                        }
+                    },
+                    {
+                        "name": "list_crate_files",
+                        "description": "List the files of the published source of a Rust crate version, as packaged on crates.io, with their sizes",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {
+                                "name": {
+                                    "type": "string",
+                                    "description": "Name of the crate"
+                                },
+                                "version": {
+                                    "type": "string",
+                                    "description": "Specific version (defaults to latest)"
+                                },
+                                "directory": {
+                                    "type": "string",
+                                    "description": "Only list files below this directory, e.g. src/sync"
+                                }
+                            },
+                            "required": ["name"]
+                        }
+                    },
+                    {
+                        "name": "read_crate_file",
+                        "description": "Read a file of the published source of a Rust crate version, e.g. src/lib.rs, optionally only a range of lines (up to 100 KB)",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {
+                                "name": {
+                                    "type": "string",
+                                    "description": "Name of the crate"
+                                },
+                                "path": {
+                                    "type": "string",
+                                    "description": "Path of the file relative to the crate root, as listed by list_crate_files"
+                                },
+                                "version": {
+                                    "type": "string",
+                                    "description": "Specific version (defaults to latest)"
+                                },
+                                "start_line": {
+                                    "type": "integer",
+                                    "description": "First line to return, starting at 1 (default: 1)",
+                                    "minimum": 1
+                                },
+                                "end_line": {
+                                    "type": "integer",
+                                    "description": "Last line to return (default: the end of the file)",
+                                    "minimum": 1
+                                }
+                            },
+                            "required": ["name", "path"]
+                        }
+                    },
+                    {
+                        "name": "grep_crate_source",
+                        "description": "Search the published source of a Rust crate version for a regex, returning each matching file and line with context lines",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {
+                                "name": {
+                                    "type": "string",
+                                    "description": "Name of the crate"
+                                },
+                                "pattern": {
+                                    "type": "string",
+                                    "description": "Regular expression matched against each line (Rust regex syntax), e.g. std::process::Command"
+                                },
+                                "version": {
+                                    "type": "string",
+                                    "description": "Specific version (defaults to latest)"
+                                },
+                                "case_insensitive": {
+                                    "type": "boolean",
+                                    "description": "Match regardless of case (default: false)"
+                                },
+                                "include": {
+                                    "type": "array",
+                                    "items": {"type": "string"},
+                                    "description": "Only search files matching one of these globs, e.g. [\"*.rs\"] or [\"src/proto/**\"]. Globs without a / match the file name"
+                                },
+                                "exclude": {
+                                    "type": "array",
+                                    "items": {"type": "string"},
+                                    "description": "Skip files matching one of these globs, e.g. [\"tests/**\", \"benches/**\"]"
+                                },
+                                "context_lines": {
+                                    "type": "integer",
+                                    "description": "Lines of context before and after each match (default: 2, max: 10)",
+                                    "minimum": 0,
+                                    "maximum": 10
+                                },
+                                "max_results": {
+                                    "type": "integer",
+                                    "description": "Maximum number of matches to return (default: 50, max: 500)",
+                                    "minimum": 1,
+                                    "maximum": 500
+                                }
+                            },
+                            "required": ["name", "pattern"]
+                        }
+                    },
+                    {
+                        "name": "diff_api",
+                        "description": "Compare the public API of two versions of a Rust crate: added, removed and changed items and signatures, each classified as a semver major, minor or patch change",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {
+                                "name": {
+                                    "type": "string",
+                                    "description": "Name of the crate"
+                                },
+                                "old_version": {
+                                    "type": "string",
+                                    "description": "Version to compare from, e.g. 1.2.0"
+                                },
+                                "new_version": {
+                                    "type": "string",
+                                    "description": "Version to compare to (defaults to latest)"
+                                }
+                            },
+                            "required": ["name", "old_version"]
+                        }
                     }
                 ]
             }
@@ -245,28 +734,14 @@ impl CratesIoMcpServer {
         let params = match request.get("params") {
             Some(p) => p,
             None => {
-                return json!({
-                    "jsonrpc": "2.0",
-                    "id": id,
-                    "error": {
-                        "code": -32602,
-                        "message": "Invalid params"
-                    }
-                });
+                return Self::create_error_response(id, rpc::INVALID_PARAMS, "Invalid params");
             }
         };
 
         let tool_name = match params.get("name").and_then(|n| n.as_str()) {
             Some(name) => name,
             None => {
-                return json!({
-                    "jsonrpc": "2.0",
-                    "id": id,
-                    "error": {
-                        "code": -32602,
-                        "message": "Missing tool name"
-                    }
-                });
+                return Self::create_error_response(id, rpc::INVALID_PARAMS, "Missing tool name");
             }
         };
 
@@ -280,16 +755,29 @@ impl CratesIoMcpServer {
             "get_crate_info" => self.call_get_crate_info(arguments).await,
             "get_crate_versions" => self.call_get_crate_versions(arguments).await,
             "get_crate_dependencies" => self.call_get_crate_dependencies(arguments).await,
+            "match_version_requirement" => self.call_match_version_requirement(arguments).await,
+            "get_dependency_tree" => self.call_get_dependency_tree(arguments).await,
+            "resolve_features" => self.call_resolve_features(arguments).await,
+            "analyze_manifest" => self.call_analyze_manifest(arguments).await,
+            "audit_lockfile" => self.call_audit_lockfile(arguments).await,
+            "get_outdated_dependencies" => self.call_get_outdated_dependencies(arguments).await,
+            "check_advisories" => self.call_check_advisories(arguments).await,
+            "check_licenses" => self.call_check_licenses(arguments).await,
+            "get_reverse_dependencies" => self.call_get_reverse_dependencies(arguments).await,
             "get_crate_documentation" => self.call_get_crate_documentation(arguments).await,
+            "get_item_documentation" => self.call_get_item_documentation(arguments).await,
+            "search_crate_docs" => self.call_search_crate_docs(arguments).await,
+            "get_crate_examples" => self.call_get_crate_examples(arguments).await,
+            "list_crate_files" => self.call_list_crate_files(arguments).await,
+            "read_crate_file" => self.call_read_crate_file(arguments).await,
+            "grep_crate_source" => self.call_grep_crate_source(arguments).await,
+            "diff_api" => self.call_diff_api(arguments).await,
             _ => {
-                return json!({
-                    "jsonrpc": "2.0",
-                    "id": id,
-                    "error": {
-                        "code": -32601,
-                        "message": "Unknown tool"
-                    }
-                });
+                return Self::create_error_response(
+                    id,
+                    rpc::INVALID_PARAMS,
+                    &format!("Unknown tool: {}", tool_name),
+                );
             }
         };
 
@@ -308,29 +796,12 @@ impl CratesIoMcpServer {
                     }
                 })
             }
-            Err(e) => {
-                json!({
-                    "jsonrpc": "2.0",
-                    "id": id,
-                    "result": {
-                        "content": [
-                            {
-                                "type": "text",
-                                "text": format!("Error: {}", e)
-                            }
-                        ],
-                        "isError": true
-                    }
-                })
-            }
+            Err(e) => Self::create_tool_error_response(id, &e),
         }
     }
 
-    async fn call_search_crates(&self, arguments: &serde_json::Value) -> Result<String> {
-        let query = arguments
-            .get("query")
-            .and_then(|v| v.as_str())
-            .context("Missing 'query' parameter")?;
+    async fn call_search_crates(&self, arguments: &serde_json::Value) -> error::Result<String> {
+        let query = required_str(arguments, "query")?;
 
         let limit = arguments
             .get("limit")
@@ -348,31 +819,33 @@ impl CratesIoMcpServer {
             .unwrap_or(25000);
 
         // Additional validation
-        if let Some(limit) = limit {
-            if limit > 100 {
-                return Err(anyhow::anyhow!("Limit cannot exceed 100"));
-            }
+        if let Some(limit) = limit
+            && limit > 100
+        {
+            return Err(Error::InvalidInput("Limit cannot exceed 100".to_string()));
         }
 
-        let results = self.crates_client.search_crates(query, limit, sort_by, min_downloads).await?;
-        serde_json::to_string_pretty(&results).context("Failed to serialize search results")
+        let results = self
+            .crates_client
+            .search_crates(query, limit, sort_by, min_downloads)
+            .await?;
+        let json =
+            serde_json::to_string_pretty(&results).context("Failed to serialize search results")?;
+        Ok(json)
     }
 
-    async fn call_get_crate_info(&self, arguments: &serde_json::Value) -> Result<String> {
-        let name = arguments
-            .get("name")
-            .and_then(|v| v.as_str())
-            .context("Missing 'name' parameter")?;
+    async fn call_get_crate_info(&self, arguments: &serde_json::Value) -> error::Result<String> {
+        let name = required_str(arguments, "name")?;
 
         let info = self.crates_client.get_crate_info(name).await?;
-        serde_json::to_string_pretty(&info).context("Failed to serialize crate info")
+        Ok(serde_json::to_string_pretty(&info).context("Failed to serialize crate info")?)
     }
 
-    async fn call_get_crate_versions(&self, arguments: &serde_json::Value) -> Result<String> {
-        let name = arguments
-            .get("name")
-            .and_then(|v| v.as_str())
-            .context("Missing 'name' parameter")?;
+    async fn call_get_crate_versions(
+        &self,
+        arguments: &serde_json::Value,
+    ) -> error::Result<String> {
+        let name = required_str(arguments, "name")?;
 
         let limit = arguments
             .get("limit")
@@ -380,26 +853,262 @@ impl CratesIoMcpServer {
             .map(|n| n as usize);
 
         let versions = self.crates_client.get_crate_versions(name, limit).await?;
-        serde_json::to_string_pretty(&versions).context("Failed to serialize versions")
+        Ok(serde_json::to_string_pretty(&versions).context("Failed to serialize versions")?)
     }
 
-    async fn call_get_crate_dependencies(&self, arguments: &serde_json::Value) -> Result<String> {
-        let name = arguments
-            .get("name")
-            .and_then(|v| v.as_str())
-            .context("Missing 'name' parameter")?;
+    async fn call_get_crate_dependencies(
+        &self,
+        arguments: &serde_json::Value,
+    ) -> error::Result<String> {
+        let name = required_str(arguments, "name")?;
 
         let version = arguments.get("version").and_then(|v| v.as_str());
 
-        let dependencies = self.crates_client.get_crate_dependencies(name, version)?;
-        serde_json::to_string_pretty(&dependencies).context("Failed to serialize dependencies")
+        let format = GraphFormat::parse(arguments.get("format").and_then(|v| v.as_str()))?;
+
+        let (version, dependencies) = self.crates_client.get_crate_dependencies(name, version)?;
+        if format != GraphFormat::List {
+            let graph = graph::from_dependencies(name, &version, &dependencies);
+            return graph::render(&graph, format);
+        }
+        let json = serde_json::to_string_pretty(&dependencies)
+            .context("Failed to serialize dependencies")?;
+        Ok(json)
     }
 
-    async fn call_get_crate_documentation(&self, arguments: &serde_json::Value) -> Result<String> {
-        let name = arguments
-            .get("name")
-            .and_then(|v| v.as_str())
-            .context("Missing 'name' parameter")?;
+    async fn call_match_version_requirement(
+        &self,
+        arguments: &serde_json::Value,
+    ) -> error::Result<String> {
+        let name = required_str(arguments, "name")?;
+
+        let requirement = required_str(arguments, "requirement")?;
+
+        let matches = self
+            .crates_client
+            .match_version_requirement(name, requirement)?;
+        let json = serde_json::to_string_pretty(&matches)
+            .context("Failed to serialize matching versions")?;
+        Ok(json)
+    }
+
+    async fn call_get_dependency_tree(
+        &self,
+        arguments: &serde_json::Value,
+    ) -> error::Result<String> {
+        let name = required_str(arguments, "name")?;
+
+        let version = arguments.get("version").and_then(|v| v.as_str());
+
+        let options = TreeOptions {
+            features: string_list(arguments, "features"),
+            default_features: arguments
+                .get("default_features")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(true),
+            target: arguments
+                .get("target")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string()),
+            max_depth: arguments
+                .get("max_depth")
+                .and_then(|v| v.as_u64())
+                .map(|n| n as usize)
+                .unwrap_or(resolver::DEFAULT_MAX_DEPTH),
+            include_dev: arguments
+                .get("include_dev")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+        };
+
+        let format = GraphFormat::parse(arguments.get("format").and_then(|v| v.as_str()))?;
+
+        let tree = self
+            .crates_client
+            .get_dependency_tree(name, version, &options)?;
+        if format != GraphFormat::List {
+            return graph::render(&graph::from_tree(&tree), format);
+        }
+        let json =
+            serde_json::to_string_pretty(&tree).context("Failed to serialize dependency tree")?;
+        Ok(json)
+    }
+
+    async fn call_resolve_features(&self, arguments: &serde_json::Value) -> error::Result<String> {
+        let name = required_str(arguments, "name")?;
+
+        let version = arguments.get("version").and_then(|v| v.as_str());
+
+        let features = string_list(arguments, "features");
+
+        let default_features = arguments
+            .get("default_features")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(true);
+
+        let resolution =
+            self.crates_client
+                .resolve_features(name, version, &features, default_features)?;
+        let json = serde_json::to_string_pretty(&resolution)
+            .context("Failed to serialize feature resolution")?;
+        Ok(json)
+    }
+
+    async fn call_analyze_manifest(&self, arguments: &serde_json::Value) -> error::Result<String> {
+        let path = required_str(arguments, "path")?;
+
+        let analysis = self.crates_client.analyze_manifest(Path::new(path)).await?;
+        let json = serde_json::to_string_pretty(&analysis)
+            .context("Failed to serialize manifest analysis")?;
+        Ok(json)
+    }
+
+    async fn call_audit_lockfile(&self, arguments: &serde_json::Value) -> error::Result<String> {
+        let path = required_str(arguments, "path")?;
+
+        let only_issues = arguments
+            .get("only_issues")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let audit = self
+            .crates_client
+            .audit_lockfile(Path::new(path), only_issues)?;
+        let json =
+            serde_json::to_string_pretty(&audit).context("Failed to serialize lockfile audit")?;
+        Ok(json)
+    }
+
+    async fn call_get_outdated_dependencies(
+        &self,
+        arguments: &serde_json::Value,
+    ) -> error::Result<String> {
+        let only_outdated = arguments
+            .get("only_outdated")
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false);
+
+        let report = match (
+            arguments.get("path").and_then(|v| v.as_str()),
+            arguments.get("name").and_then(|v| v.as_str()),
+        ) {
+            (Some(path), _) => self
+                .crates_client
+                .get_outdated_manifest_dependencies(Path::new(path), only_outdated)?,
+            (None, Some(name)) => {
+                let version = arguments.get("version").and_then(|v| v.as_str());
+                self.crates_client
+                    .get_outdated_dependencies(name, version, only_outdated)?
+            }
+            (None, None) => {
+                return Err(Error::InvalidInput(
+                    "Either 'name' or 'path' parameter is required".to_string(),
+                ));
+            }
+        };
+        let json = serde_json::to_string_pretty(&report)
+            .context("Failed to serialize outdated dependencies")?;
+        Ok(json)
+    }
+
+    async fn call_check_advisories(&self, arguments: &serde_json::Value) -> error::Result<String> {
+        let report = match (
+            arguments.get("lockfile").and_then(|v| v.as_str()),
+            arguments.get("name").and_then(|v| v.as_str()),
+        ) {
+            (Some(lockfile), _) => self
+                .crates_client
+                .check_lockfile_advisories(Path::new(lockfile))?,
+            (None, Some(name)) => {
+                let version = arguments.get("version").and_then(|v| v.as_str());
+                let include_dependencies = arguments
+                    .get("include_dependencies")
+                    .and_then(|v| v.as_bool())
+                    .unwrap_or(false);
+                let target = arguments.get("target").and_then(|v| v.as_str());
+                self.crates_client
+                    .check_advisories(name, version, include_dependencies, target)?
+            }
+            (None, None) => {
+                return Err(Error::InvalidInput(
+                    "Either 'name' or 'lockfile' parameter is required".to_string(),
+                ));
+            }
+        };
+        let json =
+            serde_json::to_string_pretty(&report).context("Failed to serialize advisories")?;
+        Ok(json)
+    }
+
+    async fn call_check_licenses(&self, arguments: &serde_json::Value) -> error::Result<String> {
+        // Lists passed with the call replace the server's default policy
+        let mut policy = self.license_policy.clone();
+        if arguments.get("allow").is_some() {
+            policy.allow = string_list(arguments, "allow");
+        }
+        if arguments.get("deny").is_some() {
+            policy.deny = string_list(arguments, "deny");
+        }
+
+        let report = match (
+            arguments.get("lockfile").and_then(|v| v.as_str()),
+            arguments.get("name").and_then(|v| v.as_str()),
+        ) {
+            (Some(lockfile), _) => {
+                self.crates_client
+                    .check_lockfile_licenses(Path::new(lockfile), &policy)
+                    .await?
+            }
+            (None, Some(name)) => {
+                let version = arguments.get("version").and_then(|v| v.as_str());
+                let target = arguments.get("target").and_then(|v| v.as_str());
+                self.crates_client
+                    .check_licenses(name, version, target, &policy)
+                    .await?
+            }
+            (None, None) => {
+                return Err(Error::InvalidInput(
+                    "Either 'name' or 'lockfile' parameter is required".to_string(),
+                ));
+            }
+        };
+        let json =
+            serde_json::to_string_pretty(&report).context("Failed to serialize license report")?;
+        Ok(json)
+    }
+
+    async fn call_get_reverse_dependencies(
+        &self,
+        arguments: &serde_json::Value,
+    ) -> error::Result<String> {
+        let name = required_str(arguments, "name")?;
+
+        let page = arguments
+            .get("page")
+            .and_then(|v| v.as_u64())
+            .map(|n| n as u32);
+
+        let per_page = arguments
+            .get("per_page")
+            .and_then(|v| v.as_u64())
+            .map(|n| n as u32);
+
+        let version = arguments.get("version").and_then(|v| v.as_str());
+
+        let reverse_dependencies = self
+            .crates_client
+            .get_reverse_dependencies(name, page, per_page, version)
+            .await?;
+        let json = serde_json::to_string_pretty(&reverse_dependencies)
+            .context("Failed to serialize reverse dependencies")?;
+        Ok(json)
+    }
+
+    async fn call_get_crate_documentation(
+        &self,
+        arguments: &serde_json::Value,
+    ) -> error::Result<String> {
+        let name = required_str(arguments, "name")?;
 
         let version = arguments.get("version").and_then(|v| v.as_str());
 
@@ -407,8 +1116,189 @@ impl CratesIoMcpServer {
             .docs_client
             .get_crate_documentation(name, version)
             .await?;
-        serde_json::to_string_pretty(&docs).context("Failed to serialize documentation")
+        Ok(serde_json::to_string_pretty(&docs).context("Failed to serialize documentation")?)
+    }
+
+    async fn call_get_item_documentation(
+        &self,
+        arguments: &serde_json::Value,
+    ) -> error::Result<String> {
+        let name = required_str(arguments, "name")?;
+
+        let path = required_str(arguments, "path")?;
+
+        let version = arguments.get("version").and_then(|v| v.as_str());
+
+        let docs = self
+            .docs_client
+            .get_item_documentation(name, version, path)
+            .await?;
+        Ok(
+            serde_json::to_string_pretty(&docs)
+                .context("Failed to serialize item documentation")?,
+        )
     }
+
+    async fn call_search_crate_docs(&self, arguments: &serde_json::Value) -> error::Result<String> {
+        let name = required_str(arguments, "name")?;
+
+        let query = required_str(arguments, "query")?;
+
+        let version = arguments.get("version").and_then(|v| v.as_str());
+
+        let limit = arguments
+            .get("limit")
+            .and_then(|v| v.as_u64())
+            .map(|n| n as usize)
+            .unwrap_or(20);
+
+        if limit > 100 {
+            return Err(Error::InvalidInput("Limit cannot exceed 100".to_string()));
+        }
+
+        let results = self
+            .docs_client
+            .search_documentation(name, version, query, limit)
+            .await?;
+        Ok(serde_json::to_string_pretty(&results)
+            .context("Failed to serialize documentation search results")?)
+    }
+
+    async fn call_get_crate_examples(
+        &self,
+        arguments: &serde_json::Value,
+    ) -> error::Result<String> {
+        let name = required_str(arguments, "name")?;
+
+        let version = arguments.get("version").and_then(|v| v.as_str());
+
+        let example = arguments.get("example").and_then(|v| v.as_str());
+
+        let examples = self
+            .crates_client
+            .get_crate_examples(name, version, example)
+            .await?;
+        Ok(
+            serde_json::to_string_pretty(&examples)
+                .context("Failed to serialize crate examples")?,
+        )
+    }
+
+    async fn call_list_crate_files(&self, arguments: &serde_json::Value) -> error::Result<String> {
+        let name = required_str(arguments, "name")?;
+
+        let version = arguments.get("version").and_then(|v| v.as_str());
+
+        let directory = arguments.get("directory").and_then(|v| v.as_str());
+
+        let files = self
+            .crates_client
+            .list_crate_files(name, version, directory)
+            .await?;
+        Ok(serde_json::to_string_pretty(&files).context("Failed to serialize crate files")?)
+    }
+
+    async fn call_read_crate_file(&self, arguments: &serde_json::Value) -> error::Result<String> {
+        let name = required_str(arguments, "name")?;
+
+        let path = required_str(arguments, "path")?;
+
+        let version = arguments.get("version").and_then(|v| v.as_str());
+
+        let start_line = arguments
+            .get("start_line")
+            .and_then(|v| v.as_u64())
+            .map(|n| n as usize);
+
+        let end_line = arguments
+            .get("end_line")
+            .and_then(|v| v.as_u64())
+            .map(|n| n as usize);
+
+        let file = self
+            .crates_client
+            .read_crate_file(name, version, path, start_line, end_line)
+            .await?;
+        Ok(serde_json::to_string_pretty(&file).context("Failed to serialize crate file")?)
+    }
+
+    async fn call_grep_crate_source(&self, arguments: &serde_json::Value) -> error::Result<String> {
+        let name = required_str(arguments, "name")?;
+
+        let pattern = required_str(arguments, "pattern")?;
+
+        let version = arguments.get("version").and_then(|v| v.as_str());
+
+        let mut options = GrepOptions {
+            case_insensitive: arguments
+                .get("case_insensitive")
+                .and_then(|v| v.as_bool())
+                .unwrap_or(false),
+            include: string_list(arguments, "include"),
+            exclude: string_list(arguments, "exclude"),
+            ..GrepOptions::default()
+        };
+        if let Some(context) = arguments.get("context_lines").and_then(|v| v.as_u64()) {
+            if context > 10 {
+                return Err(Error::InvalidInput(
+                    "context_lines cannot exceed 10".to_string(),
+                ));
+            }
+            options.context = context as usize;
+        }
+        if let Some(max_results) = arguments.get("max_results").and_then(|v| v.as_u64()) {
+            if max_results > 500 {
+                return Err(Error::InvalidInput(
+                    "max_results cannot exceed 500".to_string(),
+                ));
+            }
+            options.max_results = (max_results as usize).max(1);
+        }
+
+        let results = self
+            .crates_client
+            .grep_crate_source(name, version, pattern, &options)
+            .await?;
+        Ok(serde_json::to_string_pretty(&results)
+            .context("Failed to serialize source search results")?)
+    }
+
+    async fn call_diff_api(&self, arguments: &serde_json::Value) -> error::Result<String> {
+        let name = required_str(arguments, "name")?;
+
+        let old_version = required_str(arguments, "old_version")?;
+
+        let new_version = arguments.get("new_version").and_then(|v| v.as_str());
+
+        let diff = self
+            .docs_client
+            .diff_api(name, old_version, new_version)
+            .await?;
+        Ok(serde_json::to_string_pretty(&diff).context("Failed to serialize API diff")?)
+    }
+}
+
+/// Get a required string argument of a tool call
+fn required_str<'a>(arguments: &'a serde_json::Value, key: &str) -> error::Result<&'a str> {
+    arguments
+        .get(key)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| Error::InvalidInput(format!("Missing '{}' parameter", key)))
+}
+
+/// Get an optional list of strings argument of a tool call
+fn string_list(arguments: &serde_json::Value, key: &str) -> Vec<String> {
+    arguments
+        .get(key)
+        .and_then(|v| v.as_array())
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|v| v.as_str())
+                .map(|s| s.to_string())
+                .collect()
+        })
+        .unwrap_or_default()
 }
 
 #[cfg(test)]
@@ -417,7 +1307,35 @@ mod tests {
 
     #[tokio::test]
     async fn test_server_creation() -> Result<()> {
-        let _server = CratesIoMcpServer::new().await?;
+        let _server = CratesIoMcpServer::new(ServerConfig::default()).await?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_error_codes() -> Result<()> {
+        let server = CratesIoMcpServer::new(ServerConfig::default()).await?;
+
+        let response = server
+            .handle_request(json!({"jsonrpc": "2.0", "id": 1, "method": "tools/call",
+                "params": {"name": "no_such_tool"}}))
+            .await;
+        assert_eq!(response["error"]["code"], rpc::INVALID_PARAMS);
+
+        let response = server
+            .handle_request(json!({"jsonrpc": "2.0", "id": 2}))
+            .await;
+        assert_eq!(response["error"]["code"], rpc::INVALID_REQUEST);
+
+        let response = server
+            .handle_request(json!({"jsonrpc": "2.0", "id": 3, "method": "tools/call",
+                "params": {"name": "get_crate_info", "arguments": {}}}))
+            .await;
+        assert_eq!(response["result"]["isError"], true);
+        assert_eq!(
+            response["result"]["structuredContent"]["error"]["code"],
+            "invalid_input"
+        );
+
         Ok(())
     }
 }
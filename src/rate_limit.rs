@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::debug;
+
+/// Request rates allowed per upstream host
+#[derive(Debug, Clone)]
+pub struct RateLimitConfig {
+    /// Requests per second for hosts without an explicit rate, 0 disables limiting
+    pub default_rate: f64,
+    /// Requests per second for specific hosts, e.g. `crates.io`
+    pub host_rates: HashMap<String, f64>,
+    /// How many requests a host may receive in a burst before being throttled
+    pub burst: u32,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        // The crates.io data access policy asks crawlers for at most one request per second
+        Self {
+            default_rate: 5.0,
+            host_rates: HashMap::from([("crates.io".to_string(), 1.0)]),
+            burst: 1,
+        }
+    }
+}
+
+impl RateLimitConfig {
+    fn rate_for(&self, host: &str) -> f64 {
+        self.host_rates
+            .get(host)
+            .copied()
+            .unwrap_or(self.default_rate)
+    }
+}
+
+/// A token bucket that hands out reservations, so callers queue up instead of failing
+#[derive(Debug)]
+struct TokenBucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(rate: f64, burst: u32, now: Instant) -> Self {
+        let capacity = f64::from(burst.max(1));
+        Self {
+            rate,
+            capacity,
+            tokens: capacity,
+            last_refill: now,
+        }
+    }
+
+    /// Take a token and return how long the caller has to wait before using it
+    ///
+    /// The token count may go negative: every queued caller reserves the next free
+    /// slot, which keeps waiters in arrival order.
+    fn reserve(&mut self, now: Instant) -> Duration {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.last_refill = now;
+
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.tokens / self.rate)
+        }
+    }
+}
+
+/// Per-host token bucket limiter shared by every request the server makes
+pub struct RateLimiter {
+    config: RateLimitConfig,
+    buckets: Mutex<HashMap<String, TokenBucket>>,
+}
+
+impl RateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            config,
+            buckets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Wait until a request to the given host is allowed
+    pub async fn acquire(&self, host: &str) {
+        let rate = self.config.rate_for(host);
+        if rate <= 0.0 {
+            return;
+        }
+
+        let wait = {
+            let mut buckets = self.buckets.lock().unwrap_or_else(|e| e.into_inner());
+            let now = Instant::now();
+            buckets
+                .entry(host.to_string())
+                .or_insert_with(|| TokenBucket::new(rate, self.config.burst, now))
+                .reserve(now)
+        };
+
+        if !wait.is_zero() {
+            debug!("Rate limiting request to {} for {:?}", host, wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_token_bucket_queues_requests() {
+        let start = Instant::now();
+        let mut bucket = TokenBucket::new(1.0, 2, start);
+
+        // The burst is served immediately
+        assert_eq!(bucket.reserve(start), Duration::ZERO);
+        assert_eq!(bucket.reserve(start), Duration::ZERO);
+
+        // Further requests queue up one second apart
+        assert_eq!(bucket.reserve(start), Duration::from_secs(1));
+        assert_eq!(bucket.reserve(start), Duration::from_secs(2));
+
+        // Tokens refill over time, but never beyond the burst size
+        let later = start + Duration::from_secs(10);
+        assert_eq!(bucket.reserve(later), Duration::ZERO);
+        assert_eq!(bucket.reserve(later), Duration::ZERO);
+        assert_eq!(bucket.reserve(later), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn test_rate_limiter_is_per_host() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            default_rate: 0.0,
+            host_rates: HashMap::from([("crates.io".to_string(), 20.0)]),
+            burst: 1,
+        });
+
+        let start = Instant::now();
+        limiter.acquire("crates.io").await;
+        limiter.acquire("crates.io").await;
+        limiter.acquire("crates.io").await;
+        assert!(start.elapsed() >= Duration::from_millis(100));
+
+        // Unlimited hosts never wait
+        let start = Instant::now();
+        for _ in 0..10 {
+            limiter.acquire("docs.rs").await;
+        }
+        assert!(start.elapsed() < Duration::from_millis(50));
+    }
+}
@@ -0,0 +1,1015 @@
+use cfg_expr::targets::{TargetInfo, get_builtin_target_by_triple};
+use cfg_expr::{Expression, Predicate};
+use crates_index::{Crate, DependencyKind, Version};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet, VecDeque};
+use tracing::debug;
+
+use crate::error::{Error, Result};
+use crate::types::{
+    DependencyEdge, DependencyNode, DependencyTree, FeatureEdge, FeatureResolution,
+    UnresolvedDependency,
+};
+
+/// Depth used when the caller does not ask for one
+pub const DEFAULT_MAX_DEPTH: usize = 10;
+
+/// Deepest tree we are willing to resolve
+pub const MAX_DEPTH_LIMIT: usize = 50;
+
+/// Features activated for one crate version, computed the way cargo does
+#[derive(Debug, Default)]
+pub struct FeatureActivation {
+    /// Activated features, including implicit features of enabled optional dependencies
+    pub features: BTreeSet<String>,
+    /// Optional dependencies (by dependency name, which may be a rename) that are enabled
+    pub enabled_optional: BTreeSet<String>,
+    /// Features requested on dependencies through `dep/feature` and `dep?/feature`
+    pub dep_features: BTreeMap<String, BTreeSet<String>>,
+    /// Features that exist neither in the feature table nor as optional dependencies
+    pub unknown: Vec<String>,
+    /// Entries of activated features that were followed, in activation order
+    pub edges: Vec<FeatureEdge>,
+}
+
+fn feature_edge(from: &str, to: &str, kind: &str, dependency: Option<&str>) -> FeatureEdge {
+    FeatureEdge {
+        from: from.to_string(),
+        to: to.to_string(),
+        kind: kind.to_string(),
+        dependency: dependency.map(|d| d.to_string()),
+        fired: true,
+    }
+}
+
+/// Compute the features a crate version ends up with for the requested features
+///
+/// Handles the `default` feature, `dep:name` entries, `dep/feature` and weak
+/// `dep?/feature` entries, and the implicit features of optional dependencies that
+/// are not referenced through `dep:` anywhere.
+pub fn activate_features(
+    version: &Version,
+    requested: &[String],
+    default_features: bool,
+) -> FeatureActivation {
+    let table = version.features();
+    let optional: BTreeSet<&str> = version
+        .dependencies()
+        .iter()
+        .filter(|dep| dep.is_optional())
+        .map(|dep| dep.name())
+        .collect();
+    let namespaced: BTreeSet<&str> = table
+        .values()
+        .flatten()
+        .filter_map(|value| value.strip_prefix("dep:"))
+        .collect();
+    let has_implicit_feature = |name: &str| {
+        optional.contains(name) && !namespaced.contains(name) && !table.contains_key(name)
+    };
+
+    let mut activation = FeatureActivation::default();
+    let mut queue: VecDeque<String> = requested.iter().cloned().collect();
+    if default_features && table.contains_key("default") {
+        queue.push_back("default".to_string());
+    }
+
+    let mut weak = Vec::new();
+    while let Some(feature) = queue.pop_front() {
+        if activation.features.contains(&feature) {
+            continue;
+        }
+
+        if let Some(values) = table.get(&feature) {
+            for value in values {
+                if let Some(dep) = value.strip_prefix("dep:") {
+                    activation
+                        .edges
+                        .push(feature_edge(&feature, value, "dependency", Some(dep)));
+                    activation.enabled_optional.insert(dep.to_string());
+                } else if let Some((dep, dep_feature)) = value.split_once('/') {
+                    if let Some(dep) = dep.strip_suffix('?') {
+                        weak.push((
+                            activation.edges.len(),
+                            dep.to_string(),
+                            dep_feature.to_string(),
+                        ));
+                        activation.edges.push(feature_edge(
+                            &feature,
+                            value,
+                            "weak_dependency_feature",
+                            Some(dep),
+                        ));
+                        continue;
+                    }
+                    activation.edges.push(feature_edge(
+                        &feature,
+                        value,
+                        "dependency_feature",
+                        Some(dep),
+                    ));
+                    if optional.contains(dep) {
+                        activation.enabled_optional.insert(dep.to_string());
+                        if has_implicit_feature(dep) {
+                            activation.features.insert(dep.to_string());
+                        }
+                    }
+                    // `dep/feature` also enables a feature of the same name as the dependency
+                    if table.contains_key(dep) {
+                        queue.push_back(dep.to_string());
+                    }
+                    activation
+                        .dep_features
+                        .entry(dep.to_string())
+                        .or_default()
+                        .insert(dep_feature.to_string());
+                } else {
+                    activation
+                        .edges
+                        .push(feature_edge(&feature, value, "feature", None));
+                    queue.push_back(value.clone());
+                }
+            }
+            activation.features.insert(feature);
+        } else if has_implicit_feature(&feature) {
+            activation.edges.push(feature_edge(
+                &feature,
+                &format!("dep:{}", feature),
+                "dependency",
+                Some(&feature),
+            ));
+            activation.enabled_optional.insert(feature.clone());
+            activation.features.insert(feature);
+        } else if !activation.unknown.contains(&feature) {
+            activation.unknown.push(feature);
+        }
+    }
+
+    // Weak features only apply once the optional dependency is enabled by something else
+    for (edge, dep, dep_feature) in weak {
+        let fired = !optional.contains(dep.as_str()) || activation.enabled_optional.contains(&dep);
+        activation.edges[edge].fired = fired;
+        if fired {
+            activation
+                .dep_features
+                .entry(dep)
+                .or_default()
+                .insert(dep_feature);
+        }
+    }
+
+    activation
+}
+
+/// Look up a built-in target triple, e.g. `x86_64-unknown-linux-gnu`
+pub fn target_info(triple: &str) -> Result<&'static TargetInfo> {
+    get_builtin_target_by_triple(triple)
+        .ok_or_else(|| Error::InvalidInput(format!("Unknown target triple '{}'", triple)))
+}
+
+/// Whether a dependency declared for `dep_target` is used when building for `target`
+///
+/// Without a target every platform-specific dependency is included. Unparseable
+/// `cfg(...)` expressions are kept rather than silently dropped.
+pub fn dependency_applies(dep_target: Option<&str>, target: Option<&TargetInfo>) -> bool {
+    let (Some(dep_target), Some(target)) = (dep_target, target) else {
+        return true;
+    };
+
+    if dep_target.starts_with("cfg(") {
+        match Expression::parse(dep_target) {
+            Ok(expression) => expression.eval(|predicate| match predicate {
+                Predicate::Target(target_predicate) => target_predicate.matches(target),
+                _ => false,
+            }),
+            Err(e) => {
+                debug!(
+                    "Keeping dependency with unparseable target {}: {}",
+                    dep_target, e
+                );
+                true
+            }
+        }
+    } else {
+        dep_target == target.triple.as_str()
+    }
+}
+
+/// Find a version in the index, defaulting to the highest stable release
+pub fn find_version<'a>(index_crate: &'a Crate, version: Option<&str>) -> Result<&'a Version> {
+    match version {
+        Some(v) => index_crate
+            .versions()
+            .iter()
+            .find(|ver| ver.version() == v)
+            .ok_or_else(|| Error::VersionNotFound {
+                name: index_crate.name().to_string(),
+                version: v.to_string(),
+            }),
+        None => Ok(index_crate
+            .highest_normal_version()
+            .unwrap_or_else(|| index_crate.highest_version())),
+    }
+}
+
+/// The part of a version that has to stay the same for an upgrade to be
+/// semver-compatible, e.g. `1` for 1.4.2, `0.3` for 0.3.7 and `0.0.5` for 0.0.5
+pub fn compatibility_key(version: &semver::Version) -> String {
+    match (version.major, version.minor) {
+        (0, 0) => format!("0.0.{}", version.patch),
+        (0, minor) => format!("0.{}", minor),
+        (major, _) => major.to_string(),
+    }
+}
+
+/// The lowest version a requirement admits, ignoring pre-releases, `None` when
+/// it has no lower bound like `<2`
+pub fn lower_bound(req: &semver::VersionReq) -> Option<semver::Version> {
+    use semver::Op;
+
+    req.comparators
+        .iter()
+        .filter(|comparator| !matches!(comparator.op, Op::Less | Op::LessEq))
+        .map(|comparator| {
+            semver::Version::new(
+                comparator.major,
+                comparator.minor.unwrap_or(0),
+                comparator.patch.unwrap_or(0),
+            )
+        })
+        .max()
+}
+
+/// Spell a version requirement out as explicit bounds, e.g. `^0.3` as `>=0.3.0, <0.4.0`
+pub fn describe_requirement(req: &semver::VersionReq) -> String {
+    if req.comparators.is_empty() {
+        return "any version".to_string();
+    }
+    req.comparators
+        .iter()
+        .map(describe_comparator)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn describe_comparator(comparator: &semver::Comparator) -> String {
+    match comparator_bounds(comparator) {
+        (Some((lower, true)), Some((upper, true))) if lower == upper => format!("={}", lower),
+        (None, None) => "any version".to_string(),
+        (lower, upper) => {
+            let lower =
+                lower.map(|(v, inclusive)| format!("{}{}", if inclusive { ">=" } else { ">" }, v));
+            let upper =
+                upper.map(|(v, inclusive)| format!("{}{}", if inclusive { "<=" } else { "<" }, v));
+            lower
+                .into_iter()
+                .chain(upper)
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+    }
+}
+
+/// A bound of a version range, with whether it is part of the range
+type Bound = Option<(semver::Version, bool)>;
+
+/// The lower and upper bound of a comparator, e.g. `>=0.3.0` and `<0.4.0` for `^0.3`
+///
+/// An upper bound past the largest representable version, e.g. for `^18446744073709551615`,
+/// is left open.
+fn comparator_bounds(comparator: &semver::Comparator) -> (Bound, Bound) {
+    use semver::Op;
+
+    let major = comparator.major;
+    let version = |major, minor, patch| semver::Version {
+        pre: comparator.pre.clone(),
+        ..semver::Version::new(major, minor, patch)
+    };
+    let release = |major, minor, patch| semver::Version::new(major, minor, patch);
+    let range = |lower, upper: Option<semver::Version>| {
+        (Some((lower, true)), upper.map(|upper| (upper, false)))
+    };
+
+    match (comparator.op, comparator.minor, comparator.patch) {
+        (Op::Exact, Some(minor), Some(patch)) => {
+            let exact = version(major, minor, patch);
+            (Some((exact.clone(), true)), Some((exact, true)))
+        }
+        (Op::Greater, Some(minor), Some(patch)) => {
+            (Some((version(major, minor, patch), false)), None)
+        }
+        (Op::Greater, minor, None) => match next_release(major, minor, None) {
+            Some(next) => (Some((next, true)), None),
+            // Above everything starting with `major.minor`
+            None => (
+                Some((release(major, minor.unwrap_or(u64::MAX), u64::MAX), false)),
+                None,
+            ),
+        },
+        (Op::GreaterEq, minor, patch) => (
+            Some((version(major, minor.unwrap_or(0), patch.unwrap_or(0)), true)),
+            None,
+        ),
+        (Op::Less, minor, patch) => (
+            None,
+            Some((
+                version(major, minor.unwrap_or(0), patch.unwrap_or(0)),
+                false,
+            )),
+        ),
+        (Op::LessEq, Some(minor), Some(patch)) => {
+            (None, Some((version(major, minor, patch), true)))
+        }
+        (Op::LessEq, minor, None) => (
+            None,
+            next_release(major, minor, None).map(|upper| (upper, false)),
+        ),
+        (Op::Tilde, Some(minor), patch) => range(
+            version(major, minor, patch.unwrap_or(0)),
+            next_release(major, Some(minor), None),
+        ),
+        (Op::Caret, Some(0), Some(patch)) if major == 0 => {
+            range(version(0, 0, patch), next_release(0, Some(0), Some(patch)))
+        }
+        (Op::Caret, Some(minor), patch) if major == 0 => range(
+            version(0, minor, patch.unwrap_or(0)),
+            next_release(0, Some(minor), None),
+        ),
+        (Op::Caret, Some(minor), patch) => range(
+            version(major, minor, patch.unwrap_or(0)),
+            next_release(major, None, None),
+        ),
+        (Op::Exact | Op::Wildcard, Some(minor), None) => range(
+            release(major, minor, 0),
+            next_release(major, Some(minor), None),
+        ),
+        // `=1`, `~1`, `^1` and `1.*` all mean any 1.x.y
+        _ => range(release(major, 0, 0), next_release(major, None, None)),
+    }
+}
+
+/// The first release after everything starting with the given components, e.g.
+/// `1.3.0` after `1.2`, `None` when it cannot be represented
+fn next_release(major: u64, minor: Option<u64>, patch: Option<u64>) -> Option<semver::Version> {
+    match (minor, patch) {
+        (Some(minor), Some(patch)) => {
+            Some(semver::Version::new(major, minor, patch.checked_add(1)?))
+        }
+        (Some(minor), None) => Some(semver::Version::new(major, minor.checked_add(1)?, 0)),
+        (None, _) => Some(semver::Version::new(major.checked_add(1)?, 0, 0)),
+    }
+}
+
+/// Whether a version lies within the bounds of a requirement, ignoring the rule
+/// that pre-releases only match requirements naming a pre-release of the same
+/// version
+///
+/// `2.0.0-alpha` is within `^1.2` (`>=1.2.0, <2.0.0`), `1.2.0-rc.1` is not.
+pub fn within_bounds(req: &semver::VersionReq, version: &semver::Version) -> bool {
+    req.comparators.iter().all(|comparator| {
+        let (lower, upper) = comparator_bounds(comparator);
+        let above = lower
+            .is_none_or(|(lower, inclusive)| version > &lower || (inclusive && version == &lower));
+        let below = upper
+            .is_none_or(|(upper, inclusive)| version < &upper || (inclusive && version == &upper));
+        above && below
+    })
+}
+
+/// The version a requirement resolves to: the highest non-yanked match, or the
+/// highest yanked match when nothing else is left
+pub fn highest_match<'a>(index_crate: &'a Crate, req: &semver::VersionReq) -> Option<&'a Version> {
+    let matches = || {
+        index_crate.versions().iter().filter_map(|v| {
+            semver::Version::parse(v.version())
+                .ok()
+                .filter(|parsed| req.matches(parsed))
+                .map(|parsed| (parsed, v))
+        })
+    };
+    matches()
+        .filter(|(_, v)| !v.is_yanked())
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .or_else(|| matches().max_by(|(a, _), (b, _)| a.cmp(b)))
+        .map(|(_, v)| v)
+}
+
+/// Resolve the feature set of a single crate version
+pub fn resolve_features(
+    version: &Version,
+    requested: &[String],
+    default_features: bool,
+) -> FeatureResolution {
+    let activation = activate_features(version, requested, default_features);
+
+    FeatureResolution {
+        name: version.name().to_string(),
+        version: version.version().to_string(),
+        requested: requested.to_vec(),
+        default_features,
+        features: activation.features.into_iter().collect(),
+        enabled_dependencies: activation.enabled_optional.into_iter().collect(),
+        dependency_features: activation
+            .dep_features
+            .into_iter()
+            .map(|(dep, features)| (dep, features.into_iter().collect()))
+            .collect(),
+        edges: activation.edges,
+        unknown_features: activation.unknown,
+        feature_table: version
+            .features()
+            .iter()
+            .map(|(name, values)| (name.clone(), values.clone()))
+            .collect(),
+    }
+}
+
+/// Options for resolving a dependency tree
+#[derive(Debug, Clone)]
+pub struct TreeOptions {
+    /// Features requested on the root crate
+    pub features: Vec<String>,
+    /// Whether the root crate's default features are enabled
+    pub default_features: bool,
+    /// Target triple used to evaluate `target` tables, `None` includes all of them
+    pub target: Option<String>,
+    /// How many levels below the root are expanded
+    pub max_depth: usize,
+    /// Whether the root crate's dev-dependencies are included
+    pub include_dev: bool,
+}
+
+impl Default for TreeOptions {
+    fn default() -> Self {
+        Self {
+            features: Vec::new(),
+            default_features: true,
+            target: None,
+            max_depth: DEFAULT_MAX_DEPTH,
+            include_dev: false,
+        }
+    }
+}
+
+/// A selected crate version and everything requested of it so far
+struct NodeState {
+    version: Version,
+    features: BTreeSet<String>,
+    default_features: bool,
+    depth: usize,
+}
+
+impl NodeState {
+    fn activation(&self) -> FeatureActivation {
+        let features: Vec<String> = self.features.iter().cloned().collect();
+        activate_features(&self.version, &features, self.default_features)
+    }
+}
+
+fn node_id(version: &Version) -> String {
+    format!("{}@{}", version.name(), version.version())
+}
+
+/// Pick the version cargo would use for a requirement
+///
+/// A version already in the tree is reused when it matches, so shared dependencies
+/// unify into one node. Otherwise the highest non-yanked match is taken.
+fn select_version<'a>(
+    index_crate: &'a Crate,
+    req: &semver::VersionReq,
+    selected: &[semver::Version],
+) -> Option<&'a Version> {
+    let parsed = |version: &'a Version| {
+        semver::Version::parse(version.version())
+            .ok()
+            .map(|parsed| (parsed, version))
+    };
+
+    if let Some(reused) = selected.iter().filter(|v| req.matches(v)).max() {
+        return index_crate
+            .versions()
+            .iter()
+            .find(|v| v.version() == reused.to_string());
+    }
+
+    index_crate
+        .versions()
+        .iter()
+        .filter(|v| !v.is_yanked())
+        .filter_map(parsed)
+        .filter(|(parsed, _)| req.matches(parsed))
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, version)| version)
+}
+
+/// Resolve the transitive dependencies of a crate version against the index
+///
+/// `lookup` returns the index entry of a crate. Crates it reports as not found end
+/// up in `unresolved`; any other error aborts the resolution.
+pub fn resolve_tree(
+    lookup: impl Fn(&str) -> Result<Crate>,
+    name: &str,
+    version: Option<&str>,
+    options: &TreeOptions,
+) -> Result<DependencyTree> {
+    let target = options.target.as_deref().map(target_info).transpose()?;
+    let max_depth = options.max_depth.min(MAX_DEPTH_LIMIT);
+
+    let root_crate = lookup(name)?;
+    let root_version = find_version(&root_crate, version)?;
+    let root_id = node_id(root_version);
+
+    let mut crates: HashMap<String, Option<Crate>> = HashMap::new();
+    let mut selected: HashMap<String, Vec<semver::Version>> = HashMap::new();
+    let mut nodes: BTreeMap<String, NodeState> = BTreeMap::new();
+    let mut edges: Vec<DependencyEdge> = Vec::new();
+    let mut seen_edges: HashSet<(String, String, String, String)> = HashSet::new();
+    let mut unresolved: Vec<UnresolvedDependency> = Vec::new();
+    let mut seen_unresolved: HashSet<(String, String, String)> = HashSet::new();
+
+    if let Ok(parsed) = semver::Version::parse(root_version.version()) {
+        selected.entry(name.to_string()).or_default().push(parsed);
+    }
+    nodes.insert(
+        root_id.clone(),
+        NodeState {
+            version: root_version.clone(),
+            features: options.features.iter().cloned().collect(),
+            default_features: options.default_features,
+            depth: 0,
+        },
+    );
+    crates.insert(name.to_string(), Some(root_crate.clone()));
+
+    let mut queue = VecDeque::from([root_id.clone()]);
+    while let Some(id) = queue.pop_front() {
+        let state = &nodes[&id];
+        if state.depth >= max_depth {
+            continue;
+        }
+        let depth = state.depth;
+        let version = state.version.clone();
+        let activation = state.activation();
+
+        for dep in version.dependencies() {
+            let include_kind = match dep.kind() {
+                DependencyKind::Normal | DependencyKind::Build => true,
+                DependencyKind::Dev => depth == 0 && options.include_dev,
+            };
+            if !include_kind
+                || (dep.is_optional() && !activation.enabled_optional.contains(dep.name()))
+                || !dependency_applies(dep.target(), target)
+            {
+                continue;
+            }
+
+            let crate_name = dep.crate_name();
+            let mut unresolved_dep = |reason: String| {
+                let key = (
+                    id.clone(),
+                    crate_name.to_string(),
+                    dep.requirement().to_string(),
+                );
+                if seen_unresolved.insert(key) {
+                    unresolved.push(UnresolvedDependency {
+                        from: id.clone(),
+                        name: crate_name.to_string(),
+                        version_req: dep.requirement().to_string(),
+                        reason,
+                    });
+                }
+            };
+
+            if !crates.contains_key(crate_name) {
+                let entry = match lookup(crate_name) {
+                    Ok(index_crate) => Some(index_crate),
+                    Err(Error::NotFound(_)) => None,
+                    Err(e) => return Err(e),
+                };
+                crates.insert(crate_name.to_string(), entry);
+            }
+            let Some(index_crate) = &crates[crate_name] else {
+                unresolved_dep("Crate not found in index".to_string());
+                continue;
+            };
+
+            let req = match semver::VersionReq::parse(dep.requirement()) {
+                Ok(req) => req,
+                Err(e) => {
+                    unresolved_dep(format!("Invalid version requirement: {}", e));
+                    continue;
+                }
+            };
+
+            let already_selected = selected.get(crate_name).map(Vec::as_slice).unwrap_or(&[]);
+            let Some(child) = select_version(index_crate, &req, already_selected) else {
+                unresolved_dep("No non-yanked version matches the requirement".to_string());
+                continue;
+            };
+            let child_id = node_id(child);
+
+            let mut requested: BTreeSet<String> = dep.features().iter().cloned().collect();
+            if let Some(dep_features) = activation.dep_features.get(dep.name()) {
+                requested.extend(dep_features.iter().cloned());
+            }
+
+            match nodes.get_mut(&child_id) {
+                Some(existing) => {
+                    // Features unify across every edge into a node, so it may need another pass
+                    let before = (
+                        existing.features.len(),
+                        existing.default_features,
+                        existing.depth,
+                    );
+                    existing.features.extend(requested);
+                    existing.default_features |= dep.has_default_features();
+                    existing.depth = existing.depth.min(depth + 1);
+                    if before
+                        != (
+                            existing.features.len(),
+                            existing.default_features,
+                            existing.depth,
+                        )
+                        && !queue.contains(&child_id)
+                    {
+                        queue.push_back(child_id.clone());
+                    }
+                }
+                None => {
+                    if let Ok(parsed) = semver::Version::parse(child.version()) {
+                        selected
+                            .entry(crate_name.to_string())
+                            .or_default()
+                            .push(parsed);
+                    }
+                    nodes.insert(
+                        child_id.clone(),
+                        NodeState {
+                            version: child.clone(),
+                            features: requested,
+                            default_features: dep.has_default_features(),
+                            depth: depth + 1,
+                        },
+                    );
+                    queue.push_back(child_id.clone());
+                }
+            }
+
+            let kind = format!("{:?}", dep.kind()).to_lowercase();
+            let edge_key = (
+                id.clone(),
+                child_id.clone(),
+                kind.clone(),
+                dep.name().to_string(),
+            );
+            if seen_edges.insert(edge_key) {
+                edges.push(DependencyEdge {
+                    from: id.clone(),
+                    to: child_id,
+                    version_req: dep.requirement().to_string(),
+                    kind,
+                    optional: dep.is_optional(),
+                    target: dep.target().map(|t| t.to_string()),
+                });
+            }
+        }
+    }
+
+    let mut nodes: Vec<DependencyNode> = nodes
+        .into_iter()
+        .map(|(id, state)| DependencyNode {
+            name: state.version.name().to_string(),
+            version: state.version.version().to_string(),
+            features: state.activation().features.into_iter().collect(),
+            depth: state.depth,
+            expanded: state.depth < max_depth,
+            id,
+        })
+        .collect();
+    nodes.sort_by(|a, b| a.depth.cmp(&b.depth).then_with(|| a.id.cmp(&b.id)));
+
+    Ok(DependencyTree {
+        root: root_id,
+        target: options.target.clone(),
+        max_depth,
+        nodes,
+        edges,
+        unresolved,
+    })
+}
+
+/// Build an index entry from `(version, dependencies, features, yanked)` tuples, with
+/// the same `features2` table, if any, in every version
+#[cfg(test)]
+pub(crate) fn index_crate(
+    name: &str,
+    versions: &[(&str, serde_json::Value, serde_json::Value, bool)],
+    features2: Option<serde_json::Value>,
+) -> Crate {
+    let lines: Vec<String> = versions
+        .iter()
+        .map(|(vers, deps, features, yanked)| {
+            let mut line = serde_json::json!({
+                "name": name,
+                "vers": vers,
+                "deps": deps,
+                "features": features,
+                "cksum": "0".repeat(64),
+                "yanked": yanked,
+            });
+            if let Some(features2) = &features2 {
+                line["features2"] = features2.clone();
+            }
+            line.to_string()
+        })
+        .collect();
+    Crate::from_slice(lines.join("\n").as_bytes()).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn dep(name: &str, req: &str, optional: bool, target: Option<&str>) -> serde_json::Value {
+        json!({
+            "name": name,
+            "req": req,
+            "features": [],
+            "optional": optional,
+            "default_features": true,
+            "target": target,
+            "kind": "normal",
+        })
+    }
+
+    #[test]
+    fn test_activate_features() {
+        let krate = index_crate(
+            "app",
+            &[(
+                "1.0.0",
+                json!([
+                    dep("serde", "^1", true, None),
+                    dep("log", "^0.4", true, None),
+                    dep("rayon", "^1", true, None),
+                ]),
+                json!({
+                    "default": ["std"],
+                    "std": ["serde?/std"],
+                    "derive": ["serde/derive"],
+                    "parallel": ["dep:rayon"],
+                }),
+                false,
+            )],
+            None,
+        );
+        let version = krate.highest_version();
+
+        // The weak feature does not enable serde on its own
+        let activation = activate_features(version, &[], true);
+        assert_eq!(
+            activation.features,
+            BTreeSet::from(["default".into(), "std".into()])
+        );
+        assert!(activation.enabled_optional.is_empty());
+        assert!(activation.dep_features.is_empty());
+
+        let activation = activate_features(
+            version,
+            &[
+                "derive".into(),
+                "log".into(),
+                "parallel".into(),
+                "nope".into(),
+            ],
+            true,
+        );
+        assert_eq!(
+            activation.enabled_optional,
+            BTreeSet::from(["log".into(), "rayon".into(), "serde".into()])
+        );
+        assert_eq!(
+            activation.dep_features["serde"],
+            BTreeSet::from(["derive".into(), "std".into()])
+        );
+        // `rayon` is only reachable through `dep:`, so it has no implicit feature
+        assert!(activation.features.contains("serde") && !activation.features.contains("rayon"));
+        assert_eq!(activation.unknown, vec!["nope".to_string()]);
+    }
+
+    #[test]
+    fn test_resolve_features_merges_features2() {
+        let krate = index_crate(
+            "lib",
+            &[(
+                "2.0.0",
+                json!([
+                    dep("serde", "^1", true, None),
+                    dep("log", "^0.4", true, None)
+                ]),
+                json!({ "default": ["std"], "std": [] }),
+                false,
+            )],
+            Some(json!({ "serde": ["dep:serde", "log?/std"], "std": ["log?/std"] })),
+        );
+        let resolution = resolve_features(krate.highest_version(), &["serde".into()], true);
+
+        assert_eq!(resolution.features, vec!["default", "serde", "std"]);
+        assert_eq!(resolution.enabled_dependencies, vec!["serde"]);
+        assert!(resolution.dependency_features.is_empty());
+        assert!(resolution.feature_table.contains_key("serde"));
+
+        let fired: Vec<(&str, &str, bool)> = resolution
+            .edges
+            .iter()
+            .map(|e| (e.from.as_str(), e.to.as_str(), e.fired))
+            .collect();
+        assert_eq!(
+            fired,
+            vec![
+                ("serde", "dep:serde", true),
+                ("serde", "log?/std", false),
+                ("default", "std", true),
+                ("std", "log?/std", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_highest_match_and_compatibility() {
+        let krate = index_crate(
+            "dep",
+            &[
+                ("1.0.0", json!([]), json!({}), false),
+                ("1.4.0", json!([]), json!({}), false),
+                ("1.5.0", json!([]), json!({}), true),
+                ("2.0.0", json!([]), json!({}), false),
+            ],
+            None,
+        );
+
+        let req = |r: &str| semver::VersionReq::parse(r).unwrap();
+        let matched = |r: &str| highest_match(&krate, &req(r)).map(|v| v.version().to_string());
+        assert_eq!(matched("^1").as_deref(), Some("1.4.0"));
+        // A pinned yanked version is still reported, so callers can flag it
+        assert_eq!(matched("=1.5.0").as_deref(), Some("1.5.0"));
+        assert_eq!(matched("^3"), None);
+
+        let key = |v: &str| compatibility_key(&semver::Version::parse(v).unwrap());
+        assert_eq!(key("1.4.2"), "1");
+        assert_eq!(key("0.3.7"), "0.3");
+        assert_eq!(key("0.0.5"), "0.0.5");
+    }
+
+    #[test]
+    fn test_requirement_bounds() {
+        let req = |r: &str| semver::VersionReq::parse(r).unwrap();
+        let describe = |r: &str| describe_requirement(&req(r));
+        assert_eq!(describe("^0.3"), ">=0.3.0, <0.4.0");
+        assert_eq!(describe("^0.0.3"), ">=0.0.3, <0.0.4");
+        assert_eq!(describe("1.2"), ">=1.2.0, <2.0.0");
+        assert_eq!(describe("~1.2.3"), ">=1.2.3, <1.3.0");
+        assert_eq!(describe("1.*"), ">=1.0.0, <2.0.0");
+        assert_eq!(describe(">1.2, <=2"), ">=1.3.0, <3.0.0");
+        assert_eq!(describe("=1.2"), ">=1.2.0, <1.3.0");
+        assert_eq!(describe("=1.2.3-rc.1"), "=1.2.3-rc.1");
+        assert_eq!(describe(">1.2.3"), ">1.2.3");
+        assert_eq!(describe("*"), "any version");
+        assert_eq!(
+            describe("^18446744073709551615"),
+            ">=18446744073709551615.0.0"
+        );
+        assert_eq!(describe("<=1.18446744073709551615"), "any version");
+        assert_eq!(
+            describe(">1.18446744073709551615"),
+            ">1.18446744073709551615.18446744073709551615"
+        );
+
+        let within = |r: &str, v: &str| within_bounds(&req(r), &semver::Version::parse(v).unwrap());
+        assert!(within("^1.2", "2.0.0-alpha"));
+        assert!(!within("^1.2", "1.2.0-rc.1"));
+        assert!(within("^1.2", "1.5.0-beta.2"));
+        assert!(within(">=1.0.0-rc.1", "1.0.0-rc.2"));
+        assert!(!within("<=1.2.3", "1.2.4-alpha"));
+        assert!(within("*", "0.1.0-alpha"));
+        assert!(within("^18446744073709551615", "18446744073709551615.1.0"));
+    }
+
+    #[test]
+    fn test_resolve_tree() -> anyhow::Result<()> {
+        let registry: HashMap<&str, Crate> = HashMap::from([
+            (
+                "app",
+                index_crate(
+                    "app",
+                    &[(
+                        "0.1.0",
+                        json!([
+                            dep("a", "^1", false, None),
+                            dep("b", "^1", false, None),
+                            dep("winapi", "^0.3", false, Some("cfg(windows)")),
+                            dep("extra", "^1", true, None),
+                            dep("missing", "^1", false, None),
+                        ]),
+                        json!({}),
+                        false,
+                    )],
+                    None,
+                ),
+            ),
+            (
+                "a",
+                index_crate(
+                    "a",
+                    &[(
+                        "1.0.0",
+                        json!([dep("shared", "^1.1", false, None)]),
+                        json!({}),
+                        false,
+                    )],
+                    None,
+                ),
+            ),
+            (
+                "b",
+                index_crate(
+                    "b",
+                    &[(
+                        "1.0.0",
+                        json!([dep("shared", "^1", false, None)]),
+                        json!({}),
+                        false,
+                    )],
+                    None,
+                ),
+            ),
+            (
+                "shared",
+                index_crate(
+                    "shared",
+                    &[
+                        ("1.0.0", json!([]), json!({}), false),
+                        ("1.2.0", json!([]), json!({}), false),
+                        ("2.0.0", json!([]), json!({}), false),
+                    ],
+                    None,
+                ),
+            ),
+            (
+                "winapi",
+                index_crate("winapi", &[("0.3.9", json!([]), json!({}), false)], None),
+            ),
+            (
+                "extra",
+                index_crate("extra", &[("1.0.0", json!([]), json!({}), false)], None),
+            ),
+        ]);
+        let lookup = |name: &str| {
+            registry
+                .get(name)
+                .cloned()
+                .ok_or_else(|| Error::NotFound(format!("Crate '{}' not found in index", name)))
+        };
+
+        let options = TreeOptions {
+            target: Some("x86_64-unknown-linux-gnu".to_string()),
+            ..TreeOptions::default()
+        };
+        let tree = resolve_tree(lookup, "app", None, &options)?;
+        let ids: Vec<&str> = tree.nodes.iter().map(|n| n.id.as_str()).collect();
+        // The shared dependency is resolved once, windows-only and disabled optional deps are skipped
+        assert_eq!(ids, vec!["app@0.1.0", "a@1.0.0", "b@1.0.0", "shared@1.2.0"]);
+        assert_eq!(
+            tree.edges.iter().filter(|e| e.to == "shared@1.2.0").count(),
+            2
+        );
+        assert_eq!(tree.unresolved.len(), 1);
+        assert_eq!(tree.unresolved[0].name, "missing");
+
+        // Without a target and with the optional dependency enabled, both show up
+        let options = TreeOptions {
+            features: vec!["extra".to_string()],
+            max_depth: 1,
+            ..TreeOptions::default()
+        };
+        let tree = resolve_tree(lookup, "app", None, &options)?;
+        assert!(tree.nodes.iter().any(|n| n.id == "winapi@0.3.9"));
+        assert!(tree.nodes.iter().any(|n| n.id == "extra@1.0.0"));
+        assert!(!tree.nodes.iter().any(|n| n.name == "shared"));
+        assert!(tree.nodes.iter().all(|n| n.depth == 0 || !n.expanded));
+
+        let error = resolve_tree(
+            lookup,
+            "app",
+            None,
+            &TreeOptions {
+                target: Some("not-a-target".to_string()),
+                ..TreeOptions::default()
+            },
+        )
+        .unwrap_err();
+        assert_eq!(error.code(), "invalid_input");
+
+        Ok(())
+    }
+}
@@ -0,0 +1,270 @@
+use scraper::{ElementRef, Html, Selector};
+
+use crate::types::DocumentationItem;
+
+/// Links to items in the item tables of a rustdoc page, for the layouts rustdoc
+/// has used over time: `<dl>` tables (1.80+), `<ul>` lists of `item-name` /
+/// `desc` pairs (1.70+), `item-left` / `item-right` rows and plain `<table>`s
+///
+/// Links inside the `<code>` of re-exports do not match, as their parent is the `<code>`.
+const ITEM_LINKS: &str = "dl.item-table > dt > a, .item-name > a, .item-left > a, \
+                          table tr > td:first-child > a";
+
+/// Rustdoc link classes and the kinds they are reported as
+const ITEM_KINDS: &[(&str, &str)] = &[
+    ("mod", "module"),
+    ("struct", "struct"),
+    ("enum", "enum"),
+    ("union", "union"),
+    ("trait", "trait"),
+    ("traitalias", "trait_alias"),
+    ("fn", "function"),
+    ("macro", "macro"),
+    ("attr", "attribute_macro"),
+    ("derive", "derive_macro"),
+    ("type", "type_alias"),
+    ("constant", "constant"),
+    ("static", "static"),
+];
+
+/// Extract the items listed on a rustdoc module page, such as a crate root
+///
+/// Paths come from the `title` of each link (`struct tokio::sync::Mutex`), falling
+/// back to `module_path::name`. Descriptions are the one-line summaries rustdoc
+/// renders next to each item.
+pub fn extract_items(html: &str, module_path: &str) -> Vec<DocumentationItem> {
+    listed_items(html, module_path)
+        .into_iter()
+        .map(|(item, _)| item)
+        .collect()
+}
+
+/// Find an item by name among the items of a module page, with the link to its page
+///
+/// A trait and its derive macro share a name, the trait is preferred.
+pub fn find_item(html: &str, module_path: &str, name: &str) -> Option<(DocumentationItem, String)> {
+    listed_items(html, module_path)
+        .into_iter()
+        .filter(|(item, href)| item.name == name && href.is_some())
+        .min_by_key(|(item, _)| item.kind.ends_with("macro"))
+        .and_then(|(item, href)| Some((item, href?)))
+}
+
+fn listed_items(html: &str, module_path: &str) -> Vec<(DocumentationItem, Option<String>)> {
+    let document = Html::parse_document(html);
+    let links = Selector::parse(ITEM_LINKS).expect("item link selector is valid");
+
+    let mut items = Vec::new();
+    for link in document.select(&links) {
+        let Some(kind) = link.value().attr("class").and_then(item_kind) else {
+            continue;
+        };
+        let name = normalize_text(link.text());
+        if name.is_empty() {
+            continue;
+        }
+        let path = link
+            .value()
+            .attr("title")
+            .and_then(|title| title.split_once(' '))
+            .map(|(_, path)| path.to_string())
+            .unwrap_or_else(|| format!("{}::{}", module_path, name));
+
+        let item = DocumentationItem {
+            name,
+            kind: kind.to_string(),
+            path,
+            description: summary(link),
+            ..Default::default()
+        };
+        items.push((item, link.value().attr("href").map(|href| href.to_string())));
+    }
+    items
+}
+
+/// Extract every item of a crate from its `all.html` page, which lists the items
+/// by their path below the crate root under one heading per kind
+///
+/// The kind comes from the file name the link points to, e.g. `sync/struct.Mutex.html`.
+/// Modules and associated items are not listed there.
+pub fn extract_all_items(html: &str, crate_name: &str) -> Vec<DocumentationItem> {
+    let document = Html::parse_document(html);
+    let links = Selector::parse("ul li > a[href]").expect("all items selector is valid");
+
+    document
+        .select(&links)
+        .filter_map(|link| {
+            let href = link.value().attr("href")?;
+            let file_name = href.rsplit('/').next()?;
+            let kind = file_name
+                .split_once('.')
+                .and_then(|(class, _)| item_kind(class))?;
+            let relative_path = normalize_text(link.text());
+            let name = relative_path.rsplit("::").next()?.to_string();
+            Some(DocumentationItem {
+                name,
+                kind: kind.to_string(),
+                path: format!("{}::{}", crate_name, relative_path),
+                ..Default::default()
+            })
+        })
+        .collect()
+}
+
+/// Paths of the modules among the items of a page
+pub fn module_paths(items: &[DocumentationItem]) -> Vec<String> {
+    items
+        .iter()
+        .filter(|item| item.kind == "module")
+        .map(|item| item.path.clone())
+        .collect()
+}
+
+fn item_kind(class: &str) -> Option<&'static str> {
+    class.split_whitespace().find_map(|class| {
+        ITEM_KINDS
+            .iter()
+            .find(|(rustdoc_class, _)| *rustdoc_class == class)
+            .map(|(_, kind)| *kind)
+    })
+}
+
+/// The summary cell following the cell that holds an item link
+fn summary(link: ElementRef) -> Option<String> {
+    let cell = link.parent().and_then(ElementRef::wrap)?;
+    let next = cell.next_siblings().find_map(ElementRef::wrap)?;
+
+    let element = next.value();
+    let is_summary = element.name() == "dd"
+        || element.name() == "td"
+        || element.has_class("desc", scraper::CaseSensitivity::CaseSensitive)
+        || element.has_class("docblock-short", scraper::CaseSensitivity::CaseSensitive)
+        || element.has_class("item-right", scraper::CaseSensitivity::CaseSensitive);
+    if !is_summary {
+        return None;
+    }
+    let text = normalize_text(next.text());
+    (!text.is_empty()).then_some(text)
+}
+
+/// Join text nodes and collapse whitespace, as rustdoc spreads summaries over lines and tags
+fn normalize_text<'a>(text: impl Iterator<Item = &'a str>) -> String {
+    text.collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURRENT_LAYOUT: &str = include_str!("../tests/fixtures/rustdoc/demo-dl.html");
+    const LIST_LAYOUT: &str = include_str!("../tests/fixtures/rustdoc/demo-ul.html");
+    const ALL_ITEMS: &str = include_str!("../tests/fixtures/rustdoc/demo-all.html");
+
+    fn find<'a>(items: &'a [DocumentationItem], path: &str) -> &'a DocumentationItem {
+        items
+            .iter()
+            .find(|item| item.path == path)
+            .unwrap_or_else(|| panic!("{} not extracted", path))
+    }
+
+    #[test]
+    fn test_extract_items_current_layout() {
+        let items = extract_items(CURRENT_LAYOUT, "demo");
+        let kinds: Vec<&str> = items.iter().map(|item| item.kind.as_str()).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                "module",
+                "module",
+                "macro",
+                "struct",
+                "struct",
+                "enum",
+                "trait",
+                "function",
+                "attribute_macro",
+                "derive_macro",
+                "type_alias",
+                "constant",
+            ]
+        );
+
+        let io = find(&items, "demo::io");
+        assert_eq!(io.name, "io");
+        assert_eq!(io.description.as_deref(), Some("Asynchronous I/O helpers."));
+        assert_eq!(module_paths(&items), vec!["demo::io", "demo::sync"]);
+
+        // Badges stay out of the name, undocumented items have no summary
+        let runtime = find(&items, "demo::runtime::Runtime");
+        assert_eq!(runtime.name, "Runtime");
+        assert_eq!(
+            runtime.description.as_deref(),
+            Some("The runtime that drives futures to completion.")
+        );
+        assert_eq!(find(&items, "demo::Handle").description, None);
+        assert_eq!(find(&items, "demo::spawn").kind, "function");
+        assert_eq!(find(&items, "demo::Result").kind, "type_alias");
+
+        // Re-exported items are documented by their own crate
+        assert!(items.iter().all(|item| item.path != "bytes::Bytes"));
+    }
+
+    #[test]
+    fn test_extract_items_list_layout() {
+        let items = extract_items(LIST_LAYOUT, "demo::sync");
+        assert_eq!(items.len(), 5);
+
+        let mutex = find(&items, "demo::sync::Mutex");
+        assert_eq!(mutex.kind, "struct");
+        assert_eq!(
+            mutex.description.as_deref(),
+            Some("An asynchronous Mutex-like type.")
+        );
+        assert_eq!(find(&items, "demo::sync::TryLockError").kind, "enum");
+        assert_eq!(find(&items, "demo::sync::LOCKS").kind, "static");
+
+        let (mutex, href) = find_item(LIST_LAYOUT, "demo::sync", "Mutex").unwrap();
+        assert_eq!(mutex.path, "demo::sync::Mutex");
+        assert_eq!(href, "struct.Mutex.html");
+        assert!(find_item(LIST_LAYOUT, "demo::sync", "Missing").is_none());
+
+        // Without a title the path is built from the module path
+        let notify = find(&items, "demo::sync::Notify");
+        assert_eq!(notify.kind, "struct");
+        assert_eq!(
+            notify.description.as_deref(),
+            Some("Notifies a single task.")
+        );
+    }
+
+    #[test]
+    fn test_extract_all_items() {
+        let items = extract_all_items(ALL_ITEMS, "demo");
+        let paths: Vec<&str> = items.iter().map(|item| item.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "demo::Handle",
+                "demo::Runtime",
+                "demo::sync::Mutex",
+                "demo::sync::TryLockError",
+                "demo::AsyncRead",
+                "demo::join",
+                "demo::boxed",
+                "demo::spawn",
+                "demo::visit",
+                "demo::Result",
+                "demo::SPAWNED",
+                "demo::VERSION",
+            ]
+        );
+
+        let mutex = find(&items, "demo::sync::Mutex");
+        assert_eq!(mutex.name, "Mutex");
+        assert_eq!(mutex.kind, "struct");
+        assert_eq!(find(&items, "demo::SPAWNED").kind, "static");
+    }
+}
@@ -0,0 +1,1707 @@
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::Read;
+use tracing::debug;
+
+use crate::doc_search::SearchEntry;
+use crate::error::{Error, Result};
+use crate::types::{DocumentationItem, ItemDocumentation, TraitImplementation};
+
+/// Oldest rustdoc JSON `format_version` understood
+pub const MIN_FORMAT_VERSION: u32 = 26;
+
+/// Newest `format_version` this was checked against; newer ones are read on a best-effort basis
+pub const MAX_KNOWN_FORMAT_VERSION: u32 = 57;
+
+/// Largest rustdoc JSON read, compressed or not; parsed, it takes several times as
+/// much memory
+pub const MAX_RUSTDOC_JSON_SIZE: u64 = 256 * 1024 * 1024;
+
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+static NULL: Value = Value::Null;
+
+/// The rustdoc JSON output for one crate
+///
+/// Items are kept as JSON values: the schema changes with every `format_version`,
+/// so fields are looked up under their current and their older names.
+#[derive(Debug, Deserialize)]
+pub struct RustdocCrate {
+    pub format_version: u32,
+    pub crate_version: Option<String>,
+    root: Value,
+    index: HashMap<String, Value>,
+    /// Canonical path of every item, by id
+    #[serde(default)]
+    paths: HashMap<String, Value>,
+    /// Size of the JSON this was parsed from, in bytes
+    #[serde(skip)]
+    pub json_size: u64,
+}
+
+/// Parse rustdoc JSON, compressed with zstd (as served by docs.rs), gzip or not at all
+pub fn parse(bytes: &[u8]) -> Result<RustdocCrate> {
+    let json = decompress(bytes, MAX_RUSTDOC_JSON_SIZE)?;
+    let mut krate: RustdocCrate = serde_json::from_slice(&json)
+        .map_err(|e| Error::InvalidResponse(format!("Failed to parse rustdoc JSON: {}", e)))?;
+    krate.json_size = json.len() as u64;
+
+    if krate.format_version < MIN_FORMAT_VERSION {
+        return Err(Error::InvalidResponse(format!(
+            "Unsupported rustdoc JSON format version {}, the oldest supported is {}",
+            krate.format_version, MIN_FORMAT_VERSION
+        )));
+    }
+    if krate.format_version > MAX_KNOWN_FORMAT_VERSION {
+        debug!(
+            "Reading rustdoc JSON format version {}, newer than {}",
+            krate.format_version, MAX_KNOWN_FORMAT_VERSION
+        );
+    }
+    Ok(krate)
+}
+
+/// Read rustdoc JSON, decompressing it, up to `max_size` bytes
+fn decompress(bytes: &[u8], max_size: u64) -> Result<Vec<u8>> {
+    let failed = |e: std::io::Error| {
+        Error::InvalidResponse(format!("Failed to decompress rustdoc JSON: {}", e))
+    };
+
+    let reader: Box<dyn Read + '_> = if bytes.starts_with(&ZSTD_MAGIC) {
+        Box::new(zstd::stream::read::Decoder::new(bytes).map_err(failed)?)
+    } else if bytes.starts_with(&GZIP_MAGIC) {
+        Box::new(flate2::read::GzDecoder::new(bytes))
+    } else {
+        Box::new(bytes)
+    };
+
+    // Read one byte past the limit to tell a document of exactly `max_size` apart
+    let mut json = Vec::new();
+    reader
+        .take(max_size + 1)
+        .read_to_end(&mut json)
+        .map_err(failed)?;
+    if json.len() as u64 > max_size {
+        return Err(Error::InvalidResponse(format!(
+            "Rustdoc JSON is larger than {} bytes",
+            max_size
+        )));
+    }
+    Ok(json)
+}
+
+impl RustdocCrate {
+    /// Look up an item by id, which is a string in older format versions and a number since
+    fn item(&self, id: &Value) -> Option<&Value> {
+        let key = match id {
+            Value::String(id) => id.clone(),
+            Value::Number(id) => id.to_string(),
+            _ => return None,
+        };
+        self.index.get(&key)
+    }
+
+    fn root(&self) -> Option<&Value> {
+        self.item(&self.root)
+    }
+
+    /// Name of the crate as used in paths, e.g. `serde_json`
+    pub fn crate_name(&self) -> String {
+        self.root()
+            .and_then(|root| root.get("name"))
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string()
+    }
+
+    /// First paragraph of the crate-level documentation
+    pub fn description(&self) -> Option<String> {
+        summary(self.root()?.get("docs")?.as_str()?)
+    }
+
+    /// The crate-level documentation
+    pub fn docs(&self) -> Option<String> {
+        self.root()?
+            .get("docs")?
+            .as_str()
+            .filter(|docs| !docs.trim().is_empty())
+            .map(|docs| docs.to_string())
+    }
+
+    /// Public items of a module with the names they are visible under: its own
+    /// items and the items of this crate it re-exports
+    fn module_items<'a>(&'a self, module: &'a Value) -> Vec<(String, &'a Value)> {
+        let Some(("module", inner)) = item_inner(module) else {
+            return Vec::new();
+        };
+
+        let mut items = Vec::new();
+        for item in array(inner.get("items")).filter_map(|id| self.item(id)) {
+            if item.get("visibility").and_then(Value::as_str) != Some("public") {
+                continue;
+            }
+            match item_inner(item) {
+                Some(("use" | "import", import)) => {
+                    // Glob imports and re-exports of other crates are not followed
+                    if field(import, &["is_glob", "glob"]).and_then(Value::as_bool) == Some(true) {
+                        continue;
+                    }
+                    let target = import.get("id").and_then(|id| self.item(id));
+                    let name = import.get("name").and_then(Value::as_str);
+                    if let (Some(target), Some(name)) = (target, name) {
+                        items.push((name.to_string(), target));
+                    }
+                }
+                Some(_) => {
+                    if let Some(name) = item.get("name").and_then(Value::as_str) {
+                        items.push((name.to_string(), item));
+                    }
+                }
+                None => {}
+            }
+        }
+        items
+    }
+
+    /// Paths of all public modules below the crate root
+    pub fn module_paths(&self) -> Vec<String> {
+        let Some(root) = self.root() else {
+            return Vec::new();
+        };
+
+        let mut paths = Vec::new();
+        let mut seen = HashSet::new();
+        let mut stack = vec![(self.crate_name(), root)];
+        while let Some((path, module)) = stack.pop() {
+            for (name, item) in self.module_items(module) {
+                let id = item.get("id").map(Value::to_string);
+                if matches!(item_inner(item), Some(("module", _))) && seen.insert(id) {
+                    let child = format!("{}::{}", path, name);
+                    paths.push(child.clone());
+                    stack.push((child, item));
+                }
+            }
+        }
+        paths.sort();
+        paths
+    }
+
+    /// Public items declared in or re-exported from the crate root
+    pub fn items(&self) -> Vec<DocumentationItem> {
+        let Some(root) = self.root() else {
+            return Vec::new();
+        };
+        let crate_name = self.crate_name();
+
+        self.module_items(root)
+            .into_iter()
+            .filter_map(|(name, item)| {
+                self.document_item(item, &name, &format!("{}::{}", crate_name, name))
+            })
+            .collect()
+    }
+
+    /// Documentation of the item at a path such as `tokio::sync::Mutex` or
+    /// `tokio::sync::Mutex::lock`; the crate name may be left out
+    pub fn item_documentation(&self, path: &str) -> Option<ItemDocumentation> {
+        let (path, item, associated) = self.resolve(path)?;
+        let name = path.rsplit("::").next().unwrap_or_default().to_string();
+        let kind = match item_inner(item)? {
+            ("function", _) if associated => "method",
+            (kind, inner) => item_kind(kind, inner)?,
+        };
+
+        Some(ItemDocumentation {
+            crate_name: self.crate_name(),
+            version: self.crate_version.clone().unwrap_or_default(),
+            kind: kind.to_string(),
+            signature: self.signature(item, &name),
+            docs: item
+                .get("docs")
+                .and_then(Value::as_str)
+                .filter(|docs| !docs.trim().is_empty())
+                .map(|docs| docs.to_string()),
+            deprecation: item.get("deprecation").and_then(deprecation),
+            methods: self.methods(item, &path),
+            trait_impls: self.trait_impls(item),
+            source: "rustdoc_json".to_string(),
+            format_version: Some(self.format_version),
+            path,
+            name,
+        })
+    }
+
+    /// Find an item by walking its path through modules, re-exports and associated
+    /// items; returns the path it was found at and whether it is an associated item
+    fn resolve(&self, path: &str) -> Option<(String, &Value, bool)> {
+        let crate_name = self.crate_name();
+        let mut segments: Vec<&str> = path
+            .split("::")
+            .map(str::trim)
+            .filter(|segment| !segment.is_empty())
+            .collect();
+        if segments
+            .first()
+            .is_some_and(|first| first.replace('-', "_") == crate_name)
+        {
+            segments.remove(0);
+        }
+
+        let mut resolved = crate_name.clone();
+        let mut current = self.root()?;
+        let mut associated = false;
+        for segment in &segments {
+            if associated {
+                return self.resolve_by_paths(&crate_name, &segments);
+            }
+            let candidates = if matches!(item_inner(current), Some(("module", _))) {
+                self.module_items(current)
+            } else {
+                associated = true;
+                self.associated_items(current)
+            };
+            // A trait and its derive macro share a name, prefer the trait
+            let next = candidates
+                .into_iter()
+                .filter(|(name, _)| name == segment)
+                .min_by_key(|(_, item)| {
+                    matches!(item_inner(item), Some(("macro" | "proc_macro", _)))
+                });
+            let Some((_, item)) = next else {
+                return self.resolve_by_paths(&crate_name, &segments);
+            };
+            resolved = format!("{}::{}", resolved, segment);
+            current = item;
+        }
+        Some((resolved, current, associated))
+    }
+
+    /// Find an item by its canonical path, which also covers items only reachable
+    /// through glob re-exports
+    fn resolve_by_paths(
+        &self,
+        crate_name: &str,
+        segments: &[&str],
+    ) -> Option<(String, &Value, bool)> {
+        let path: Vec<&str> = std::iter::once(crate_name)
+            .chain(segments.iter().copied())
+            .collect();
+        let item = self
+            .paths
+            .iter()
+            .filter(|(_, summary)| summary.get("crate_id").and_then(Value::as_u64) == Some(0))
+            .find(|(_, summary)| {
+                array(summary.get("path"))
+                    .map(|segment| segment.as_str().unwrap_or_default())
+                    .eq(path.iter().copied())
+            })
+            .and_then(|(id, _)| self.index.get(id))?;
+        Some((path.join("::"), item, false))
+    }
+
+    /// Impl blocks of a struct, enum or union, with their contents
+    fn impls<'a>(&'a self, ids: Option<&'a Value>) -> Vec<(&'a Value, &'a Value)> {
+        array(ids)
+            .filter_map(|id| self.item(id))
+            .filter_map(|item| match item_inner(item) {
+                Some(("impl", implementation)) => Some((item, implementation)),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Variants and inherent associated items of a type, or the items of a trait
+    fn associated_items<'a>(&'a self, item: &'a Value) -> Vec<(String, &'a Value)> {
+        let Some((kind, inner)) = item_inner(item) else {
+            return Vec::new();
+        };
+        let ids: Vec<&Value> = match kind {
+            "trait" => array(inner.get("items")).collect(),
+            "struct" | "enum" | "union" => {
+                let mut ids: Vec<&Value> = array(inner.get("variants")).collect();
+                for (_, implementation) in self.impls(inner.get("impls")) {
+                    if implementation.get("trait").is_none_or(Value::is_null) {
+                        ids.extend(array(implementation.get("items")));
+                    }
+                }
+                ids
+            }
+            _ => return Vec::new(),
+        };
+
+        ids.into_iter()
+            .filter_map(|id| self.item(id))
+            .filter(|item| {
+                matches!(
+                    item.get("visibility").and_then(Value::as_str),
+                    Some("public" | "default")
+                )
+            })
+            .filter_map(|item| Some((item.get("name")?.as_str()?.to_string(), item)))
+            .collect()
+    }
+
+    /// Methods, associated types and associated constants of a type or trait
+    fn methods(&self, item: &Value, path: &str) -> Vec<DocumentationItem> {
+        let is_trait = matches!(item_inner(item), Some(("trait", _)));
+
+        self.associated_items(item)
+            .into_iter()
+            .filter_map(|(name, member)| self.document_member(member, name, path, is_trait))
+            .collect()
+    }
+
+    /// Describe an associated item of the type or trait at `parent_path`
+    ///
+    /// `None` for variants, which are part of the enum's signature.
+    fn document_member(
+        &self,
+        member: &Value,
+        name: String,
+        parent_path: &str,
+        is_trait: bool,
+    ) -> Option<DocumentationItem> {
+        let (kind, inner) = item_inner(member)?;
+        let kind = match kind {
+            "function" if is_trait => {
+                if inner.get("has_body").and_then(Value::as_bool) == Some(true) {
+                    "provided_method"
+                } else {
+                    "required_method"
+                }
+            }
+            "function" => "method",
+            "assoc_type" => "associated_type",
+            "assoc_const" => "associated_constant",
+            _ => return None,
+        };
+        Some(DocumentationItem {
+            kind: kind.to_string(),
+            path: format!("{}::{}", parent_path, name),
+            description: member.get("docs").and_then(Value::as_str).and_then(summary),
+            signature: self.signature(member, &name),
+            generics: inner
+                .get("generics")
+                .map(generic_params)
+                .unwrap_or_default(),
+            deprecation: member.get("deprecation").and_then(deprecation),
+            docs: None,
+            name,
+        })
+    }
+
+    /// Every public item and associated item of the crate, to search through
+    ///
+    /// Items re-exported under several paths are listed once, under the shortest.
+    pub fn search_entries(&self) -> Vec<SearchEntry> {
+        let mut entries = Vec::new();
+        for (path, name, item) in self.public_items(false) {
+            let Some((kind, inner)) = item_inner(item) else {
+                continue;
+            };
+            let Some(documented) = self.document_item(item, &name, &path) else {
+                continue;
+            };
+            let (inputs, output) = function_types(kind, inner, None);
+            entries.push(SearchEntry {
+                item: DocumentationItem {
+                    docs: None,
+                    ..documented
+                },
+                inputs,
+                output,
+            });
+
+            let is_trait = kind == "trait";
+            for (member_name, member) in self.associated_items(item) {
+                let Some(documented) = self.document_member(member, member_name, &path, is_trait)
+                else {
+                    continue;
+                };
+                let (inputs, output) = match item_inner(member) {
+                    Some((kind, inner)) => function_types(kind, inner, Some(&name)),
+                    None => (Vec::new(), None),
+                };
+                entries.push(SearchEntry {
+                    item: documented,
+                    inputs,
+                    output,
+                });
+            }
+        }
+        entries
+    }
+
+    /// Every public item and associated item under every path it can be named by,
+    /// to compare the public API of two releases
+    pub fn api_items(&self) -> Vec<DocumentationItem> {
+        let mut items = Vec::new();
+        for (path, name, item) in self.public_items(true) {
+            let Some(documented) = self.document_item(item, &name, &path) else {
+                continue;
+            };
+            items.push(DocumentationItem {
+                docs: None,
+                ..documented
+            });
+            items.extend(self.methods(item, &path));
+        }
+        items
+    }
+
+    /// Public items with their paths and names, walking modules breadth-first so
+    /// shorter paths come first
+    ///
+    /// With `every_path`, items re-exported under several paths are listed under
+    /// each of them, otherwise once, under the shortest.
+    fn public_items(&self, every_path: bool) -> Vec<(String, String, &Value)> {
+        let Some(root) = self.root() else {
+            return Vec::new();
+        };
+
+        let mut items = Vec::new();
+        let mut seen_items = HashSet::new();
+        let mut seen_modules = HashSet::new();
+        let mut modules = VecDeque::from([(self.crate_name(), root)]);
+        while let Some((module_path, module)) = modules.pop_front() {
+            for (name, item) in self.module_items(module) {
+                let id = item.get("id").map(Value::to_string);
+                if !every_path && !seen_items.insert(id.clone()) {
+                    continue;
+                }
+                let path = format!("{}::{}", module_path, name);
+                if matches!(item_inner(item), Some(("module", _))) && seen_modules.insert(id) {
+                    modules.push_back((path.clone(), item));
+                }
+                items.push((path, name, item));
+            }
+        }
+        items
+    }
+
+    /// Trait implementations of a type, or the implementations of a trait
+    ///
+    /// Hand-written impls come first, then auto traits, then blanket impls.
+    fn trait_impls(&self, item: &Value) -> Vec<TraitImplementation> {
+        let ids = match item_inner(item) {
+            Some(("trait", inner)) => inner.get("implementations"),
+            Some(("struct" | "enum" | "union", inner)) => inner.get("impls"),
+            _ => return Vec::new(),
+        };
+
+        let mut impls: Vec<TraitImplementation> = self
+            .impls(ids)
+            .into_iter()
+            .filter_map(|(item, implementation)| {
+                let trait_path = implementation.get("trait").filter(|t| !t.is_null())?;
+                let kind = if field(implementation, &["is_synthetic", "synthetic"])
+                    .and_then(Value::as_bool)
+                    == Some(true)
+                {
+                    "auto"
+                } else if implementation
+                    .get("blanket_impl")
+                    .is_some_and(|blanket| !blanket.is_null())
+                {
+                    "blanket"
+                } else {
+                    "explicit"
+                };
+                Some(TraitImplementation {
+                    trait_name: render_path(trait_path),
+                    for_type: render_type(implementation.get("for")?),
+                    signature: render_impl(implementation),
+                    kind: kind.to_string(),
+                    description: item.get("docs").and_then(Value::as_str).and_then(summary),
+                    items: array(implementation.get("items"))
+                        .filter_map(|id| self.item(id))
+                        .filter_map(|member| member.get("name")?.as_str())
+                        .map(|name| name.to_string())
+                        .collect(),
+                })
+            })
+            .collect();
+        impls.sort_by_key(|implementation| match implementation.kind.as_str() {
+            "explicit" => 0,
+            "auto" => 1,
+            _ => 2,
+        });
+        impls
+    }
+
+    /// Describe an item under the name and path it is visible at
+    ///
+    /// `None` for kinds that are not listed on module pages, like impls or fields.
+    fn document_item(&self, item: &Value, name: &str, path: &str) -> Option<DocumentationItem> {
+        let (kind, inner) = item_inner(item)?;
+        let kind = item_kind(kind, inner)?;
+        let docs = item
+            .get("docs")
+            .and_then(Value::as_str)
+            .filter(|docs| !docs.trim().is_empty());
+
+        Some(DocumentationItem {
+            name: name.to_string(),
+            kind: kind.to_string(),
+            path: path.to_string(),
+            description: docs.and_then(summary),
+            signature: self.signature(item, name),
+            generics: inner
+                .get("generics")
+                .map(generic_params)
+                .unwrap_or_default(),
+            docs: docs.map(|docs| docs.to_string()),
+            deprecation: item.get("deprecation").and_then(deprecation),
+        })
+    }
+
+    /// Declaration of an item as rustdoc shows it, bodies elided
+    fn signature(&self, item: &Value, name: &str) -> Option<String> {
+        let (kind, inner) = item_inner(item)?;
+        let generics = inner.get("generics");
+        let params = render_generics(generics);
+        let where_clause = render_where(generics);
+
+        let signature = match kind {
+            "function" => {
+                let visibility = match item.get("visibility").and_then(Value::as_str) {
+                    Some("public") => "pub ",
+                    _ => "",
+                };
+                render_function(name, inner, visibility)
+            }
+            "variant" => self.variant(item)?.trim_end_matches(',').to_string(),
+            "assoc_type" | "assoc_const" => self.trait_item(item)?,
+            "struct" | "union" => {
+                let body = match inner.get("kind").and_then(tagged) {
+                    Some(("unit", _)) => format!("{};", where_clause),
+                    Some(("tuple", fields)) => {
+                        format!("({}){};", self.tuple_fields(fields), where_clause)
+                    }
+                    Some(("plain", plain)) => format!(
+                        "{} {}",
+                        where_clause,
+                        self.block(
+                            plain.get("fields"),
+                            field(plain, &["has_stripped_fields", "fields_stripped"]),
+                            "/* private fields */",
+                            |field| self.named_field(field).map(|f| format!("{},", f)),
+                        )
+                    ),
+                    // Unions have their fields directly
+                    _ => format!(
+                        "{} {}",
+                        where_clause,
+                        self.block(
+                            inner.get("fields"),
+                            field(inner, &["has_stripped_fields", "fields_stripped"]),
+                            "/* private fields */",
+                            |field| self.named_field(field).map(|f| format!("{},", f)),
+                        )
+                    ),
+                };
+                format!(
+                    "{}pub {} {}{}{}",
+                    non_exhaustive_attr(item),
+                    kind,
+                    name,
+                    params,
+                    body
+                )
+            }
+            "enum" => format!(
+                "{}pub enum {}{}{} {}",
+                non_exhaustive_attr(item),
+                name,
+                params,
+                where_clause,
+                self.block(
+                    inner.get("variants"),
+                    field(inner, &["has_stripped_variants", "variants_stripped"]),
+                    "// some variants omitted",
+                    |variant| self.variant(variant),
+                )
+            ),
+            "trait" => {
+                let flag =
+                    |names: &[&str]| field(inner, names).and_then(Value::as_bool) == Some(true);
+                let bounds = render_bounds(inner.get("bounds"));
+                format!(
+                    "pub {}{}trait {}{}{}{} {}",
+                    if flag(&["is_unsafe"]) { "unsafe " } else { "" },
+                    if flag(&["is_auto"]) { "auto " } else { "" },
+                    name,
+                    params,
+                    if bounds.is_empty() {
+                        String::new()
+                    } else {
+                        format!(": {}", bounds)
+                    },
+                    where_clause,
+                    self.block(inner.get("items"), None, "", |item| {
+                        self.trait_item(item)
+                    })
+                )
+            }
+            "trait_alias" => format!(
+                "pub trait {}{} = {}{};",
+                name,
+                params,
+                render_bounds(inner.get("params")),
+                where_clause
+            ),
+            "type_alias" | "typedef" => format!(
+                "pub type {}{}{} = {};",
+                name,
+                params,
+                where_clause,
+                render_type(inner.get("type")?)
+            ),
+            "constant" => {
+                // The expression moved into a nested `const` object
+                let expr = inner
+                    .get("const")
+                    .unwrap_or(inner)
+                    .get("expr")
+                    .and_then(Value::as_str)
+                    .unwrap_or("_");
+                format!(
+                    "pub const {}: {} = {};",
+                    name,
+                    render_type(inner.get("type")?),
+                    expr
+                )
+            }
+            "static" => format!(
+                "pub static {}{}: {};",
+                if field(inner, &["is_mutable", "mutable"]).and_then(Value::as_bool) == Some(true) {
+                    "mut "
+                } else {
+                    ""
+                },
+                name,
+                render_type(inner.get("type")?)
+            ),
+            "macro" => inner.as_str()?.to_string(),
+            "proc_macro" => match inner.get("kind").and_then(Value::as_str) {
+                Some("derive") => format!("#[derive({})]", name),
+                Some("attr") => format!("#[{}]", name),
+                _ => format!("{}!() {{ ... }}", name),
+            },
+            _ => return None,
+        };
+        Some(signature)
+    }
+
+    /// A `{ ... }` block with one line per child, noting children rustdoc left out
+    fn block(
+        &self,
+        ids: Option<&Value>,
+        stripped: Option<&Value>,
+        stripped_note: &str,
+        render: impl Fn(&Value) -> Option<String>,
+    ) -> String {
+        let mut lines: Vec<String> = array(ids)
+            .filter_map(|id| self.item(id))
+            .filter_map(render)
+            .map(|line| format!("    {}", line))
+            .collect();
+        if stripped.and_then(Value::as_bool) == Some(true) {
+            lines.push(format!("    {}", stripped_note));
+        }
+        if lines.is_empty() {
+            "{}".to_string()
+        } else {
+            format!("{{\n{}\n}}", lines.join("\n"))
+        }
+    }
+
+    /// `pub name: Type` of a public named field
+    fn named_field(&self, item: &Value) -> Option<String> {
+        let Some(("struct_field", ty)) = item_inner(item) else {
+            return None;
+        };
+        let visibility = match item.get("visibility").and_then(Value::as_str) {
+            Some("public") => "pub ",
+            Some("default") => "",
+            _ => return None,
+        };
+        Some(format!(
+            "{}{}: {}",
+            visibility,
+            item.get("name")?.as_str()?,
+            render_type(ty)
+        ))
+    }
+
+    /// Fields of a tuple struct or variant, with `_` for private and stripped ones
+    fn tuple_fields(&self, fields: &Value) -> String {
+        array(Some(fields))
+            .map(|id| {
+                let field = self.item(id);
+                match field.and_then(item_inner) {
+                    Some(("struct_field", ty)) => {
+                        match field
+                            .and_then(|f| f.get("visibility"))
+                            .and_then(Value::as_str)
+                        {
+                            Some("public") => format!("pub {}", render_type(ty)),
+                            Some("default") => render_type(ty),
+                            _ => "_".to_string(),
+                        }
+                    }
+                    _ => "_".to_string(),
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    fn variant(&self, item: &Value) -> Option<String> {
+        let name = item.get("name")?.as_str()?;
+        let Some(("variant", variant)) = item_inner(item) else {
+            return None;
+        };
+        let line = match variant.get("kind").and_then(tagged) {
+            Some(("tuple", fields)) => format!("{}({}),", name, self.tuple_fields(fields)),
+            Some(("struct", fields)) => {
+                let fields: Vec<String> = array(fields.get("fields"))
+                    .filter_map(|id| self.item(id))
+                    .filter_map(|field| self.named_field(field))
+                    .collect();
+                format!("{} {{ {} }},", name, fields.join(", "))
+            }
+            _ => format!("{},", name),
+        };
+        Some(line)
+    }
+
+    /// Associated type, constant or method of a trait
+    fn trait_item(&self, item: &Value) -> Option<String> {
+        let name = item.get("name")?.as_str()?;
+        let line = match item_inner(item)? {
+            ("function", function) => {
+                let body = if function.get("has_body").and_then(Value::as_bool) == Some(true) {
+                    " { ... }"
+                } else {
+                    ";"
+                };
+                format!("{}{}", render_function(name, function, ""), body)
+            }
+            ("assoc_type", assoc) => {
+                let bounds = render_bounds(assoc.get("bounds"));
+                let default = field(assoc, &["type", "default"])
+                    .filter(|ty| !ty.is_null())
+                    .map(|ty| format!(" = {}", render_type(ty)))
+                    .unwrap_or_default();
+                format!(
+                    "type {}{}{}{};",
+                    name,
+                    render_generics(assoc.get("generics")),
+                    if bounds.is_empty() {
+                        String::new()
+                    } else {
+                        format!(": {}", bounds)
+                    },
+                    default
+                )
+            }
+            ("assoc_const", assoc) => {
+                let default = field(assoc, &["value", "default"])
+                    .and_then(Value::as_str)
+                    .map(|value| format!(" = {}", value))
+                    .unwrap_or_default();
+                format!(
+                    "const {}: {}{};",
+                    name,
+                    render_type(assoc.get("type")?),
+                    default
+                )
+            }
+            _ => return None,
+        };
+        Some(line)
+    }
+}
+
+/// `#[non_exhaustive]` and a line break for items marked with it, which rustdoc
+/// writes as `non_exhaustive`, `#[non_exhaustive]` or `{"other": "#[attr = NonExhaustive]"}`
+fn non_exhaustive_attr(item: &Value) -> &'static str {
+    let non_exhaustive = array(item.get("attrs")).any(|attr| {
+        attr.as_str()
+            .or_else(|| attr.get("other").and_then(Value::as_str))
+            .is_some_and(|attr| {
+                matches!(attr.trim(), "non_exhaustive" | "#[non_exhaustive]")
+                    || attr.contains("NonExhaustive")
+            })
+    });
+    if non_exhaustive {
+        "#[non_exhaustive]\n"
+    } else {
+        ""
+    }
+}
+
+/// The kind tag and contents of an item, tagged with `kind`/`inner` fields in older
+/// format versions and as a single-key `inner` object since
+fn item_inner(item: &Value) -> Option<(&str, &Value)> {
+    if item.get("kind").is_some_and(Value::is_string) {
+        tagged(item)
+    } else {
+        tagged(item.get("inner")?)
+    }
+}
+
+/// Split an enum value serialized by serde: `"unit"`, `{"tag": value}` or
+/// `{"kind": "tag", "inner": value}`
+fn tagged(value: &Value) -> Option<(&str, &Value)> {
+    match value {
+        Value::String(tag) => Some((tag, &NULL)),
+        Value::Object(map) => {
+            if let (Some(Value::String(tag)), Some(inner)) = (map.get("kind"), map.get("inner")) {
+                return Some((tag, inner));
+            }
+            if map.len() == 1 {
+                map.iter().next().map(|(tag, inner)| (tag.as_str(), inner))
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// The first of several spellings a field had across format versions
+fn field<'a>(value: &'a Value, names: &[&str]) -> Option<&'a Value> {
+    names
+        .iter()
+        .find_map(|name| value.get(*name).filter(|v| !v.is_null()))
+}
+
+fn array(value: Option<&Value>) -> impl Iterator<Item = &Value> {
+    value.and_then(Value::as_array).into_iter().flatten()
+}
+
+fn item_kind(kind: &str, inner: &Value) -> Option<&'static str> {
+    let kind = match kind {
+        "module" => "module",
+        "struct" => "struct",
+        "enum" => "enum",
+        "union" => "union",
+        "trait" => "trait",
+        "trait_alias" => "trait_alias",
+        "function" => "function",
+        "macro" => "macro",
+        "proc_macro" => match inner.get("kind").and_then(Value::as_str) {
+            Some("attr") => "attribute_macro",
+            Some("derive") => "derive_macro",
+            _ => "macro",
+        },
+        "type_alias" | "typedef" => "type_alias",
+        "constant" => "constant",
+        "static" => "static",
+        "variant" => "variant",
+        "assoc_type" => "associated_type",
+        "assoc_const" => "associated_constant",
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// First paragraph of a doc comment, on one line
+pub fn summary(docs: &str) -> Option<String> {
+    let paragraph = docs.trim().split("\n\n").next()?;
+    let summary = paragraph.split_whitespace().collect::<Vec<_>>().join(" ");
+    (!summary.is_empty()).then_some(summary)
+}
+
+fn deprecation(deprecation: &Value) -> Option<String> {
+    if deprecation.is_null() {
+        return None;
+    }
+    let since = deprecation.get("since").and_then(Value::as_str);
+    let note = deprecation.get("note").and_then(Value::as_str);
+    Some(match (since, note) {
+        (Some(since), Some(note)) => format!("deprecated since {}: {}", since, note),
+        (Some(since), None) => format!("deprecated since {}", since),
+        (None, Some(note)) => format!("deprecated: {}", note),
+        (None, None) => "deprecated".to_string(),
+    })
+}
+
+fn render_function(name: &str, function: &Value, prefix: &str) -> String {
+    let header = function.get("header").unwrap_or(&NULL);
+    let generics = function.get("generics");
+    let sig = field(function, &["sig", "decl"]).unwrap_or(&NULL);
+
+    let inputs: Vec<String> = array(sig.get("inputs"))
+        .filter_map(|input| {
+            let name = input.get(0)?.as_str()?;
+            Some(render_input(name, input.get(1)?))
+        })
+        .collect();
+
+    format!(
+        "{}{}fn {}{}({}){}{}",
+        prefix,
+        render_qualifiers(header),
+        name,
+        render_generics(generics),
+        inputs.join(", "),
+        render_output(sig),
+        render_where(generics)
+    )
+}
+
+/// Rendered parameter and return types of a function, with `Self` spelled as the
+/// type or trait it belongs to
+fn function_types(
+    kind: &str,
+    function: &Value,
+    self_type: Option<&str>,
+) -> (Vec<String>, Option<String>) {
+    if kind != "function" {
+        return (Vec::new(), None);
+    }
+    let sig = field(function, &["sig", "decl"]).unwrap_or(&NULL);
+    let render = |ty: &Value| {
+        let rendered = render_type(ty);
+        match self_type {
+            Some(self_type) => replace_self(&rendered, self_type),
+            None => rendered,
+        }
+    };
+
+    let inputs = array(sig.get("inputs"))
+        .filter_map(|input| input.get(1))
+        .map(render)
+        .collect();
+    let output = sig
+        .get("output")
+        .filter(|output| !output.is_null())
+        .map(render);
+    (inputs, output)
+}
+
+/// Replace `Self` as a whole word, leaving `Self::Error` alone
+fn replace_self(ty: &str, self_type: &str) -> String {
+    let mut replaced = String::new();
+    let mut rest = ty;
+    while let Some(position) = rest.find("Self") {
+        let before = rest[..position].chars().next_back();
+        let after = &rest[position + 4..];
+        let is_word = !before.is_some_and(|c| c.is_alphanumeric() || c == '_')
+            && !after.starts_with(|c: char| c.is_alphanumeric() || c == '_' || c == ':');
+        replaced.push_str(&rest[..position]);
+        replaced.push_str(if is_word { self_type } else { "Self" });
+        rest = after;
+    }
+    replaced.push_str(rest);
+    replaced
+}
+
+/// Header of an impl block, e.g. `impl<T: Default> Default for Mutex<T>`
+fn render_impl(implementation: &Value) -> String {
+    let generics = implementation.get("generics");
+    let flag = |names: &[&str]| field(implementation, names).and_then(Value::as_bool) == Some(true);
+
+    let trait_path = implementation
+        .get("trait")
+        .filter(|t| !t.is_null())
+        .map(|t| {
+            format!(
+                "{}{} for ",
+                if flag(&["is_negative", "negative"]) {
+                    "!"
+                } else {
+                    ""
+                },
+                render_path(t)
+            )
+        })
+        .unwrap_or_default();
+    format!(
+        "{}impl{} {}{}{}",
+        if flag(&["is_unsafe"]) { "unsafe " } else { "" },
+        render_generics(generics),
+        trait_path,
+        render_type(implementation.get("for").unwrap_or(&NULL)),
+        render_where(generics)
+    )
+}
+
+/// `const`, `async`, `unsafe` and `extern "abi"` of a function header
+fn render_qualifiers(header: &Value) -> String {
+    let flag = |names: &[&str]| field(header, names).and_then(Value::as_bool) == Some(true);
+
+    let mut qualifiers = String::new();
+    if flag(&["is_const", "const_"]) {
+        qualifiers.push_str("const ");
+    }
+    if flag(&["is_async", "async_"]) {
+        qualifiers.push_str("async ");
+    }
+    if flag(&["is_unsafe", "unsafe_"]) {
+        qualifiers.push_str("unsafe ");
+    }
+    match header.get("abi").and_then(tagged) {
+        Some(("Rust", _)) | None => {}
+        Some((abi, _)) => qualifiers.push_str(&format!("extern \"{}\" ", abi)),
+    }
+    qualifiers
+}
+
+fn render_input(name: &str, ty: &Value) -> String {
+    let is_self = |ty: &Value| matches!(tagged(ty), Some(("generic", g)) if g == "Self");
+    if name == "self" {
+        match tagged(ty) {
+            Some(("generic", _)) if is_self(ty) => return "self".to_string(),
+            Some(("borrowed_ref", reference)) if reference.get("type").is_some_and(is_self) => {
+                return format!("{}self", render_reference_prefix(reference));
+            }
+            _ => {}
+        }
+    }
+    format!("{}: {}", name, render_type(ty))
+}
+
+fn render_output(sig: &Value) -> String {
+    match sig.get("output") {
+        Some(output) if !output.is_null() => format!(" -> {}", render_type(output)),
+        _ => String::new(),
+    }
+}
+
+/// `&`, `&mut ` or `&'a mut ` of a reference
+fn render_reference_prefix(reference: &Value) -> String {
+    let lifetime = reference
+        .get("lifetime")
+        .and_then(Value::as_str)
+        .map(|lifetime| format!("{} ", lifetime))
+        .unwrap_or_default();
+    let mutable =
+        field(reference, &["is_mutable", "mutable"]).and_then(Value::as_bool) == Some(true);
+    format!("&{}{}", lifetime, if mutable { "mut " } else { "" })
+}
+
+/// Render a type as it would be written in source
+pub fn render_type(ty: &Value) -> String {
+    let Some((kind, inner)) = tagged(ty) else {
+        return "_".to_string();
+    };
+    match kind {
+        "resolved_path" => render_path(inner),
+        "generic" | "primitive" => inner.as_str().unwrap_or("_").to_string(),
+        "borrowed_ref" => format!(
+            "{}{}",
+            render_reference_prefix(inner),
+            render_type(inner.get("type").unwrap_or(&NULL))
+        ),
+        "raw_pointer" => format!(
+            "*{} {}",
+            if field(inner, &["is_mutable", "mutable"]).and_then(Value::as_bool) == Some(true) {
+                "mut"
+            } else {
+                "const"
+            },
+            render_type(inner.get("type").unwrap_or(&NULL))
+        ),
+        "slice" => format!("[{}]", render_type(inner)),
+        "array" => format!(
+            "[{}; {}]",
+            render_type(inner.get("type").unwrap_or(&NULL)),
+            inner.get("len").and_then(Value::as_str).unwrap_or("_")
+        ),
+        "tuple" => {
+            let types: Vec<String> = array(Some(inner)).map(render_type).collect();
+            if types.len() == 1 {
+                format!("({},)", types[0])
+            } else {
+                format!("({})", types.join(", "))
+            }
+        }
+        "impl_trait" => format!("impl {}", render_bounds(Some(inner))),
+        "dyn_trait" => {
+            let mut bounds: Vec<String> = array(inner.get("traits"))
+                .map(|poly| {
+                    format!(
+                        "{}{}",
+                        render_for(poly.get("generic_params")),
+                        render_path(poly.get("trait").unwrap_or(&NULL))
+                    )
+                })
+                .collect();
+            if let Some(lifetime) = inner.get("lifetime").and_then(Value::as_str) {
+                bounds.push(lifetime.to_string());
+            }
+            format!("dyn {}", bounds.join(" + "))
+        }
+        "qualified_path" => {
+            let self_type = render_type(inner.get("self_type").unwrap_or(&NULL));
+            let name = inner.get("name").and_then(Value::as_str).unwrap_or("_");
+            let args = render_args(inner.get("args"));
+            // An empty trait path stands for the `T::Name` shorthand
+            match inner.get("trait").filter(|t| !t.is_null()) {
+                Some(tr)
+                    if !field(tr, &["path", "name"])
+                        .and_then(Value::as_str)
+                        .unwrap_or("")
+                        .is_empty() =>
+                {
+                    format!("<{} as {}>::{}{}", self_type, render_path(tr), name, args)
+                }
+                _ => format!("{}::{}{}", self_type, name, args),
+            }
+        }
+        "function_pointer" => {
+            let sig = field(inner, &["sig", "decl"]).unwrap_or(&NULL);
+            let inputs: Vec<String> = array(sig.get("inputs"))
+                .filter_map(|input| input.get(1))
+                .map(render_type)
+                .collect();
+            format!(
+                "{}{}fn({}){}",
+                render_for(inner.get("generic_params")),
+                render_qualifiers(inner.get("header").unwrap_or(&NULL)),
+                inputs.join(", "),
+                render_output(sig)
+            )
+        }
+        "pat" => render_type(inner.get("type").unwrap_or(&NULL)),
+        _ => "_".to_string(),
+    }
+}
+
+/// A path with its generic arguments, e.g. `Option<&str>`
+fn render_path(path: &Value) -> String {
+    format!(
+        "{}{}",
+        field(path, &["path", "name"])
+            .and_then(Value::as_str)
+            .unwrap_or("_"),
+        render_args(path.get("args"))
+    )
+}
+
+fn render_args(args: Option<&Value>) -> String {
+    let Some((kind, args)) = args.and_then(tagged) else {
+        return String::new();
+    };
+    match kind {
+        "angle_bracketed" => {
+            let mut parts: Vec<String> = array(args.get("args"))
+                .map(|arg| match tagged(arg) {
+                    Some(("type", ty)) => render_type(ty),
+                    Some(("lifetime", lifetime)) => lifetime.as_str().unwrap_or("'_").to_string(),
+                    Some(("const", constant)) => constant
+                        .get("expr")
+                        .and_then(Value::as_str)
+                        .unwrap_or("_")
+                        .to_string(),
+                    _ => "_".to_string(),
+                })
+                .collect();
+            parts.extend(array(field(args, &["constraints", "bindings"])).map(render_constraint));
+            if parts.is_empty() {
+                String::new()
+            } else {
+                format!("<{}>", parts.join(", "))
+            }
+        }
+        "parenthesized" => {
+            let inputs: Vec<String> = array(args.get("inputs")).map(render_type).collect();
+            format!("({}){}", inputs.join(", "), render_output(args))
+        }
+        _ => "(..)".to_string(),
+    }
+}
+
+/// An associated item constraint like `Item = u8` or `Item: Send`
+fn render_constraint(constraint: &Value) -> String {
+    let name = format!(
+        "{}{}",
+        constraint
+            .get("name")
+            .and_then(Value::as_str)
+            .unwrap_or("_"),
+        render_args(constraint.get("args"))
+    );
+    match constraint.get("binding").and_then(tagged) {
+        Some(("equality", term)) => format!("{} = {}", name, render_term(term)),
+        Some(("constraint", bounds)) => format!("{}: {}", name, render_bounds(Some(bounds))),
+        _ => name,
+    }
+}
+
+fn render_term(term: &Value) -> String {
+    match tagged(term) {
+        Some(("type", ty)) => render_type(ty),
+        Some(("constant", constant)) => constant
+            .get("expr")
+            .and_then(Value::as_str)
+            .unwrap_or("_")
+            .to_string(),
+        _ => render_type(term),
+    }
+}
+
+fn render_bounds(bounds: Option<&Value>) -> String {
+    array(bounds)
+        .map(|bound| match tagged(bound) {
+            Some(("trait_bound", bound)) => {
+                let modifier = match bound.get("modifier").and_then(Value::as_str) {
+                    Some("maybe") => "?",
+                    Some("maybe_const") => "~const ",
+                    _ => "",
+                };
+                format!(
+                    "{}{}{}",
+                    render_for(bound.get("generic_params")),
+                    modifier,
+                    render_path(bound.get("trait").unwrap_or(&NULL))
+                )
+            }
+            Some(("outlives", lifetime)) => lifetime.as_str().unwrap_or("'_").to_string(),
+            Some(("use", args)) => {
+                let args: Vec<&str> = array(Some(args))
+                    .filter_map(|arg| match arg {
+                        Value::String(arg) => Some(arg.as_str()),
+                        _ => tagged(arg).and_then(|(_, arg)| arg.as_str()),
+                    })
+                    .collect();
+                format!("use<{}>", args.join(", "))
+            }
+            _ => "_".to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+/// Higher-ranked `for<'a> ` binder
+fn render_for(params: Option<&Value>) -> String {
+    let params: Vec<String> = array(params).filter_map(render_param).collect();
+    if params.is_empty() {
+        String::new()
+    } else {
+        format!("for<{}> ", params.join(", "))
+    }
+}
+
+/// Generic parameters as declared, leaving out the ones `impl Trait` arguments introduce
+pub fn generic_params(generics: &Value) -> Vec<String> {
+    array(generics.get("params"))
+        .filter_map(render_param)
+        .collect()
+}
+
+fn render_param(param: &Value) -> Option<String> {
+    let name = param.get("name")?.as_str()?;
+    let rendered = match param.get("kind").and_then(tagged)? {
+        ("lifetime", lifetime) => {
+            let outlives: Vec<&str> = array(lifetime.get("outlives"))
+                .filter_map(Value::as_str)
+                .collect();
+            if outlives.is_empty() {
+                name.to_string()
+            } else {
+                format!("{}: {}", name, outlives.join(" + "))
+            }
+        }
+        ("type", ty) => {
+            if field(ty, &["is_synthetic", "synthetic"]).and_then(Value::as_bool) == Some(true) {
+                return None;
+            }
+            let bounds = render_bounds(ty.get("bounds"));
+            let mut rendered = name.to_string();
+            if !bounds.is_empty() {
+                rendered.push_str(&format!(": {}", bounds));
+            }
+            if let Some(default) = ty.get("default").filter(|d| !d.is_null()) {
+                rendered.push_str(&format!(" = {}", render_type(default)));
+            }
+            rendered
+        }
+        ("const", constant) => {
+            let mut rendered = format!(
+                "const {}: {}",
+                name,
+                render_type(constant.get("type").unwrap_or(&NULL))
+            );
+            if let Some(default) = constant.get("default").and_then(Value::as_str) {
+                rendered.push_str(&format!(" = {}", default));
+            }
+            rendered
+        }
+        _ => return None,
+    };
+    Some(rendered)
+}
+
+fn render_generics(generics: Option<&Value>) -> String {
+    let params = generics.map(generic_params).unwrap_or_default();
+    if params.is_empty() {
+        String::new()
+    } else {
+        format!("<{}>", params.join(", "))
+    }
+}
+
+fn render_where(generics: Option<&Value>) -> String {
+    let predicates: Vec<String> = array(generics.and_then(|g| g.get("where_predicates")))
+        .filter_map(|predicate| match tagged(predicate)? {
+            ("bound_predicate", bound) => Some(format!(
+                "{}{}: {}",
+                render_for(bound.get("generic_params")),
+                render_type(bound.get("type")?),
+                render_bounds(bound.get("bounds"))
+            )),
+            ("lifetime_predicate" | "region_predicate", lifetime) => Some(format!(
+                "{}: {}",
+                lifetime.get("lifetime")?.as_str()?,
+                array(lifetime.get("outlives"))
+                    .filter_map(Value::as_str)
+                    .collect::<Vec<_>>()
+                    .join(" + ")
+            )),
+            ("eq_predicate", eq) => Some(format!(
+                "{} = {}",
+                render_type(eq.get("lhs")?),
+                render_term(eq.get("rhs")?)
+            )),
+            _ => None,
+        })
+        .collect();
+    if predicates.is_empty() {
+        String::new()
+    } else {
+        format!(" where {}", predicates.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::doc_search;
+
+    const CURRENT_FORMAT: &str = include_str!("../tests/fixtures/rustdoc/demo-v57.json");
+    const LEGACY_FORMAT: &str = include_str!("../tests/fixtures/rustdoc/demo-v26.json");
+
+    fn find<'a>(items: &'a [DocumentationItem], name: &str) -> &'a DocumentationItem {
+        items
+            .iter()
+            .find(|item| item.name == name)
+            .unwrap_or_else(|| panic!("{} not extracted", name))
+    }
+
+    fn assert_demo_crate(krate: &RustdocCrate) {
+        assert_eq!(krate.crate_name(), "demo");
+        assert_eq!(krate.crate_version.as_deref(), Some("1.2.0"));
+        assert_eq!(
+            krate.description().as_deref(),
+            Some("A demo crate for asynchronous applications.")
+        );
+        assert_eq!(krate.module_paths(), vec!["demo::sync"]);
+
+        let items = krate.items();
+        let kinds: Vec<(&str, &str)> = items
+            .iter()
+            .map(|item| (item.name.as_str(), item.kind.as_str()))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ("sync", "module"),
+                ("AsyncRead", "trait"),
+                ("Runtime", "struct"),
+                ("Handle", "struct"),
+                ("spawn", "function"),
+                ("visit", "function"),
+                ("boxed", "function"),
+                ("Result", "type_alias"),
+                ("VERSION", "constant"),
+                ("SPAWNED", "static"),
+                ("Mutex", "struct"),
+                ("join", "macro"),
+            ]
+        );
+
+        let spawn = find(&items, "spawn");
+        assert_eq!(spawn.path, "demo::spawn");
+        assert_eq!(
+            spawn.signature.as_deref(),
+            Some(
+                "pub fn spawn<F>(future: F, name: Option<&str>) -> Handle \
+                 where F: std::future::Future + Send + 'static, F::Output: Send"
+            )
+        );
+        assert_eq!(spawn.generics, vec!["F"]);
+        assert_eq!(
+            find(&items, "visit").signature.as_deref(),
+            Some(
+                "pub unsafe extern \"C\" fn visit(items: *const u8, len: usize, f: fn(&u8) -> bool)"
+            )
+        );
+        let boxed = find(&items, "boxed");
+        assert_eq!(
+            boxed.signature.as_deref(),
+            Some(
+                "pub fn boxed(reader: impl AsyncRead<Error = std::io::Error> + 'static) \
+                 -> Box<dyn AsyncRead<Error = std::io::Error>>"
+            )
+        );
+        assert!(boxed.generics.is_empty());
+
+        let runtime = find(&items, "Runtime");
+        assert_eq!(
+            runtime.signature.as_deref(),
+            Some("pub struct Runtime(pub usize);")
+        );
+        assert_eq!(
+            runtime.deprecation.as_deref(),
+            Some("deprecated since 1.1.0: use `Handle` instead")
+        );
+        assert_eq!(
+            find(&items, "Handle").signature.as_deref(),
+            Some("pub struct Handle {\n    pub workers: usize,\n    /* private fields */\n}")
+        );
+
+        // Re-exports are listed under the re-exported path
+        let mutex = find(&items, "Mutex");
+        assert_eq!(mutex.path, "demo::Mutex");
+        assert_eq!(mutex.generics, vec!["T: ?Sized"]);
+        assert_eq!(
+            mutex.description.as_deref(),
+            Some("An asynchronous `Mutex`-like type.")
+        );
+        assert_eq!(
+            mutex.docs.as_deref(),
+            Some("An asynchronous `Mutex`-like type.\n\nLocking is fair.")
+        );
+
+        assert_eq!(
+            find(&items, "AsyncRead").signature.as_deref(),
+            Some(
+                "pub trait AsyncRead: Send {\n    type Error;\n    \
+                 fn poll_read(&mut self, buf: &mut [u8]) -> Result<usize, Self::Error>;\n    \
+                 fn read_exact<'a>(&'a mut self, buf: &'a mut [u8]) -> Result<(), Self::Error> { ... }\n}"
+            )
+        );
+        assert_eq!(
+            find(&items, "Result").signature.as_deref(),
+            Some("pub type Result<T, E = std::io::Error> = std::result::Result<T, E>;")
+        );
+        assert_eq!(
+            find(&items, "VERSION").signature.as_deref(),
+            Some("pub const VERSION: &str = \"1.2.0\";")
+        );
+        assert_eq!(
+            find(&items, "SPAWNED").signature.as_deref(),
+            Some("pub static SPAWNED: std::sync::atomic::AtomicUsize;")
+        );
+        assert!(
+            find(&items, "join")
+                .signature
+                .as_deref()
+                .is_some_and(|s| s.starts_with("macro_rules! join {"))
+        );
+    }
+
+    #[test]
+    fn test_item_documentation() {
+        for fixture in [CURRENT_FORMAT, LEGACY_FORMAT] {
+            let krate = parse(fixture.as_bytes()).unwrap();
+
+            let mutex = krate.item_documentation("demo::sync::Mutex").unwrap();
+            assert_eq!(mutex.kind, "struct");
+            assert_eq!(
+                mutex.signature.as_deref(),
+                Some("pub struct Mutex<T: ?Sized> {\n    /* private fields */\n}")
+            );
+            let methods: Vec<(&str, &str)> = mutex
+                .methods
+                .iter()
+                .map(|m| (m.name.as_str(), m.signature.as_deref().unwrap_or_default()))
+                .collect();
+            assert_eq!(
+                methods,
+                vec![
+                    ("new", "pub const fn new(value: T) -> Self where T: Sized"),
+                    ("lock", "pub async fn lock(&self) -> &mut T"),
+                    (
+                        "try_lock",
+                        "pub fn try_lock(&self) -> Result<&T, TryLockError>"
+                    ),
+                ]
+            );
+            assert_eq!(mutex.methods[1].path, "demo::sync::Mutex::lock");
+            assert_eq!(
+                mutex.methods[1].description.as_deref(),
+                Some("Locks this mutex, waiting until it is available.")
+            );
+
+            let find_impl = |name: &str| {
+                mutex
+                    .trait_impls
+                    .iter()
+                    .find(|i| i.trait_name == name)
+                    .unwrap_or_else(|| panic!("no impl of {}", name))
+            };
+            assert_eq!(mutex.trait_impls[0].kind, "explicit");
+            assert_eq!(
+                find_impl("Default").signature,
+                "impl<T: Default> Default for Mutex<T>"
+            );
+            assert_eq!(find_impl("Default").items, vec!["default"]);
+            let send = find_impl("Send");
+            assert_eq!(send.kind, "auto");
+            assert_eq!(
+                send.signature,
+                "impl<T> Send for Mutex<T> where T: Send + ?Sized"
+            );
+            assert_eq!(find_impl("Borrow<T>").kind, "blanket");
+
+            // Re-exported paths, crate-relative paths and associated items resolve too
+            assert_eq!(
+                krate.item_documentation("Mutex").unwrap().path,
+                "demo::Mutex"
+            );
+            let lock = krate.item_documentation("demo::sync::Mutex::lock").unwrap();
+            assert_eq!(lock.kind, "method");
+            assert_eq!(
+                lock.docs.as_deref(),
+                Some("Locks this mutex, waiting until it is available.")
+            );
+            let poisoned = krate
+                .item_documentation("sync::TryLockError::Poisoned")
+                .unwrap();
+            assert_eq!(poisoned.kind, "variant");
+            assert_eq!(poisoned.signature.as_deref(), Some("Poisoned(String)"));
+
+            let read = krate.item_documentation("demo::AsyncRead").unwrap();
+            let kinds: Vec<(&str, &str)> = read
+                .methods
+                .iter()
+                .map(|m| (m.name.as_str(), m.kind.as_str()))
+                .collect();
+            assert_eq!(
+                kinds,
+                vec![
+                    ("Error", "associated_type"),
+                    ("poll_read", "required_method"),
+                    ("read_exact", "provided_method"),
+                ]
+            );
+
+            assert!(krate.item_documentation("demo::sync::Missing").is_none());
+        }
+    }
+
+    #[test]
+    fn test_search_entries() {
+        for fixture in [CURRENT_FORMAT, LEGACY_FORMAT] {
+            let krate = parse(fixture.as_bytes()).unwrap();
+            let entries = krate.search_entries();
+
+            // Re-exported items are listed once, under the shortest path
+            let mutexes: Vec<&str> = entries
+                .iter()
+                .filter(|entry| entry.item.name == "Mutex")
+                .map(|entry| entry.item.path.as_str())
+                .collect();
+            assert_eq!(mutexes, vec!["demo::Mutex"]);
+
+            let try_lock = entries
+                .iter()
+                .find(|entry| entry.item.path == "demo::Mutex::try_lock")
+                .unwrap();
+            assert_eq!(try_lock.item.kind, "method");
+            assert_eq!(try_lock.inputs, vec!["&Mutex"]);
+            assert_eq!(try_lock.output.as_deref(), Some("Result<&T, TryLockError>"));
+
+            let paths = |query: &str| -> Vec<String> {
+                doc_search::search(&entries, query, 3)
+                    .into_iter()
+                    .map(|item| item.path)
+                    .collect()
+            };
+            assert_eq!(paths("fn(&str) -> Handle"), vec!["demo::spawn"]);
+            assert_eq!(paths("-> Mutex"), vec!["demo::Mutex::new"]);
+            assert_eq!(paths("poll_read")[0], "demo::AsyncRead::poll_read");
+            assert_eq!(paths("sync::TryLock")[0], "demo::sync::TryLockError");
+
+            // The API lists re-exported items under every path
+            let api_items = krate.api_items();
+            let mutexes: Vec<&str> = api_items
+                .iter()
+                .filter(|item| item.name == "Mutex")
+                .map(|item| item.path.as_str())
+                .collect();
+            assert_eq!(mutexes, vec!["demo::Mutex", "demo::sync::Mutex"]);
+            assert!(
+                api_items
+                    .iter()
+                    .any(|item| item.path == "demo::sync::Mutex::try_lock")
+            );
+        }
+    }
+
+    #[test]
+    fn test_parse_current_format() {
+        // docs.rs serves the JSON compressed with zstd
+        let compressed = zstd::encode_all(CURRENT_FORMAT.as_bytes(), 3).unwrap();
+        let krate = parse(&compressed).unwrap();
+        assert_eq!(krate.format_version, 57);
+        assert_demo_crate(&krate);
+    }
+
+    #[test]
+    fn test_non_exhaustive_attr() {
+        let attr = |attrs: Value| non_exhaustive_attr(&serde_json::json!({ "attrs": attrs }));
+        assert_eq!(
+            attr(serde_json::json!(["non_exhaustive"])),
+            "#[non_exhaustive]\n"
+        );
+        assert_eq!(
+            attr(serde_json::json!(["#[non_exhaustive]"])),
+            "#[non_exhaustive]\n"
+        );
+        assert_eq!(
+            attr(serde_json::json!([{ "other": "#[attr = NonExhaustive]" }])),
+            "#[non_exhaustive]\n"
+        );
+        assert_eq!(attr(serde_json::json!(["automatically_derived"])), "");
+    }
+
+    #[test]
+    fn test_decompress_limit() {
+        use std::io::Write;
+
+        let json = CURRENT_FORMAT.as_bytes();
+        let size = json.len() as u64;
+        let zstd = zstd::encode_all(json, 3).unwrap();
+        let mut gzip = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        gzip.write_all(json).unwrap();
+        let gzip = gzip.finish().unwrap();
+
+        for bytes in [zstd.as_slice(), gzip.as_slice(), json] {
+            assert_eq!(decompress(bytes, size).unwrap(), json);
+            let error = decompress(bytes, size - 1).unwrap_err();
+            assert_eq!(error.code(), "invalid_response");
+        }
+    }
+
+    #[test]
+    fn test_parse_legacy_format() {
+        let krate = parse(LEGACY_FORMAT.as_bytes()).unwrap();
+        assert_eq!(krate.format_version, 26);
+        assert_demo_crate(&krate);
+
+        let too_old = LEGACY_FORMAT.replace("\"format_version\":26", "\"format_version\":20");
+        assert_eq!(
+            parse(too_old.as_bytes()).unwrap_err().code(),
+            "invalid_response"
+        );
+    }
+}
@@ -0,0 +1,622 @@
+use ego_tree::NodeRef;
+use reqwest::Url;
+use scraper::{CaseSensitivity, ElementRef, Html, Node, Selector};
+
+/// Elements of rustdoc and docs.rs pages that are navigation or interface, not documentation
+const CHROME_ELEMENTS: &[&str] = &[
+    "script",
+    "style",
+    "noscript",
+    "nav",
+    "button",
+    "img",
+    "svg",
+    "form",
+    "input",
+    "rustdoc-toolbar",
+    "rustdoc-search",
+    "rustdoc-topbar",
+];
+
+/// Classes of the same, including the "Expand description" and "Show N methods"
+/// toggles (`hideme`), whose content is rendered expanded
+const CHROME_CLASSES: &[&str] = &[
+    "sidebar",
+    "out-of-band",
+    "sub-heading",
+    "rustdoc-breadcrumbs",
+    "anchor",
+    "doc-anchor",
+    "src",
+    "rightside",
+    "hideme",
+    "tooltip",
+    "notable-traits",
+    "copy-button",
+    "nav-container",
+];
+
+/// Lists of impl blocks that are rendered as their headers only: the docs of trait
+/// methods repeat the trait's own docs
+const IMPL_HEADER_LISTS: &[&str] = &[
+    "trait-implementations-list",
+    "synthetic-implementations-list",
+    "blanket-implementations-list",
+    "implementors-list",
+    "synthetic-implementors-list",
+];
+
+/// The parts of a rustdoc item or module page, as markdown
+#[derive(Debug, Default)]
+pub struct ItemPage {
+    /// The item declaration, as code
+    pub declaration: Option<String>,
+    pub deprecation: Option<String>,
+    /// The item's own doc comment
+    pub docs: Option<String>,
+    /// Everything below the doc comment: fields, variants, methods, impls, item lists
+    pub sections: Option<String>,
+}
+
+/// Convert a rustdoc page to markdown, without the sidebar and navigation
+///
+/// Relative links are resolved against `page_url`, so intra-doc links keep working.
+pub fn item_page(html: &str, page_url: &str) -> ItemPage {
+    let document = Html::parse_document(html);
+    let base = Url::parse(page_url).ok();
+    let select = |selectors: &str| {
+        let selector = Selector::parse(selectors).expect("page selector is valid");
+        document.select(&selector).next()
+    };
+
+    let Some(content) = select("#main-content").or_else(|| select("main")) else {
+        return ItemPage::default();
+    };
+    let declaration = select("#main-content pre.item-decl, #main-content .item-decl pre");
+    let docs = select("#main-content details.top-doc .docblock")
+        .or_else(|| select("#main-content > .docblock"));
+    let deprecation = select("#main-content > .item-info .stab.deprecated");
+
+    let mut writer = MarkdownWriter::new(base.as_ref());
+    for child in content.children() {
+        let Some(element) = ElementRef::wrap(child) else {
+            continue;
+        };
+        let is_header = element
+            .value()
+            .has_class("main-heading", CaseSensitivity::CaseSensitive)
+            || element
+                .value()
+                .has_class("item-info", CaseSensitivity::CaseSensitive);
+        let is_described = [declaration, docs]
+            .iter()
+            .flatten()
+            .any(|described| described.id() == element.id() || is_ancestor(element, *described));
+        if !is_header && !is_described {
+            writer.node(child);
+        }
+    }
+
+    ItemPage {
+        declaration: declaration
+            .map(|declaration| raw_text(declaration).trim().to_string())
+            .filter(|declaration| !declaration.is_empty()),
+        deprecation: deprecation
+            .map(|deprecation| collapse_whitespace(&raw_text(deprecation)))
+            .filter(|deprecation| !deprecation.is_empty()),
+        docs: docs
+            .map(|docs| to_markdown(docs, base.as_ref()))
+            .filter(|docs| !docs.is_empty()),
+        sections: Some(writer.finish()).filter(|sections| !sections.is_empty()),
+    }
+}
+
+/// Convert an HTML fragment, such as a README rendered by crates.io, to markdown
+pub fn html_to_markdown(html: &str) -> String {
+    let fragment = Html::parse_fragment(html);
+    to_markdown(fragment.root_element(), None)
+}
+
+/// Convert an element and its contents to markdown
+fn to_markdown(element: ElementRef, base: Option<&Url>) -> String {
+    let mut writer = MarkdownWriter::new(base);
+    writer.children(element);
+    writer.finish()
+}
+
+fn is_ancestor(element: ElementRef, descendant: ElementRef) -> bool {
+    descendant
+        .ancestors()
+        .any(|ancestor| ancestor.id() == element.id())
+}
+
+fn is_chrome(element: ElementRef) -> bool {
+    let element = element.value();
+    CHROME_ELEMENTS.contains(&element.name())
+        || element.id() == Some("copy-path")
+        || element
+            .classes()
+            .any(|class| CHROME_CLASSES.contains(&class))
+}
+
+/// Text of an element as written, for code, leaving out interface elements
+fn raw_text(element: ElementRef) -> String {
+    let mut text = String::new();
+    for child in element.children() {
+        match child.value() {
+            Node::Text(t) => text.push_str(t),
+            Node::Element(_) => {
+                if let Some(child) = ElementRef::wrap(child)
+                    && !is_chrome(child)
+                {
+                    // `where` clauses are blocks within code headers and declarations
+                    if child.value().name() == "div" && !text.ends_with(char::is_whitespace) {
+                        text.push('\n');
+                    }
+                    text.push_str(&raw_text(child));
+                }
+            }
+            _ => {}
+        }
+    }
+    text
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Markdown built up block by block, with whitespace collapsed as a browser would
+struct MarkdownWriter<'a> {
+    out: String,
+    base: Option<&'a Url>,
+}
+
+impl<'a> MarkdownWriter<'a> {
+    fn new(base: Option<&'a Url>) -> Self {
+        Self {
+            out: String::new(),
+            base,
+        }
+    }
+
+    /// Render children into a separate writer, for content that gets wrapped or indented
+    fn render(&self, element: ElementRef) -> String {
+        let mut writer = MarkdownWriter::new(self.base);
+        writer.children(element);
+        writer.finish()
+    }
+
+    fn finish(self) -> String {
+        self.out.trim().to_string()
+    }
+
+    fn children(&mut self, element: ElementRef) {
+        for child in element.children() {
+            self.node(child);
+        }
+    }
+
+    fn node(&mut self, node: NodeRef<Node>) {
+        match node.value() {
+            Node::Text(text) => self.text(text),
+            Node::Element(_) => {
+                if let Some(element) = ElementRef::wrap(node)
+                    && !is_chrome(element)
+                {
+                    self.element(element);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn element(&mut self, element: ElementRef) {
+        let name = element.value().name();
+        if element
+            .value()
+            .id()
+            .is_some_and(|id| IMPL_HEADER_LISTS.contains(&id))
+        {
+            self.impl_headers(element);
+            return;
+        }
+
+        match name {
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                let level = name[1..].parse().unwrap_or(1);
+                let text = collapse_whitespace(&raw_text(element));
+                let is_code = element
+                    .value()
+                    .has_class("code-header", CaseSensitivity::CaseSensitive);
+                if !text.is_empty() {
+                    self.blank_line();
+                    self.out.push_str(&"#".repeat(level));
+                    self.out.push(' ');
+                    self.out
+                        .push_str(&if is_code { code_span(&text) } else { text });
+                    self.blank_line();
+                }
+            }
+            "pre" => self.code_block(element),
+            "code" => {
+                let text = collapse_whitespace(&raw_text(element));
+                if !text.is_empty() {
+                    self.inline(&code_span(&text));
+                }
+            }
+            "a" => self.link(element),
+            "strong" | "b" => self.wrapped(element, "**"),
+            "em" | "i" => self.wrapped(element, "*"),
+            "del" | "s" => self.wrapped(element, "~~"),
+            "br" => {
+                self.trim_trailing_spaces();
+                self.out.push('\n');
+            }
+            "hr" => {
+                self.blank_line();
+                self.out.push_str("---");
+                self.blank_line();
+            }
+            "ul" | "ol" => self.list(element, name == "ol"),
+            "dl" => self.definitions(element),
+            "table" => self.table(element),
+            "blockquote" => {
+                let quote = self.render(element);
+                self.blank_line();
+                let quoted: Vec<String> = quote
+                    .lines()
+                    .map(|line| format!("> {}", line).trim_end().to_string())
+                    .collect();
+                self.out.push_str(&quoted.join("\n"));
+                self.blank_line();
+            }
+            // Badges such as "Deprecated" or the features an item needs
+            "span"
+                if element
+                    .value()
+                    .has_class("stab", CaseSensitivity::CaseSensitive) =>
+            {
+                let badge = collapse_whitespace(&self.render(element));
+                if !badge.is_empty() {
+                    self.trim_trailing_spaces();
+                    self.inline(&format!(" ({})", badge));
+                }
+            }
+            "p" | "div" | "section" | "details" | "summary" | "article" | "header" | "footer"
+            | "main" => {
+                self.blank_line();
+                self.children(element);
+                self.blank_line();
+            }
+            _ => self.children(element),
+        }
+    }
+
+    /// Append text with runs of whitespace collapsed to one space, and none at line starts
+    fn text(&mut self, text: &str) {
+        let at_line_start = self.out.is_empty() || self.out.ends_with(['\n', ' ']);
+        if text.starts_with(char::is_whitespace) && !at_line_start {
+            self.out.push(' ');
+        }
+        let words = collapse_whitespace(text);
+        if words.is_empty() {
+            return;
+        }
+        self.out.push_str(&words);
+        if text.ends_with(char::is_whitespace) {
+            self.out.push(' ');
+        }
+    }
+
+    fn inline(&mut self, markdown: &str) {
+        self.out.push_str(markdown);
+    }
+
+    fn wrapped(&mut self, element: ElementRef, marker: &str) {
+        let text = self.render(element);
+        if !text.is_empty() {
+            self.inline(&format!("{}{}{}", marker, text, marker));
+        }
+    }
+
+    /// Links keep their target, resolved to an absolute URL; links within the page
+    /// and links without text are reduced to their text
+    fn link(&mut self, element: ElementRef) {
+        let text = self.render(element);
+        if text.is_empty() {
+            return;
+        }
+        let target = element
+            .value()
+            .attr("href")
+            .filter(|href| !href.starts_with('#'))
+            .map(
+                |href| match self.base.and_then(|base| base.join(href).ok()) {
+                    Some(url) => url.to_string(),
+                    None => href.to_string(),
+                },
+            );
+        match target {
+            Some(target) => self.inline(&format!("[{}]({})", text, target)),
+            None => self.inline(&text),
+        }
+    }
+
+    fn code_block(&mut self, element: ElementRef) {
+        let code = raw_text(element);
+        let code = code.trim_matches('\n');
+        if code.trim().is_empty() {
+            return;
+        }
+        let is_rust = element
+            .value()
+            .has_class("rust", CaseSensitivity::CaseSensitive);
+        let language = if is_rust {
+            "rust".to_string()
+        } else {
+            element
+                .descendants()
+                .filter_map(ElementRef::wrap)
+                .flat_map(|descendant| descendant.value().classes())
+                .find_map(|class| class.strip_prefix("language-"))
+                .unwrap_or_default()
+                .to_string()
+        };
+        let fence = if code.contains("```") { "````" } else { "```" };
+
+        self.blank_line();
+        self.out
+            .push_str(&format!("{}{}\n{}\n{}", fence, language, code, fence));
+        self.blank_line();
+    }
+
+    fn list(&mut self, list: ElementRef, ordered: bool) {
+        // Module pages of older rustdoc list items as `item-name` and `desc` pairs
+        let is_item_table = list
+            .value()
+            .has_class("item-table", CaseSensitivity::CaseSensitive);
+        let items: Vec<String> = list
+            .children()
+            .filter_map(ElementRef::wrap)
+            .filter(|item| item.value().name() == "li")
+            .enumerate()
+            .map(|(i, item)| {
+                let marker = if ordered {
+                    format!("{}. ", i + 1)
+                } else {
+                    "- ".to_string()
+                };
+                let content = if is_item_table {
+                    item.children()
+                        .filter_map(ElementRef::wrap)
+                        .map(|cell| collapse_whitespace(&self.render(cell)))
+                        .filter(|cell| !cell.is_empty())
+                        .collect::<Vec<_>>()
+                        .join(": ")
+                } else {
+                    self.render(item)
+                };
+                let indent = " ".repeat(marker.len());
+                let mut lines = content.lines();
+                let first = lines.next().unwrap_or_default();
+                let rest: Vec<String> = lines
+                    .map(|line| {
+                        if line.is_empty() {
+                            String::new()
+                        } else {
+                            format!("{}{}", indent, line)
+                        }
+                    })
+                    .collect();
+                std::iter::once(format!("{}{}", marker, first))
+                    .chain(rest)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+            .collect();
+        if items.is_empty() {
+            return;
+        }
+        self.blank_line();
+        self.out.push_str(&items.join("\n"));
+        self.blank_line();
+    }
+
+    /// Definition lists, which newer rustdoc uses for the items of a module, as
+    /// `- term: description`
+    fn definitions(&mut self, list: ElementRef) {
+        let mut entries: Vec<String> = Vec::new();
+        for child in list.children().filter_map(ElementRef::wrap) {
+            let text = collapse_whitespace(&self.render(child));
+            match child.value().name() {
+                "dt" => entries.push(format!("- {}", text)),
+                "dd" if !text.is_empty() => match entries.last_mut() {
+                    Some(entry) => {
+                        entry.push_str(": ");
+                        entry.push_str(&text);
+                    }
+                    None => entries.push(format!("- {}", text)),
+                },
+                _ => {}
+            }
+        }
+        if entries.is_empty() {
+            return;
+        }
+        self.blank_line();
+        self.out.push_str(&entries.join("\n"));
+        self.blank_line();
+    }
+
+    fn table(&mut self, table: ElementRef) {
+        let rows = Selector::parse("tr").expect("row selector is valid");
+        let rows: Vec<Vec<String>> = table
+            .select(&rows)
+            .map(|row| {
+                row.children()
+                    .filter_map(ElementRef::wrap)
+                    .filter(|cell| matches!(cell.value().name(), "th" | "td"))
+                    .map(|cell| collapse_whitespace(&self.render(cell)).replace('|', "\\|"))
+                    .collect()
+            })
+            .filter(|cells: &Vec<String>| !cells.is_empty())
+            .collect();
+        let Some(columns) = rows.iter().map(Vec::len).max() else {
+            return;
+        };
+
+        let line = |cells: &[String]| {
+            let mut cells = cells.to_vec();
+            cells.resize(columns, String::new());
+            format!("| {} |", cells.join(" | "))
+        };
+        let mut lines = vec![line(&rows[0]), line(&vec!["---".to_string(); columns])];
+        lines.extend(rows[1..].iter().map(|row| line(row)));
+
+        self.blank_line();
+        self.out.push_str(&lines.join("\n"));
+        self.blank_line();
+    }
+
+    /// Trait implementations, listed as their `impl` lines
+    fn impl_headers(&mut self, list: ElementRef) {
+        let headers = Selector::parse("h3.code-header").expect("impl header selector is valid");
+        let impls: Vec<String> = list
+            .select(&headers)
+            .map(|header| format!("- {}", code_span(&collapse_whitespace(&raw_text(header)))))
+            .collect();
+        if impls.is_empty() {
+            return;
+        }
+        self.blank_line();
+        self.out.push_str(&impls.join("\n"));
+        self.blank_line();
+    }
+
+    fn trim_trailing_spaces(&mut self) {
+        let trimmed = self.out.trim_end_matches(' ').len();
+        self.out.truncate(trimmed);
+    }
+
+    /// End the current block, leaving exactly one empty line
+    fn blank_line(&mut self) {
+        self.trim_trailing_spaces();
+        if self.out.is_empty() {
+            return;
+        }
+        while !self.out.ends_with("\n\n") {
+            self.out.push('\n');
+        }
+    }
+}
+
+/// Inline code, with a longer delimiter for code that contains backticks
+fn code_span(code: &str) -> String {
+    if code.contains('`') {
+        format!("`` {} ``", code)
+    } else {
+        format!("`{}`", code)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STRUCT_PAGE: &str = include_str!("../tests/fixtures/rustdoc/demo-struct.html");
+    const PAGE_URL: &str = "https://docs.rs/demo/1.2.0/demo/sync/struct.Mutex.html";
+
+    #[test]
+    fn test_item_page() {
+        let page = item_page(STRUCT_PAGE, PAGE_URL);
+        assert_eq!(
+            page.declaration.as_deref(),
+            Some("pub struct Mutex<T: ?Sized> { /* private fields */ }")
+        );
+        assert_eq!(page.deprecation, None);
+
+        // Intra-doc links point at docs.rs, code blocks and tables survive
+        let docs = page.docs.unwrap();
+        assert!(docs.starts_with("An asynchronous `Mutex`-like type.\n\nLocking is fair"));
+        assert!(docs.contains(
+            "[`TryLockError`](https://docs.rs/demo/1.2.0/demo/sync/enum.TryLockError.html)"
+        ));
+        assert!(docs.contains(
+            "## Examples\n\n```rust\nuse demo::sync::Mutex;\n\nlet mutex = Mutex::new(1);\n"
+        ));
+        assert!(docs.contains("| Method | Waits |\n| --- | --- |\n| `lock` | yes |"));
+        assert!(!docs.contains("Expand description"));
+
+        // Methods keep their docs, trait impls are reduced to their headers
+        let sections = page.sections.unwrap();
+        assert!(sections.starts_with("## Implementations\n\n### `impl<T> Mutex<T>`"));
+        assert!(sections.contains(
+            "#### `pub const fn new(value: T) -> Self where T: Sized,`\n\n\
+             Creates a new lock in an unlocked state."
+        ));
+        assert!(sections.contains(
+            "## Trait Implementations\n\n\
+             - `impl<T: ?Sized + Debug> Debug for Mutex<T>`\n\
+             - `impl<T: Default> Default for Mutex<T>`\n\n"
+        ));
+        assert!(!sections.contains("Formats the value"));
+
+        // Navigation, sidebar and source links are left out
+        for chrome in ["Copy item path", "Source", "Sections", "§"] {
+            assert!(
+                !docs.contains(chrome) && !sections.contains(chrome),
+                "{}",
+                chrome
+            );
+        }
+    }
+
+    #[test]
+    fn test_module_page() {
+        let page = item_page(
+            include_str!("../tests/fixtures/rustdoc/demo-dl.html"),
+            "https://docs.rs/demo/1.0.0/demo/index.html",
+        );
+        assert_eq!(page.declaration, None);
+        assert!(
+            page.docs
+                .unwrap()
+                .starts_with("A demo crate for asynchronous applications.")
+        );
+
+        // Item tables become lists of links with their summaries and badges
+        let sections = page.sections.unwrap();
+        assert!(sections.contains(
+            "## Modules\n\n\
+             - [io](https://docs.rs/demo/1.0.0/demo/io/index.html): Asynchronous I/O `helpers`.\n\
+             - [sync](https://docs.rs/demo/1.0.0/demo/sync/index.html): Synchronization primitives."
+        ));
+        assert!(sections.contains(
+            "- [Runtime](https://docs.rs/demo/1.0.0/demo/runtime/struct.Runtime.html) (Deprecated): \
+             The runtime that drives futures to completion.\n\
+             - [Handle](https://docs.rs/demo/1.0.0/demo/struct.Handle.html) (`rt`)\n"
+        ));
+    }
+
+    #[test]
+    fn test_html_to_markdown() {
+        let readme = r##"<h1><a href="#demo" id="user-content-demo" class="anchor"></a>demo</h1>
+<p>An <em>async</em> runtime. See the <a href="https://docs.rs/demo">docs</a>.</p>
+<pre><code class="language-toml">[dependencies]
+demo = "1"
+</code></pre>
+<ul>
+<li>Fast</li>
+<li>Small<ul><li>really</li></ul></li>
+</ul>"##;
+        assert_eq!(
+            html_to_markdown(readme),
+            "# demo\n\n\
+             An *async* runtime. See the [docs](https://docs.rs/demo).\n\n\
+             ```toml\n[dependencies]\ndemo = \"1\"\n```\n\n\
+             - Fast\n\
+             - Small\n\n  - really"
+        );
+    }
+}
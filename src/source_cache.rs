@@ -0,0 +1,415 @@
+use glob::{MatchOptions, Pattern};
+use regex::Regex;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use tracing::{debug, info};
+
+use crate::crate_archive;
+use crate::error::{Error, Result};
+use crate::local_registry::{self, crate_dir_name};
+use crate::types::{CrateFile, CrateFileContent, SourceMatch};
+
+/// Files larger than this are skipped by `grep`, they are usually generated data
+const MAX_GREP_FILE_SIZE: u64 = 4 * 1024 * 1024;
+
+/// Matched and context lines are cut off after this many characters
+const MAX_LINE_LENGTH: usize = 300;
+
+/// Published crate sources, unpacked from verified `.crate` archives into one
+/// directory per crate version (`<root>/<name>-<version>`)
+pub struct SourceCache {
+    root: PathBuf,
+}
+
+impl SourceCache {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// `crates-mcp/src` in the user's cache directory, or in the temp directory
+    pub fn default_path() -> PathBuf {
+        std::env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+            .or_else(|| std::env::var_os("LOCALAPPDATA").map(PathBuf::from))
+            .unwrap_or_else(std::env::temp_dir)
+            .join("crates-mcp/src")
+    }
+
+    /// Directory of a crate version, if it has been unpacked before
+    pub fn dir(&self, name: &str, version: &str) -> Option<PathBuf> {
+        local_registry::child_path(&self.root, &crate_dir_name(name, version)?)
+            .filter(|dir| dir.is_dir())
+    }
+
+    /// Unpack the `.crate` archive of a crate version, which must already be verified
+    ///
+    /// The archive is unpacked next to its final directory and moved into place,
+    /// so a directory in the cache is always complete.
+    pub fn unpack(&self, name: &str, version: &str, archive: &[u8]) -> Result<PathBuf> {
+        let dir_name = crate_dir_name(name, version).ok_or_else(|| {
+            Error::InvalidInput(format!("Invalid crate version '{}' {}", name, version))
+        })?;
+        let dir = self.root.join(&dir_name);
+        let partial = self
+            .root
+            .join(format!(".{}.{}", dir_name, std::process::id()));
+        debug!(
+            "Unpacking crate '{}' {} into: {}",
+            name,
+            version,
+            dir.display()
+        );
+
+        fs::create_dir_all(&self.root).map_err(|e| cache_error(&self.root, e))?;
+        let _ = fs::remove_dir_all(&partial);
+        crate_archive::unpack(archive, &partial).inspect_err(|_| {
+            let _ = fs::remove_dir_all(&partial);
+        })?;
+
+        if let Err(e) = fs::rename(&partial, &dir) {
+            let _ = fs::remove_dir_all(&partial);
+            // Another request may have unpacked the same version in the meantime
+            if !dir.is_dir() {
+                return Err(cache_error(&dir, e));
+            }
+        }
+        info!(
+            "Unpacked crate '{}' {} into the source cache",
+            name, version
+        );
+        Ok(dir)
+    }
+}
+
+/// Files below a crate source directory, with `/`-separated paths relative to it, sorted
+///
+/// Symbolic links are not followed, and the marker file cargo leaves in the
+/// sources it unpacks is left out.
+pub fn list_files(dir: &Path) -> Result<Vec<CrateFile>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in fs::read_dir(&current).map_err(|e| cache_error(&current, e))? {
+            let entry = entry.map_err(|e| cache_error(&current, e))?;
+            let file_type = entry.file_type().map_err(|e| cache_error(&current, e))?;
+            let path = entry.path();
+            if file_type.is_dir() {
+                pending.push(path);
+            } else if file_type.is_file() && !(current == dir && entry.file_name() == ".cargo-ok") {
+                let size = entry.metadata().map(|m| m.len()).unwrap_or_default();
+                let relative = path.strip_prefix(dir).unwrap_or(&path);
+                files.push(CrateFile {
+                    path: relative.to_string_lossy().replace('\\', "/"),
+                    size,
+                });
+            }
+        }
+    }
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(files)
+}
+
+/// Read a file of a crate source directory, optionally only lines `start_line..=end_line`
+/// (1-based), keeping at most `max_bytes` of the text
+pub fn read_file(
+    dir: &Path,
+    path: &str,
+    start_line: Option<usize>,
+    end_line: Option<usize>,
+    max_bytes: usize,
+) -> Result<CrateFileContent> {
+    let relative = Path::new(path.trim_start_matches("./"));
+    if !relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(Error::InvalidInput(format!(
+            "File path '{}' must be relative to the crate root",
+            path
+        )));
+    }
+
+    let file = dir.join(relative);
+    let bytes = match fs::read(&file) {
+        Ok(bytes) => bytes,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(Error::NotFound(format!("File '{}' not found", path)));
+        }
+        Err(e) => return Err(cache_error(&file, e)),
+    };
+    let text = String::from_utf8_lossy(&bytes);
+
+    let (mut content, start_line, end_line) = if start_line.is_some() || end_line.is_some() {
+        let start = start_line.unwrap_or(1).max(1);
+        let end = end_line.unwrap_or(usize::MAX);
+        if end < start {
+            return Err(Error::InvalidInput(format!(
+                "end_line {} is before start_line {}",
+                end, start
+            )));
+        }
+        let line_count = text.split_inclusive('\n').count();
+        if start > line_count {
+            return Err(Error::InvalidInput(format!(
+                "start_line {} is past the end of the file, which has {} lines",
+                start, line_count
+            )));
+        }
+        let lines: Vec<&str> = text
+            .split_inclusive('\n')
+            .skip(start - 1)
+            .take(end - start + 1)
+            .collect();
+        let end = start + lines.len().saturating_sub(1);
+        (lines.concat(), Some(start), Some(end))
+    } else {
+        (text.into_owned(), None, None)
+    };
+
+    let truncated = content.len() > max_bytes;
+    if truncated {
+        let mut cut = max_bytes;
+        while !content.is_char_boundary(cut) {
+            cut -= 1;
+        }
+        content.truncate(cut);
+    }
+
+    Ok(CrateFileContent {
+        path: relative.to_string_lossy().replace('\\', "/"),
+        size: bytes.len() as u64,
+        content,
+        truncated,
+        start_line,
+        end_line,
+    })
+}
+
+/// Options for searching a crate's source
+#[derive(Debug, Clone)]
+pub struct GrepOptions {
+    pub case_insensitive: bool,
+    /// Globs of the files to search, all files when empty
+    pub include: Vec<String>,
+    /// Globs of the files to skip
+    pub exclude: Vec<String>,
+    /// Lines shown before and after each match
+    pub context: usize,
+    /// Matches returned at most
+    pub max_results: usize,
+}
+
+impl Default for GrepOptions {
+    fn default() -> Self {
+        Self {
+            case_insensitive: false,
+            include: Vec::new(),
+            exclude: Vec::new(),
+            context: 2,
+            max_results: 50,
+        }
+    }
+}
+
+/// Include and exclude globs selecting the files `grep` searches
+///
+/// A glob with a `/` is matched against the whole path (`src/**/*.rs`), one
+/// without against the file name (`*.rs`).
+pub struct FileFilter {
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl FileFilter {
+    pub fn new(include: &[String], exclude: &[String]) -> Result<Self> {
+        let parse = |globs: &[String]| {
+            globs
+                .iter()
+                .map(|glob| {
+                    Pattern::new(glob)
+                        .map_err(|e| Error::InvalidInput(format!("Invalid glob '{}': {}", glob, e)))
+                })
+                .collect::<Result<Vec<_>>>()
+        };
+        Ok(Self {
+            include: parse(include)?,
+            exclude: parse(exclude)?,
+        })
+    }
+
+    /// Whether a file matches an include glob, if there are any, and no exclude glob
+    pub fn matches(&self, path: &str) -> bool {
+        let options = MatchOptions {
+            require_literal_separator: true,
+            ..MatchOptions::new()
+        };
+        let file_name = path.rsplit('/').next().unwrap_or(path);
+        let matches = |pattern: &Pattern| {
+            if pattern.as_str().contains('/') {
+                pattern.matches_with(path, options)
+            } else {
+                pattern.matches_with(file_name, options)
+            }
+        };
+        (self.include.is_empty() || self.include.iter().any(matches))
+            && !self.exclude.iter().any(matches)
+    }
+}
+
+/// Search files of a crate source directory line by line for a regex, with
+/// `context` lines around each match
+///
+/// Returns at most `max_matches` matches, in file and line order, and whether
+/// more were left out. Binary and very large files are skipped.
+pub fn grep(
+    dir: &Path,
+    files: &[CrateFile],
+    regex: &Regex,
+    context: usize,
+    max_matches: usize,
+) -> Result<(Vec<SourceMatch>, bool)> {
+    let mut matches = Vec::new();
+    for file in files {
+        if file.size > MAX_GREP_FILE_SIZE {
+            debug!("Skipping {} in grep, it has {} bytes", file.path, file.size);
+            continue;
+        }
+        let path = dir.join(&file.path);
+        let bytes = fs::read(&path).map_err(|e| cache_error(&path, e))?;
+        if bytes.contains(&0) {
+            continue;
+        }
+
+        let text = String::from_utf8_lossy(&bytes);
+        let lines: Vec<&str> = text.lines().collect();
+        for (index, line) in lines.iter().enumerate() {
+            if !regex.is_match(line) {
+                continue;
+            }
+            if matches.len() == max_matches {
+                return Ok((matches, true));
+            }
+            let context_lines =
+                |range: &[&str]| range.iter().map(|line| shorten(line)).collect::<Vec<_>>();
+            matches.push(SourceMatch {
+                path: file.path.clone(),
+                line: index + 1,
+                content: shorten(line),
+                before: context_lines(&lines[index.saturating_sub(context)..index]),
+                after: context_lines(&lines[index + 1..(index + 1 + context).min(lines.len())]),
+            });
+        }
+    }
+    Ok((matches, false))
+}
+
+/// Cut a line off after `MAX_LINE_LENGTH` characters, e.g. in minified files
+fn shorten(line: &str) -> String {
+    match line.char_indices().nth(MAX_LINE_LENGTH) {
+        Some((end, _)) => format!("{}…", &line[..end]),
+        None => line.to_string(),
+    }
+}
+
+fn cache_error(path: &Path, e: std::io::Error) -> Error {
+    Error::InvalidResponse(format!("Failed to access {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_list_and_read_files() -> Result<()> {
+        let root = std::env::temp_dir().join(format!("crates-mcp-sources-{}", std::process::id()));
+        let dir = root.join("demo-1.0.0");
+        fs::create_dir_all(dir.join("src/sync")).unwrap();
+        fs::write(dir.join("Cargo.toml"), "[package]\nname = \"demo\"\n").unwrap();
+        fs::write(dir.join(".cargo-ok"), "{\"v\":1}").unwrap();
+        fs::write(dir.join("src/lib.rs"), "pub mod sync;\n\npub fn run() {}\n").unwrap();
+        fs::write(dir.join("src/sync/mod.rs"), "pub struct Mutex;\n").unwrap();
+
+        let cache = SourceCache::new(&root);
+        assert_eq!(cache.dir("demo", "1.0.0"), Some(dir.clone()));
+        assert_eq!(cache.dir("demo", "2.0.0"), None);
+        // Versions are joined onto the cache root only when they are semver
+        assert_eq!(cache.dir("demo", "1.0.0/../demo-1.0.0"), None);
+
+        let files = list_files(&dir)?;
+        let paths: Vec<&str> = files.iter().map(|file| file.path.as_str()).collect();
+        assert_eq!(paths, vec!["Cargo.toml", "src/lib.rs", "src/sync/mod.rs"]);
+        assert_eq!(files[2].size, 18);
+
+        let whole = read_file(&dir, "src/lib.rs", None, None, 1024)?;
+        assert_eq!(whole.content, "pub mod sync;\n\npub fn run() {}\n");
+        assert!(!whole.truncated);
+
+        // Line ranges are clamped to the end of the file
+        let lines = read_file(&dir, "./src/lib.rs", Some(3), Some(10), 1024)?;
+        assert_eq!(lines.content, "pub fn run() {}\n");
+        assert_eq!((lines.start_line, lines.end_line), (Some(3), Some(3)));
+
+        let cut = read_file(&dir, "src/lib.rs", None, None, 7)?;
+        assert_eq!(cut.content, "pub mod");
+        assert!(cut.truncated);
+
+        assert!(matches!(
+            read_file(&dir, "src/lib.rs", Some(4), None, 1024),
+            Err(Error::InvalidInput(_))
+        ));
+        assert!(matches!(
+            read_file(&dir, "src/missing.rs", None, None, 1024),
+            Err(Error::NotFound(_))
+        ));
+        assert!(matches!(
+            read_file(&dir, "../demo-1.0.0/Cargo.toml", None, None, 1024),
+            Err(Error::InvalidInput(_))
+        ));
+
+        fs::remove_dir_all(&root).unwrap();
+        Ok(())
+    }
+
+    #[test]
+    fn test_grep() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("crates-mcp-grep-{}", std::process::id()));
+        fs::create_dir_all(dir.join("src/sync")).unwrap();
+        fs::create_dir_all(dir.join("tests")).unwrap();
+        fs::write(
+            dir.join("src/lib.rs"),
+            "pub mod sync;\n\nuse std::process::Command;\n\npub fn run() {\n    Command::new(\"ls\");\n}\n",
+        )
+        .unwrap();
+        fs::write(dir.join("src/sync/mod.rs"), "// no commands here\n").unwrap();
+        fs::write(dir.join("tests/run.rs"), "use std::process::Command;\n").unwrap();
+        fs::write(dir.join("logo.png"), b"Command\0\x89PNG").unwrap();
+
+        let filter = FileFilter::new(&["*.rs".to_string()], &["tests/**".to_string()])?;
+        let files: Vec<CrateFile> = list_files(&dir)?
+            .into_iter()
+            .filter(|file| filter.matches(&file.path))
+            .collect();
+        assert_eq!(files.len(), 2);
+
+        let regex = Regex::new(r"\bCommand\b").unwrap();
+        let (matches, truncated) = grep(&dir, &files, &regex, 1, 10)?;
+        assert!(!truncated);
+        assert_eq!(matches.len(), 2);
+        assert_eq!(matches[0].path, "src/lib.rs");
+        assert_eq!(matches[0].line, 3);
+        assert_eq!(matches[0].before, vec![""]);
+        assert_eq!(matches[0].after, vec![""]);
+        assert_eq!(matches[1].content, "    Command::new(\"ls\");");
+        assert_eq!(matches[1].after, vec!["}"]);
+
+        // Binary files are skipped and the cap is reported
+        let (matches, truncated) = grep(&dir, &list_files(&dir)?, &regex, 0, 2)?;
+        assert_eq!(matches.len(), 2);
+        assert!(truncated);
+
+        assert!(FileFilter::new(&["src/[".to_string()], &[]).is_err());
+        fs::remove_dir_all(&dir).unwrap();
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Comprehensive information about a Rust crate
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,22 +50,497 @@ pub struct CrateDependency {
     pub kind: String,
 }
 
+/// A crate version that depends on the queried crate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReverseDependency {
+    pub name: String,
+    pub version: String,
+    pub version_req: String,
+    pub downloads: u64,
+    pub optional: bool,
+    pub kind: String,
+}
+
+/// One page of reverse dependencies from crates.io
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReverseDependencies {
+    pub name: String,
+    /// All dependents on crates.io, whether they match a version filter or not
+    pub total: u64,
+    pub page: u32,
+    pub per_page: u32,
+    /// Dependents on this page whose requirement admits the requested version, when
+    /// filtering by version
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub matching: Option<usize>,
+    pub dependents: Vec<ReverseDependency>,
+}
+
+/// A crate version in a resolved dependency tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyNode {
+    pub id: String,
+    pub name: String,
+    pub version: String,
+    pub features: Vec<String>,
+    pub depth: usize,
+    pub expanded: bool,
+}
+
+/// A dependency edge between two nodes of a resolved dependency tree
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyEdge {
+    pub from: String,
+    pub to: String,
+    pub version_req: String,
+    pub kind: String,
+    pub optional: bool,
+    pub target: Option<String>,
+}
+
+/// A dependency that could not be resolved to a concrete version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UnresolvedDependency {
+    pub from: String,
+    pub name: String,
+    pub version_req: String,
+    pub reason: String,
+}
+
+/// Transitive dependencies of a crate version, resolved against the index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyTree {
+    pub root: String,
+    pub target: Option<String>,
+    pub max_depth: usize,
+    pub nodes: Vec<DependencyNode>,
+    pub edges: Vec<DependencyEdge>,
+    pub unresolved: Vec<UnresolvedDependency>,
+}
+
+/// A dependency of a local manifest, checked against the index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestDependencyReport {
+    pub name: String,
+    pub package: String,
+    pub kind: String,
+    pub target: Option<String>,
+    pub optional: bool,
+    pub source: String,
+    pub version_req: Option<String>,
+    pub latest_version: Option<String>,
+    pub resolved_version: Option<String>,
+    pub requirement_admits_latest: Option<bool>,
+    pub yanked: Option<bool>,
+    pub license: Option<String>,
+    pub note: Option<String>,
+}
+
+/// A package of a local manifest or workspace and its dependencies
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestPackageReport {
+    pub name: String,
+    pub version: Option<String>,
+    pub manifest_path: String,
+    pub dependencies: Vec<ManifestDependencyReport>,
+}
+
+/// Analysis of a local Cargo.toml or workspace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestAnalysis {
+    pub manifest: String,
+    pub packages: Vec<ManifestPackageReport>,
+}
+
+/// A crates.io package locked in a Cargo.lock, checked against the index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockedPackageReport {
+    pub name: String,
+    pub version: String,
+    pub yanked: Option<bool>,
+    pub newest_compatible: Option<String>,
+    pub compatible_versions_behind: Option<usize>,
+    pub newest_version: Option<String>,
+    pub note: Option<String>,
+}
+
+/// A crate locked at several semver-incompatible versions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateCrate {
+    pub name: String,
+    pub versions: Vec<String>,
+}
+
+/// Audit of a Cargo.lock
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LockfileAudit {
+    pub lockfile: String,
+    pub format_version: Option<u32>,
+    pub total_packages: usize,
+    pub crates_io_packages: usize,
+    pub yanked: Vec<String>,
+    pub duplicates: Vec<DuplicateCrate>,
+    pub packages: Vec<LockedPackageReport>,
+}
+
+/// A dependency compared against the newest releases of the crate it names
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutdatedDependency {
+    pub name: String,
+    pub package: String,
+    pub kind: String,
+    pub target: Option<String>,
+    pub optional: bool,
+    pub version_req: String,
+    pub newest_matching: Option<String>,
+    pub newest_version: Option<String>,
+    pub outdated: bool,
+    pub breaking_upgrade: bool,
+    pub note: Option<String>,
+}
+
+/// Outdated dependencies of one package
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutdatedPackage {
+    pub name: String,
+    pub version: Option<String>,
+    pub dependencies: Vec<OutdatedDependency>,
+}
+
+/// Outdated dependency report for a published crate version or a local manifest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutdatedReport {
+    pub source: String,
+    pub packages: Vec<OutdatedPackage>,
+}
+
+/// A RustSec advisory affecting a crate version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdvisoryMatch {
+    pub id: String,
+    pub package: String,
+    pub version: String,
+    pub title: Option<String>,
+    pub date: Option<String>,
+    pub severity: Option<String>,
+    pub cvss_score: Option<f64>,
+    pub cvss: Option<String>,
+    pub informational: Option<String>,
+    pub categories: Vec<String>,
+    pub aliases: Vec<String>,
+    pub patched_versions: Vec<String>,
+    pub url: String,
+    pub reference_url: Option<String>,
+    pub path: Vec<String>,
+}
+
+/// Advisories found for a crate, a dependency tree or a Cargo.lock
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdvisoryReport {
+    pub database: String,
+    pub checked_packages: usize,
+    pub vulnerabilities: Vec<AdvisoryMatch>,
+    pub warnings: Vec<AdvisoryMatch>,
+}
+
+/// A crate whose license violates the policy or could not be determined
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LicenseFinding {
+    pub name: String,
+    pub version: String,
+    pub license: Option<String>,
+    pub reason: String,
+    pub path: Vec<String>,
+}
+
+/// License compliance of a dependency tree or a Cargo.lock
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LicenseReport {
+    pub source: String,
+    pub allow: Vec<String>,
+    pub deny: Vec<String>,
+    pub checked_packages: usize,
+    pub licenses: BTreeMap<String, usize>,
+    pub offending: Vec<LicenseFinding>,
+    pub unknown: Vec<LicenseFinding>,
+}
+
+/// Published versions of a crate that satisfy a version requirement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionRequirementMatch {
+    pub name: String,
+    pub requirement: String,
+    pub range: String,
+    pub matching_versions: Vec<String>,
+    pub yanked_matches: Vec<String>,
+    pub highest_match: Option<String>,
+    pub prereleases_excluded: bool,
+    pub excluded_prereleases: Vec<String>,
+}
+
+/// A node of an exported dependency graph
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphNode {
+    pub id: String,
+    pub label: String,
+}
+
+/// A dependency graph as nodes and labelled edges, for export
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DependencyGraph {
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<DependencyEdge>,
+}
+
+/// An entry of an activated feature, e.g. `dep:serde` or `serde/derive`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureEdge {
+    pub from: String,
+    pub to: String,
+    pub kind: String,
+    pub dependency: Option<String>,
+    pub fired: bool,
+}
+
+/// Features activated for a crate version and the edges that activated them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureResolution {
+    pub name: String,
+    pub version: String,
+    pub requested: Vec<String>,
+    pub default_features: bool,
+    pub features: Vec<String>,
+    pub enabled_dependencies: Vec<String>,
+    pub dependency_features: BTreeMap<String, Vec<String>>,
+    pub edges: Vec<FeatureEdge>,
+    pub unknown_features: Vec<String>,
+    pub feature_table: BTreeMap<String, Vec<String>>,
+}
+
+/// A file of a published crate, relative to the crate root
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CrateFile {
+    pub path: String,
+    /// Size in bytes
+    pub size: u64,
+}
+
+/// The content of a file of a published crate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrateFileContent {
+    pub path: String,
+    pub size: u64,
+    pub content: String,
+    /// Whether the content was cut off at the size limit
+    pub truncated: bool,
+    /// First line returned, when a line range was requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<usize>,
+    /// Last line returned, when a line range was requested
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
+}
+
+/// Files of the published source of a crate version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrateFiles {
+    pub name: String,
+    pub version: String,
+    /// Where the sources came from, `local_registry`, `source_cache` or `crates_io`
+    pub source: String,
+    pub files: Vec<CrateFile>,
+}
+
+/// A file of the published source of a crate version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrateSourceFile {
+    pub name: String,
+    pub version: String,
+    /// Where the sources came from, `local_registry`, `source_cache` or `crates_io`
+    pub source: String,
+    pub file: CrateFileContent,
+}
+
+/// A line of a crate's published source matching a search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceMatch {
+    pub path: String,
+    /// Line number, starting at 1
+    pub line: usize,
+    pub content: String,
+    /// Context lines before the match
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub before: Vec<String>,
+    /// Context lines after the match
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub after: Vec<String>,
+}
+
+/// Lines of a crate version's published source matching a regex
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SourceSearch {
+    pub name: String,
+    pub version: String,
+    /// Where the sources came from, `local_registry`, `source_cache` or `crates_io`
+    pub source: String,
+    pub pattern: String,
+    /// Number of files selected by the glob filters
+    pub searched_files: usize,
+    pub matches: Vec<SourceMatch>,
+    /// Whether matches beyond the result cap were left out
+    pub truncated: bool,
+}
+
+/// The `examples/` directory of a published crate version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CrateExamples {
+    pub name: String,
+    pub version: String,
+    /// Where the sources came from, `local_registry`, `source_cache` or `crates_io`
+    pub source: String,
+    pub examples: Vec<CrateFile>,
+    /// The requested example file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<CrateFileContent>,
+}
+
 /// Documentation information for a crate from docs.rs
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CrateDocumentation {
     pub name: String,
     pub version: String,
     pub description: Option<String>,
+    /// Crate-level documentation, as markdown
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub docs: Option<String>,
     pub readme: Option<String>,
+    /// Where the README was read from: `local_registry`, `crates_io` (rendered
+    /// by crates.io, converted back to markdown) or `crate_archive`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub readme_source: Option<String>,
     pub modules: Vec<String>,
     pub items: Vec<DocumentationItem>,
+    /// `rustdoc_json`, or `html` for releases docs.rs has no rustdoc JSON for
+    pub source: String,
+    /// Format version of the rustdoc JSON the documentation was read from
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format_version: Option<u32>,
 }
 
-/// An item in the crate documentation (function, struct, etc.)
+/// Documentation of a single item, such as `tokio::sync::Mutex`
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemDocumentation {
+    pub crate_name: String,
+    pub version: String,
+    pub path: String,
+    pub name: String,
+    pub kind: String,
+    pub signature: Option<String>,
+    /// The full doc comment, as markdown; read from a docs.rs page, followed by
+    /// the rest of the page: methods, impls and so on
+    pub docs: Option<String>,
+    pub deprecation: Option<String>,
+    /// Inherent methods of a type, or the items of a trait
+    pub methods: Vec<DocumentationItem>,
+    /// Traits a type implements, or the implementations of a trait
+    pub trait_impls: Vec<TraitImplementation>,
+    /// `rustdoc_json`, or `html` when read from the item's docs.rs page, which
+    /// leaves `methods` and `trait_impls` empty
+    pub source: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub format_version: Option<u32>,
+}
+
+/// An impl block of a trait
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TraitImplementation {
+    pub trait_name: String,
+    pub for_type: String,
+    pub signature: String,
+    /// `explicit`, `auto` for auto traits like `Send`, or `blanket` for impls like `impl<T> From<T> for T`
+    pub kind: String,
+    pub description: Option<String>,
+    /// Names of the items defined in the impl block
+    pub items: Vec<String>,
+}
+
+/// Items of a crate matching a name or type signature query, best matches first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentationSearch {
+    pub crate_name: String,
+    pub version: String,
+    pub query: String,
+    pub results: Vec<DocumentationItem>,
+    /// `rustdoc_json`, or `html` for the item list of releases without it, which
+    /// has no signatures or summaries
+    pub source: String,
+}
+
+/// A version bump under Cargo's semver rules, where for `0.x` releases the
+/// minor version acts as the major one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SemverBump {
+    None,
+    Patch,
+    Minor,
+    Major,
+}
+
+/// A difference between the public APIs of two releases of a crate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiChange {
+    pub path: String,
+    pub kind: String,
+    /// `added`, `removed` or `changed`
+    pub change: String,
+    /// The bump the change requires
+    pub severity: SemverBump,
+    /// What changed and why it needs that bump
+    pub reason: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub old_signature: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub new_signature: Option<String>,
+}
+
+/// Public API differences between two releases of a crate, most severe first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiDiff {
+    pub crate_name: String,
+    pub old_version: String,
+    pub new_version: String,
+    /// `rustdoc_json`, or `html` when a release has no rustdoc JSON; docs.rs HTML
+    /// lists neither signatures nor associated items, so only added and removed
+    /// items are found then
+    pub source: String,
+    /// The bump the changes require
+    pub required_bump: SemverBump,
+    /// The bump between the two version numbers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub declared_bump: Option<SemverBump>,
+    pub added: usize,
+    pub removed: usize,
+    pub changed: usize,
+    pub changes: Vec<ApiChange>,
+}
+
+/// An item in the crate documentation (function, struct, etc.)
+///
+/// Signatures, generics, docs and deprecation are only known from rustdoc JSON.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct DocumentationItem {
     pub name: String,
     pub kind: String,
     pub path: String,
     pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub generics: Vec<String>,
+    /// The full doc comment, as markdown
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docs: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecation: Option<String>,
 }